@@ -0,0 +1,274 @@
+//! `cargo include-packed`: lists the assets that `include_packed` embedded in an
+//! executable or prepared in a build script's `OUT_DIR`.
+//!
+//! ```text
+//! cargo include-packed <OUT_DIR>
+//! cargo include-packed <EXECUTABLE> [--out-dir <OUT_DIR>]
+//! ```
+//!
+//! Given an `OUT_DIR`, it lists every asset the build script packed, along with
+//! artifacts that no current asset refers to, e.g. left behind by earlier builds.
+//! Given an executable, it lists the asset symbols linked into it, and with
+//! `--out-dir`, which assets they hold and which packed assets are missing from it.
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::SystemTime,
+};
+
+use object::{Object, ObjectSymbol};
+
+/// The prefix of every symbol and artifact written by the build script.
+const PREFIX: &str = "include_packed_";
+
+const USAGE: &str = "\
+Usage: cargo include-packed <OUT_DIR>
+       cargo include-packed <EXECUTABLE> [--out-dir <OUT_DIR>]
+
+Lists the assets that include_packed packed into a build script's OUT_DIR, or
+linked into an executable.";
+
+fn main() -> ExitCode {
+    match run(env::args_os().skip(1).collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the command with the arguments following the program name.
+fn run(mut args: Vec<OsString>) -> Result<(), String> {
+    // Cargo passes the subcommand's name as the first argument.
+    if args.first().is_some_and(|arg| arg == "include-packed") {
+        args.remove(0);
+    }
+    let mut target = None;
+    let mut out_dir = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.to_str() {
+            Some("-h" | "--help") => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            Some("--out-dir") => {
+                out_dir = Some(PathBuf::from(args.next().ok_or("--out-dir needs a value")?));
+            }
+            _ if target.is_none() => target = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument '{}'", arg.display())),
+        }
+    }
+    let target = target.ok_or("missing the OUT_DIR or executable to inspect")?;
+
+    if target.is_dir() {
+        let assets = read_out_dir(&target)?;
+        print_assets(&assets.current);
+        print_orphans(&target, &assets)?;
+        return Ok(());
+    }
+
+    let data = fs::read(&target).map_err(|err| format!("{}: {err}", target.display()))?;
+    let file = object::File::parse(&*data).map_err(|err| format!("{}: {err}", target.display()))?;
+    let symbols = asset_symbols(&file);
+    let Some(out_dir) = out_dir else {
+        println!("{:<48} {:>12}", "SYMBOL", "SIZE");
+        for (symbol, size) in &symbols {
+            println!("{symbol:<48} {size:>12}");
+        }
+        return Ok(());
+    };
+
+    let assets = read_out_dir(&out_dir)?;
+    let (linked, missing) = assets
+        .current
+        .into_iter()
+        .partition::<Vec<_>, _>(|meta| symbols.contains_key(meta.linked_symbol()));
+    print_assets(&linked);
+    for meta in &missing {
+        println!(
+            "not linked: '{}' (symbol {})",
+            meta.source(),
+            meta.linked_symbol()
+        );
+    }
+    Ok(())
+}
+
+/// An asset's `.meta` file, written by the build script for the macros.
+struct Meta {
+    /// The path of the file.
+    path: PathBuf,
+    /// The file's modification time.
+    modified: SystemTime,
+    /// The file's `key=value` entries.
+    entries: HashMap<String, String>,
+}
+
+impl Meta {
+    /// Returns the value of `key`, or an empty string if it is missing.
+    fn get(&self, key: &str) -> &str {
+        self.entries.get(key).map_or("", String::as_str)
+    }
+
+    /// Returns the asset's logical path, or its symbol if it was packed by a version
+    /// that didn't record it.
+    fn source(&self) -> &str {
+        self.entries
+            .get("source")
+            .map_or_else(|| self.get("symbol"), String::as_str)
+    }
+
+    /// Returns the symbol holding the asset's data, which is the bundle's for bundled
+    /// assets.
+    fn linked_symbol(&self) -> &str {
+        self.entries
+            .get("bundle")
+            .map_or_else(|| self.get("symbol"), String::as_str)
+    }
+
+    /// Returns the symbols of the artifacts the asset needs.
+    fn artifacts(&self) -> impl Iterator<Item = &str> {
+        [self.linked_symbol(), self.get("dictionary")]
+            .into_iter()
+            .filter(|symbol| !symbol.is_empty())
+    }
+}
+
+/// The assets found in an `OUT_DIR`.
+struct Assets {
+    /// The newest metadata of each asset, sorted by logical path.
+    current: Vec<Meta>,
+    /// Metadata superseded by a newer build of the same asset.
+    stale: Vec<Meta>,
+}
+
+/// Reads the metadata of every asset packed into `out_dir`.
+fn read_out_dir(out_dir: &Path) -> Result<Assets, String> {
+    let error = |err: std::io::Error| format!("{}: {err}", out_dir.display());
+    let mut metas = Vec::new();
+    for entry in fs::read_dir(out_dir).map_err(error)? {
+        let path = entry.map_err(error)?.path();
+        if path.extension().is_none_or(|extension| extension != "meta") {
+            continue;
+        }
+        let contents = fs::read_to_string(&path).map_err(error)?;
+        let modified = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(error)?;
+        let entries = contents
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.to_owned(), value.to_owned()))
+            .collect::<HashMap<_, _>>();
+        // Files skipped by include or exclude patterns have nothing packed.
+        if entries.contains_key("excluded") {
+            continue;
+        }
+        metas.push(Meta {
+            path,
+            modified,
+            entries,
+        });
+    }
+
+    // Every build of a changed asset writes a new metadata file, keyed by its
+    // modification time, so only the newest one is current.
+    metas.sort_by(|a, b| a.source().cmp(b.source()).then(b.modified.cmp(&a.modified)));
+    let mut current: Vec<Meta> = Vec::new();
+    let mut stale = Vec::new();
+    for meta in metas {
+        if current
+            .last()
+            .is_some_and(|last| last.source() == meta.source())
+        {
+            stale.push(meta);
+        } else {
+            current.push(meta);
+        }
+    }
+    Ok(Assets { current, stale })
+}
+
+/// Prints a table of `assets`.
+fn print_assets(assets: &[Meta]) {
+    println!(
+        "{:<48} {:>12} {:>12} {:<10} SOURCE",
+        "SYMBOL", "SIZE", "COMPRESSED", "ALGORITHM"
+    );
+    for meta in assets {
+        println!(
+            "{:<48} {:>12} {:>12} {:<10} {}",
+            meta.linked_symbol(),
+            meta.get("len"),
+            meta.get("compressed_len"),
+            meta.get("algorithm"),
+            meta.source()
+        );
+    }
+}
+
+/// Prints the stale metadata and the artifacts in `out_dir` that no current asset
+/// refers to.
+fn print_orphans(out_dir: &Path, assets: &Assets) -> Result<(), String> {
+    let referenced = assets
+        .current
+        .iter()
+        .flat_map(Meta::artifacts)
+        .collect::<HashSet<_>>();
+    for meta in &assets.stale {
+        println!(
+            "stale: {} (superseded by a newer build of '{}')",
+            meta.path.display(),
+            meta.source()
+        );
+    }
+
+    let mut orphans = Vec::new();
+    let error = |err: std::io::Error| format!("{}: {err}", out_dir.display());
+    for entry in fs::read_dir(out_dir).map_err(error)? {
+        let path = entry.map_err(error)?.path();
+        let (Some(stem), Some(extension)) = (
+            path.file_stem().and_then(|stem| stem.to_str()),
+            path.extension(),
+        ) else {
+            continue;
+        };
+        if stem.starts_with(PREFIX)
+            && ["o", "res", "bin"].iter().any(|known| extension == *known)
+            && !referenced.contains(stem)
+        {
+            orphans.push(path);
+        }
+    }
+    orphans.sort();
+    for path in orphans {
+        println!(
+            "orphaned: {} (no current asset refers to it)",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Returns the sizes of the asset symbols defined in `file`, by name.
+fn asset_symbols(file: &object::File<'_>) -> BTreeMap<String, u64> {
+    file.symbols()
+        .filter(|symbol| symbol.is_definition())
+        .filter_map(|symbol| {
+            let name = symbol.name().ok()?;
+            // Mach-O and 32-bit COFF prefix C symbol names with an underscore.
+            let name = name
+                .strip_prefix('_')
+                .filter(|name| name.starts_with(PREFIX))
+                .unwrap_or(name);
+            name.starts_with(PREFIX)
+                .then(|| (name.to_owned(), symbol.size()))
+        })
+        .collect()
+}
@@ -0,0 +1,22 @@
+//! Decodes arbitrary bytes with the decoder behind `include_packed!`, for an algorithm
+//! chosen by the input.
+#![no_main]
+
+use include_packed::fuzzing::{Algorithm, Dictionary, decode_all};
+use libfuzzer_sys::fuzz_target;
+
+/// Every algorithm the runtime can decode.
+const ALGORITHMS: [Algorithm; 4] = [
+    Algorithm::Zstd,
+    Algorithm::Lz4,
+    Algorithm::Brotli,
+    Algorithm::Store,
+];
+
+fuzz_target!(|input: &[u8]| {
+    let Some((&selector, data)) = input.split_first() else {
+        return;
+    };
+    let algorithm = ALGORITHMS[usize::from(selector) % ALGORITHMS.len()];
+    let _ = decode_all(algorithm, Dictionary::NONE, data);
+});
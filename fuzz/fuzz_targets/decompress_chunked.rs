@@ -0,0 +1,33 @@
+//! Decodes arbitrary bytes with the chunked decoder behind cancellable decompression,
+//! cancelling after a number of chunks chosen by the input.
+#![no_main]
+
+use include_packed::fuzzing::{Algorithm, Dictionary};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &[u8]| {
+    let Some((&chunks, data)) = input.split_first() else {
+        return;
+    };
+    let mut remaining = chunks;
+    let chunked = include_packed::fuzzing::decode_chunked(
+        Algorithm::Zstd,
+        Dictionary::NONE,
+        data,
+        4096,
+        || {
+            remaining = remaining.saturating_sub(1);
+            remaining > 0
+        },
+    );
+
+    // A run that was not cancelled must agree with the one-shot decoder.
+    if let Ok(Some(output)) = chunked {
+        assert_eq!(
+            include_packed::fuzzing::decode_all(Algorithm::Zstd, Dictionary::NONE, data)
+                .ok()
+                .as_ref(),
+            Some(&output)
+        );
+    }
+});
@@ -0,0 +1,25 @@
+//! Parses the decoded size from arbitrary bytes, then decodes into a buffer of exactly
+//! that size, as the memory-mapped decompression path does.
+#![no_main]
+
+use include_packed::fuzzing::{Algorithm, Dictionary};
+use libfuzzer_sys::fuzz_target;
+
+/// Upper bound on buffer allocations, so that huge claimed sizes don't abort the run.
+const MAX_LEN: usize = 1 << 24;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(len) = include_packed::fuzzing::decoded_len(Algorithm::Zstd, Dictionary::NONE, data)
+    else {
+        return;
+    };
+    if len > MAX_LEN {
+        return;
+    }
+    let mut output = vec![0; len];
+    if let Ok(written) =
+        include_packed::fuzzing::decode_into(Algorithm::Zstd, Dictionary::NONE, data, &mut output)
+    {
+        assert!(written <= len);
+    }
+});
@@ -0,0 +1,15 @@
+//! Parses arbitrary bytes as a patch pack, then decodes every entry it accepted.
+#![no_main]
+
+use include_packed::fuzzing::{Algorithm, Dictionary, PatchPack, decode_all};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(pack) = PatchPack::from_bytes(data.to_vec()) else {
+        return;
+    };
+    for path in pack.paths() {
+        let compressed = pack.get(path).expect("listed path is missing");
+        let _ = decode_all(Algorithm::Zstd, Dictionary::NONE, compressed);
+    }
+});
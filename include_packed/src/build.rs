@@ -1,17 +1,31 @@
 //! Build-script helpers for `include_packed`.
 use std::{
-    collections::hash_map::DefaultHasher,
-    env, fs,
+    collections::{HashMap, HashSet, hash_map::DefaultHasher},
+    env, fmt, fs,
     hash::{Hash, Hasher},
-    path::{Path, PathBuf},
+    num::NonZeroUsize,
+    path::{Component, Path, PathBuf},
+    process::Command,
     string::FromUtf8Error,
+    sync::{
+        Arc, Mutex, OnceLock, PoisonError,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
+use ar_archive_writer::{ArchiveKind, NewArchiveMember, write_archive_to_stream};
 use object::{
-    Architecture, BinaryFormat, Endianness, SymbolFlags, SymbolKind, SymbolScope,
+    Architecture, BinaryFormat, Endianness, SectionFlags, SectionKind, SymbolFlags, SymbolKind,
+    SymbolScope, elf,
     write::{Object, StandardSection, Symbol, SymbolSection},
 };
 
+#[cfg(feature = "encryption")]
+use crate::runtime::crypto::{self, Cipher};
+use crate::runtime::{Algorithm, codec};
+
 //
 // ==================== PUBLIC BUILDER API ====================
 //
@@ -30,163 +44,4297 @@ use object::{
 /// ```
 #[derive(Debug)]
 pub struct Config {
-    path: PathBuf,
+    paths: Vec<PathBuf>,
+    archive: bool,
+    #[cfg(feature = "remote")]
+    remote: Option<(String, String)>,
+    root: Option<PathBuf>,
+    aliases: Vec<(String, String)>,
     level: i32,
+    debug_level: Option<i32>,
+    release_level: Option<i32>,
+    debug_algorithm: Option<Algorithm>,
+    auto: Option<Duration>,
+    windows_resources: bool,
+    macho_section: Option<(String, String)>,
+    hidden_symbols: bool,
+    link_section: Option<String>,
+    retain_sections: bool,
+    link_targets: Vec<LinkTarget>,
+    library: bool,
+    verify_roundtrip: bool,
+    provenance: bool,
+    symbol_namespace: Option<String>,
+    obfuscate: bool,
+    algorithm: Algorithm,
+    store_extensions: Vec<String>,
+    files: Vec<(String, FileConfig)>,
+    includes: Vec<String>,
+    excludes: Vec<String>,
+    follow_symlinks: bool,
+    lfs_pull: bool,
+    cache_dir: Option<PathBuf>,
+    jobs: Option<usize>,
+    dictionary_size: Option<usize>,
+    chunk_size: Option<usize>,
+    align: usize,
+    zstd: ZstdOptions,
+    wasm_fetch: Option<(PathBuf, String)>,
+    #[cfg(feature = "encryption")]
+    cipher: Option<Cipher>,
+    #[cfg(feature = "checksums")]
+    verify: bool,
+    #[cfg(feature = "signatures")]
+    signing_key: Option<ed25519_dalek::SigningKey>,
+    dev_passthrough: bool,
+    bundle: bool,
+    static_archive: bool,
+    report: Option<PathBuf>,
+    generate_module: bool,
+    max_total_compressed: Option<u64>,
+    max_asset_size: Option<u64>,
+    transforms: Vec<Transform>,
+    transcodes: Vec<(String, Encoding)>,
+    #[cfg(feature = "image-pipeline")]
+    image_transforms: Vec<(String, ImageTransform)>,
+}
+
+/// A function rewriting assets before they are compressed, added with
+/// [`Config::transform`].
+struct Transform(Box<TransformFn>);
+
+/// The signature of the functions passed to [`Config::transform`].
+type TransformFn = dyn Fn(&Path, Vec<u8>) -> Vec<u8> + Send + Sync;
+
+/// A legacy text encoding that assets are converted from, with [`Config::transcode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Encoding {
+    /// UTF-16 in little-endian byte order, as written by most Windows tools.
+    Utf16Le,
+    /// UTF-16 in big-endian byte order.
+    Utf16Be,
+    /// ISO 8859-1, whose bytes are the first 256 Unicode code points.
+    Latin1,
+}
+
+impl Encoding {
+    /// Converts `content` from this encoding to UTF-8, dropping a leading byte order
+    /// mark. Returns `None` if `content` is not valid in this encoding.
+    fn decode(self, content: &[u8]) -> Option<String> {
+        let text = match self {
+            Self::Utf16Le | Self::Utf16Be => {
+                if !content.len().is_multiple_of(2) {
+                    return None;
+                }
+                let units = content.chunks_exact(2).map(|unit| {
+                    let unit = [unit[0], unit[1]];
+                    if self == Self::Utf16Le {
+                        u16::from_le_bytes(unit)
+                    } else {
+                        u16::from_be_bytes(unit)
+                    }
+                });
+                char::decode_utf16(units)
+                    .collect::<std::result::Result<String, _>>()
+                    .ok()?
+            }
+            Self::Latin1 => content.iter().copied().map(char::from).collect(),
+        };
+        Some(match text.strip_prefix('\u{feff}') {
+            Some(text) => text.to_owned(),
+            None => text,
+        })
+    }
+}
+
+/// A built-in image transform, applied with [`Config::image_transform`].
+#[cfg(feature = "image-pipeline")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ImageTransform {
+    /// Re-encodes images as QOI, which decodes several times faster than PNG at a
+    /// similar size, and compresses further with zstd.
+    Qoi,
+    /// Re-encodes images as lossless WebP, which is usually smaller than PNG.
+    WebP,
+    /// Downscales images larger than the given size in either dimension, preserving
+    /// their aspect ratio and format, e.g. to drop mip levels a platform never uses.
+    MaxDimension(u32),
+}
+
+#[cfg(feature = "image-pipeline")]
+impl ImageTransform {
+    /// Applies the transform to the encoded image `content`.
+    fn apply(self, content: &[u8]) -> image::ImageResult<Vec<u8>> {
+        use image::{DynamicImage, ImageFormat, imageops::FilterType};
+
+        let format = image::guess_format(content)?;
+        let mut image = image::load_from_memory_with_format(content, format)?;
+        let format = match self {
+            Self::Qoi | Self::WebP => {
+                // Both encoders only take 8-bit RGB or RGBA.
+                if !matches!(
+                    image,
+                    DynamicImage::ImageRgb8(_) | DynamicImage::ImageRgba8(_)
+                ) {
+                    image = DynamicImage::ImageRgba8(image.to_rgba8());
+                }
+                if self == Self::Qoi {
+                    ImageFormat::Qoi
+                } else {
+                    ImageFormat::WebP
+                }
+            }
+            Self::MaxDimension(max) => {
+                if image.width() <= max && image.height() <= max {
+                    return Ok(content.to_vec());
+                }
+                image = image.resize(max, max, FilterType::Lanczos3);
+                format
+            }
+        };
+        let mut encoded = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut encoded, format)?;
+        Ok(encoded.into_inner())
+    }
+}
+
+impl fmt::Debug for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Transform(..)")
+    }
+}
+
+/// Advanced zstd encoder parameters, set through the `zstd_*` methods of [`Config`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct ZstdOptions {
+    workers: u32,
+    window_log: Option<u32>,
+    long_distance_matching: bool,
+    target_block_size: Option<u32>,
+}
+
+impl ZstdOptions {
+    /// Applies the parameters that differ from zstd's defaults to `compressor`.
+    fn apply(&self, compressor: &mut zstd::bulk::Compressor<'_>) -> std::io::Result<()> {
+        use zstd::zstd_safe::CParameter;
+
+        if self.workers > 0 {
+            compressor.set_parameter(CParameter::NbWorkers(self.workers))?;
+        }
+        if let Some(log) = self.window_log {
+            compressor.set_parameter(CParameter::WindowLog(log))?;
+        }
+        if self.long_distance_matching {
+            compressor.set_parameter(CParameter::EnableLongDistanceMatching(true))?;
+        }
+        if let Some(size) = self.target_block_size {
+            compressor.set_parameter(CParameter::TargetCBlockSize(size))?;
+        }
+        Ok(())
+    }
+
+    /// Returns these options with a window spanning `len` bytes, so that a patch can
+    /// reference its whole base, as `zstd --patch-from` does.
+    fn for_patch(mut self, len: usize) -> Self {
+        let log = usize::BITS - len.saturating_sub(1).leading_zeros();
+        self.window_log = Some(log.clamp(10, 30).max(self.window_log.unwrap_or(0)));
+        self
+    }
+}
+
+/// A Cargo target kind that packed assets are linked into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum LinkTarget {
+    /// A single `[[bin]]` target, by name.
+    Bin(String),
+    /// Every binary target.
+    Bins,
+    /// Every example target.
+    Examples,
+    /// Every benchmark target.
+    Benches,
+}
+
+impl LinkTarget {
+    /// Returns the `cargo:` directive that passes a linker argument to this target.
+    fn directive(&self) -> String {
+        match self {
+            Self::Bin(name) => format!("rustc-link-arg-bin={name}="),
+            Self::Bins => "rustc-link-arg-bins=".into(),
+            Self::Examples => "rustc-link-arg-examples=".into(),
+            Self::Benches => "rustc-link-arg-benches=".into(),
+        }
+    }
+}
+
+impl Config {
+    /// Creates a new configuration for a given asset path.
+    ///
+    /// The path can be a single file or a directory and should be relative to
+    /// the crate root (`CARGO_MANIFEST_DIR`), or to the [`root`](Self::root) if one is set.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            paths: vec![path.as_ref().to_path_buf()],
+            archive: false,
+            #[cfg(feature = "remote")]
+            remote: None,
+            root: None,
+            aliases: Vec::new(),
+            level: DEFAULT_COMPRESSION_LEVEL,
+            debug_level: None,
+            release_level: None,
+            debug_algorithm: None,
+            auto: None,
+            windows_resources: false,
+            macho_section: None,
+            hidden_symbols: false,
+            link_section: None,
+            retain_sections: false,
+            link_targets: Vec::new(),
+            library: false,
+            verify_roundtrip: false,
+            provenance: false,
+            symbol_namespace: None,
+            obfuscate: false,
+            algorithm: Algorithm::Zstd,
+            store_extensions: DEFAULT_STORE_EXTENSIONS
+                .iter()
+                .map(|extension| (*extension).to_owned())
+                .collect(),
+            files: Vec::new(),
+            includes: Vec::new(),
+            excludes: Vec::new(),
+            follow_symlinks: true,
+            lfs_pull: false,
+            cache_dir: None,
+            jobs: None,
+            dictionary_size: None,
+            chunk_size: None,
+            align: 1,
+            zstd: ZstdOptions::default(),
+            wasm_fetch: None,
+            #[cfg(feature = "encryption")]
+            cipher: None,
+            #[cfg(feature = "checksums")]
+            verify: false,
+            #[cfg(feature = "signatures")]
+            signing_key: None,
+            dev_passthrough: false,
+            bundle: false,
+            static_archive: false,
+            report: None,
+            generate_module: false,
+            max_total_compressed: None,
+            max_asset_size: None,
+            transforms: Vec::new(),
+            transcodes: Vec::new(),
+            #[cfg(feature = "image-pipeline")]
+            image_transforms: Vec::new(),
+        }
+    }
+
+    /// Creates a configuration that downloads the file at `url` and packs it, failing
+    /// the build unless its SHA-256 hash is `sha256`, given as 64 hexadecimal digits.
+    ///
+    /// This suits files too large for the repository, such as ML models. The asset is
+    /// addressed by the last segment of the URL's path, e.g.
+    /// `include_packed!("model-v3.bin")` for `"https://cdn.example.com/model-v3.bin"`.
+    /// Downloads are keyed by their hash and kept in the
+    /// [compression cache](Self::cache_dir) if one is set, or in `OUT_DIR` otherwise,
+    /// so the file is only downloaded again once the hash changes. The download is
+    /// resolved like an asset below a [`root`](Self::root), so building fails if one is
+    /// set as well.
+    ///
+    /// ```no_run
+    /// // in build.rs
+    /// include_packed::Config::remote(
+    ///     "https://cdn.example.com/model-v3.bin",
+    ///     "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08",
+    /// )
+    /// .build()
+    /// .expect("Failed to pack model");
+    /// ```
+    #[cfg(feature = "remote")]
+    #[must_use]
+    pub fn remote(url: impl Into<String>, sha256: impl Into<String>) -> Self {
+        let url = url.into();
+        let path = url.split(['?', '#']).next().unwrap_or_default();
+        let name = match path.rsplit('/').next() {
+            Some(name) if !name.is_empty() && !path.ends_with(':') => name.to_owned(),
+            _ => "remote".to_owned(),
+        };
+        Self {
+            remote: Some((url, sha256.into())),
+            ..Self::new(name)
+        }
+    }
+
+    /// Creates a configuration that packs each member of the zip or tar archive at
+    /// `archive` as an individual asset, without extracting it to disk.
+    ///
+    /// Members are addressed by the archive's path joined with their path inside it,
+    /// e.g. `include_packed!("assets.zip/textures/hero.png")`. Archives ending in `.zip`
+    /// may store their members uncompressed or deflated, and tar archives may be
+    /// gzip-compressed if they end in `.tar.gz` or `.tgz`. Members are read into memory
+    /// once per build, and take the archive's modification time. Paths added with
+    /// [`add_path`](Self::add_path) must be archives as well. Unlike files on disk,
+    /// members can't be listed with `include_packed_dir!` or read through
+    /// [`dev_passthrough`](Self::dev_passthrough).
+    #[cfg(feature = "archives")]
+    #[must_use]
+    pub fn from_archive(archive: impl AsRef<Path>) -> Self {
+        Self {
+            archive: true,
+            ..Self::new(archive)
+        }
+    }
+
+    /// Adds another file or directory to pack with the same options, resolved like the
+    /// path passed to [`new`](Self::new):
+    ///
+    /// ```no_run
+    /// // in build.rs
+    /// include_packed::Config::new("assets")
+    ///     .add_path("generated")
+    ///     .add_path("../shared_assets/fonts")
+    ///     .build()
+    ///     .expect("Failed to pack assets");
+    /// ```
+    ///
+    /// Packing the paths in one [`build`](Self::build) call lets them share a trained
+    /// [dictionary](Self::train_dictionary), a [bundle](Self::bundle), and a
+    /// [budget](Self::max_total_compressed), and identical files among them are
+    /// embedded once. Files reached through several of the paths are packed once.
+    #[must_use]
+    pub fn add_path(mut self, path: impl AsRef<Path>) -> Self {
+        self.paths.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Resolves the configured paths relative to `root` instead of the crate root, e.g.
+    /// for assets at the workspace root or in a git submodule.
+    ///
+    /// `root` itself is relative to the crate root, or absolute. The macros then accept
+    /// the assets' paths relative to `root` as well, without `../` prefixes, and symbol
+    /// names are derived from those paths, so they don't depend on where the crate is
+    /// located. Paths that exist in the crate itself take precedence over those below
+    /// `root`, and include and exclude patterns match paths relative to `root`.
+    ///
+    /// ```no_run
+    /// // in build.rs of a crate two levels below the workspace root
+    /// include_packed::Config::new("assets")
+    ///     .root("../..")
+    ///     .build()
+    ///     .expect("Failed to pack assets");
+    /// ```
+    #[must_use]
+    pub fn root(mut self, root: impl AsRef<Path>) -> Self {
+        self.root = Some(root.as_ref().to_path_buf());
+        self
+    }
+
+    /// Registers `name` as an alias for the asset at the logical path `path`, which the
+    /// macros then accept as `alias = "name"` instead of the path, e.g.
+    /// `include_packed!(alias = "logo")`:
+    ///
+    /// ```no_run
+    /// // in build.rs
+    /// include_packed::Config::new("assets")
+    ///     .alias("logo", "assets/branding/logo_final_v3.png")
+    ///     .build()
+    ///     .expect("Failed to pack assets");
+    /// ```
+    ///
+    /// Renaming the file then only requires updating the alias. [`build`](Self::build)
+    /// fails if `path` isn't one of the assets it packs, or if `name` is empty or
+    /// contains whitespace. Aliases registered later replace earlier ones of the same
+    /// name.
+    #[must_use]
+    pub fn alias(mut self, name: impl Into<String>, path: impl Into<String>) -> Self {
+        self.aliases.push((name.into(), path.into()));
+        self
+    }
+
+    /// Sets the zstd compression level (1-21).
+    ///
+    /// Higher levels provide better compression at the cost of slower build times.
+    /// If not set, a default level of `6` is used.
+    ///
+    /// The `INCLUDE_PACKED_LEVEL` environment variable overrides this level without
+    /// editing the build script, and `INCLUDE_PACKED_LEVEL_RELEASE` or
+    /// `INCLUDE_PACKED_LEVEL_DEBUG` override it for the respective profile only, taking
+    /// precedence over the former. Levels set with
+    /// [`override_level`](Self::override_level) still apply to their assets.
+    #[must_use]
+    pub const fn level(mut self, level: i32) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Sets the compression level used instead of [`level`](Self::level) in debug
+    /// builds, i.e. when Cargo's `PROFILE` is `debug`.
+    ///
+    /// A low level keeps debug builds fast, while release builds still compress
+    /// thoroughly. The `INCLUDE_PACKED_LEVEL` environment variables take precedence.
+    #[must_use]
+    pub const fn debug_level(mut self, level: i32) -> Self {
+        self.debug_level = Some(level);
+        self
+    }
+
+    /// Sets the compression level used instead of [`level`](Self::level) in release
+    /// builds, i.e. when Cargo's `PROFILE` is `release`.
+    ///
+    /// The `INCLUDE_PACKED_LEVEL` environment variables take precedence.
+    #[must_use]
+    pub const fn release_level(mut self, level: i32) -> Self {
+        self.release_level = Some(level);
+        self
+    }
+
+    /// Overrides the compression level for assets matching a glob pattern.
+    ///
+    /// Patterns are matched against each asset's path relative to the crate root, e.g.
+    /// `"assets/video/**"` or `"assets/**/*.json"`. `*` does not match across `/`, while
+    /// `**` matches any number of directories. This can be called repeatedly to trade
+    /// build time against size per asset class, and the last matching override wins.
+    /// Assets that match no pattern use [`level`](Self::level). This is a shorthand for
+    /// [`file`](Self::file) with only [`FileConfig::level`] set.
+    #[must_use]
+    pub fn override_level(self, pattern: impl Into<String>, level: i32) -> Self {
+        self.file(pattern, |file| file.level(level))
+    }
+
+    /// Packs the assets matching a glob pattern as binary patches against the asset
+    /// `base`, a path relative to the crate root like the pattern.
+    ///
+    /// Variants of a large asset, such as quality tiers or A/B variants of a model, then
+    /// only cost the difference to the base in binary size. Each variant is compressed
+    /// with zstd referring back to the base's raw contents, and decompressing it
+    /// decompresses the base first, so the variant to use can be picked at runtime. The
+    /// base must be packed by the same configuration, and is packed as configured
+    /// itself, even if it matches the pattern. Patches need the `std` feature to
+    /// decompress, as `ruzstd` cannot load raw contents. This is a shorthand for
+    /// [`file`](Self::file) with only [`FileConfig::delta_from`] set.
+    ///
+    /// ```no_run
+    /// // in build.rs
+    /// include_packed::Config::new("models")
+    ///     .delta("models/low/*.bin", "models/high.bin")
+    ///     .build()
+    ///     .expect("Failed to pack models");
+    /// ```
+    #[must_use]
+    pub fn delta(self, pattern: impl Into<String>, base: impl Into<String>) -> Self {
+        self.file(pattern, |file| file.delta_from(base))
+    }
+
+    /// Overrides options for the assets matching a glob pattern, which is matched like
+    /// the patterns of [`override_level`](Self::override_level):
+    ///
+    /// ```no_run
+    /// // in build.rs
+    /// include_packed::Config::new("assets")
+    ///     .file("assets/db.bin", |file| file.level(19).align(64))
+    ///     .file("assets/music/*.flac", |file| {
+    ///         file.algorithm(include_packed::Algorithm::Store)
+    ///     })
+    ///     .build()
+    ///     .expect("Failed to pack assets");
+    /// ```
+    ///
+    /// Options a [`FileConfig`] doesn't set keep the values of this configuration, and
+    /// when several patterns match an asset, the options set by later calls win.
+    #[must_use]
+    pub fn file(
+        mut self,
+        pattern: impl Into<String>,
+        configure: impl FnOnce(FileConfig) -> FileConfig,
+    ) -> Self {
+        self.files
+            .push((pattern.into(), configure(FileConfig::default())));
+        self
+    }
+
+    /// Only packs the assets matching the glob `pattern`, e.g. `"assets/**/*.{png,ogg}"`.
+    ///
+    /// Patterns are matched like those of [`override_level`](Self::override_level), and
+    /// may list alternatives in braces. This can be called repeatedly, and assets
+    /// matching any of the patterns are packed. Without include patterns, every asset
+    /// below the configured path is packed.
+    ///
+    /// Assets that aren't packed are left out of `include_packed_dir!`,
+    /// `include_packed_glob!`, and the `PackedAssets` derive, and including them by path
+    /// fails to compile.
+    #[must_use]
+    pub fn include(mut self, pattern: impl Into<String>) -> Self {
+        self.includes.push(pattern.into());
+        self
+    }
+
+    /// Skips the assets matching the glob `pattern`, e.g. `"**/*.psd"` or
+    /// `"**/.DS_Store"`, even if they match an [`include`](Self::include) pattern.
+    ///
+    /// Patterns are matched like those of [`include`](Self::include). This can be
+    /// called repeatedly to skip several kinds of files.
+    #[must_use]
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.excludes.push(pattern.into());
+        self
+    }
+
+    /// Rewrites the contents of every asset with `transform` before it is compressed.
+    ///
+    /// The function receives the asset's logical path, i.e. the configured path joined
+    /// with the file's path below it, and its contents, and returns the contents to
+    /// pack, e.g. minified scripts or images stripped of metadata. Transforms added by
+    /// repeated calls run in order. The sizes, checksums, and verification of assets
+    /// apply to the transformed contents, and [`dev_passthrough`](Self::dev_passthrough)
+    /// is disabled, since it would read the untransformed files. Functions that can fail
+    /// should panic, which fails the build script with their message.
+    #[must_use]
+    pub fn transform(
+        mut self,
+        transform: impl Fn(&Path, Vec<u8>) -> Vec<u8> + Send + Sync + 'static,
+    ) -> Self {
+        self.transforms.push(Transform(Box::new(transform)));
+        self
+    }
+
+    /// Converts the text assets matching the glob `pattern`, e.g.
+    /// `"assets/locales/**/*.txt"`, from `encoding` to UTF-8 before they are compressed.
+    ///
+    /// Legacy files, such as UTF-16 or Latin-1 localizations, can then be included with
+    /// `include_packed_str!` directly. Patterns are matched against the assets' logical
+    /// paths like those of [`override_level`](Self::override_level), and a leading byte
+    /// order mark is dropped. Transcoding runs before all other transforms, and like
+    /// those disables [`dev_passthrough`](Self::dev_passthrough). Assets that are not
+    /// valid in `encoding` fail the build.
+    #[must_use]
+    pub fn transcode(mut self, pattern: impl Into<String>, encoding: Encoding) -> Self {
+        self.transcodes.push((pattern.into(), encoding));
+        self
+    }
+
+    /// Applies the built-in image `transform` to the assets matching the glob `pattern`,
+    /// e.g. `"assets/textures/**/*.png"`.
+    ///
+    /// Patterns are matched against the assets' logical paths like those of
+    /// [`override_level`](Self::override_level). Re-encoded assets keep their paths, so
+    /// the code loading them must expect the new format. Image transforms run in the
+    /// order they were added, before those of [`transform`](Self::transform), and like
+    /// those disable [`dev_passthrough`](Self::dev_passthrough). PNG, JPEG, QOI, and
+    /// WebP images can be read.
+    #[cfg(feature = "image-pipeline")]
+    #[must_use]
+    pub fn image_transform(
+        mut self,
+        pattern: impl Into<String>,
+        transform: ImageTransform,
+    ) -> Self {
+        self.image_transforms.push((pattern.into(), transform));
+        self
+    }
+
+    /// Sets whether symbolic links below the configured path are followed.
+    ///
+    /// Monorepos often link shared asset folders into each crate, so links are followed
+    /// by default. Every file and directory is packed at most once, even if it is
+    /// reachable through several links, and cycles of links are detected. Links whose
+    /// targets don't exist are skipped. When links aren't followed, they are skipped as
+    /// well, and left out of `include_packed_dir!` and the `PackedAssets` derive. The
+    /// configured path itself is always resolved.
+    #[must_use]
+    pub const fn follow_symlinks(mut self, follow: bool) -> Self {
+        self.follow_symlinks = follow;
+        self
+    }
+
+    /// Sets whether assets that are unfetched Git LFS pointers are fetched with
+    /// `git lfs pull`.
+    ///
+    /// A checkout without Git LFS, or with `GIT_LFS_SKIP_SMUDGE` set, leaves a small text
+    /// file pointing to the real contents in place of each LFS-tracked file. Packing it
+    /// would only fail at runtime, so the build fails instead, naming the asset. With
+    /// this option, `git lfs pull` is first run for the asset, and the build only fails
+    /// if that doesn't replace the pointer. Defaults to `false`.
+    #[must_use]
+    pub const fn lfs_pull(mut self, pull: bool) -> Self {
+        self.lfs_pull = pull;
+        self
+    }
+
+    /// Caches compressed data in `dir`, keyed by content hash, algorithm, and level.
+    ///
+    /// Assets whose contents were compressed before with the same settings are read
+    /// from the cache instead of being compressed again, even across `cargo clean` or
+    /// branch switches. Pointing CI at a persistent directory keeps packing large asset
+    /// trees from dominating build times. The directory is created if it doesn't exist,
+    /// and a relative path is resolved against the working directory, which is the crate
+    /// root in build scripts. Entries are never evicted, so the directory can be cleared
+    /// at any time.
+    #[must_use]
+    pub fn cache_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.cache_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Trains a shared zstd dictionary of up to `max_size` bytes across all assets.
+    ///
+    /// Per-file compression barely shrinks small files, such as thousands of JSON or
+    /// translation files, because each one is too short to build up useful context. A
+    /// dictionary trained on all of them captures their shared structure. It is embedded
+    /// once, and every asset is compressed and decompressed with it, which can halve the
+    /// total embedded size for small-file workloads. A `max_size` of around 100 KiB is a
+    /// good start, and the samples should total roughly a hundred times that.
+    ///
+    /// This only applies to [`Algorithm::Zstd`].
+    #[must_use]
+    pub const fn train_dictionary(mut self, max_size: usize) -> Self {
+        self.dictionary_size = Some(max_size);
+        self
+    }
+
+    /// Compresses each zstd asset with `workers` additional threads.
+    ///
+    /// zstd splits large inputs into jobs that its workers compress concurrently, which
+    /// speeds up packing a few very large assets where [`jobs`](Self::jobs) can't help.
+    /// The output is the same for any non-zero number of workers, but differs slightly
+    /// from single-threaded compression. Defaults to `0`, which compresses on the thread
+    /// packing the asset.
+    #[must_use]
+    pub const fn zstd_workers(mut self, workers: u32) -> Self {
+        self.zstd.workers = workers;
+        self
+    }
+
+    /// Sets the zstd window size to `2^log` bytes, from `10` up to `31`.
+    ///
+    /// The window bounds how far back zstd looks for repeated data. The default depends
+    /// on the level and reaches 8 MiB at level 19, so raising it improves the ratio of
+    /// large assets with distant repetition, like game archives or disk images. Decoding
+    /// needs a buffer as large as the window, or the asset, whichever is smaller. Invalid
+    /// values make [`build`](Self::build) fail.
+    #[must_use]
+    pub const fn zstd_window_log(mut self, log: u32) -> Self {
+        self.zstd.window_log = Some(log);
+        self
+    }
+
+    /// Enables zstd's long-distance matching.
+    ///
+    /// Long-distance matching finds repetitions across a large window cheaply, which
+    /// complements [`zstd_window_log`](Self::zstd_window_log) for assets of hundreds of
+    /// megabytes. Without an explicit window log, it raises the window to 128 MiB.
+    /// Defaults to `false`.
+    #[must_use]
+    pub const fn zstd_long_distance_matching(mut self, enabled: bool) -> Self {
+        self.zstd.long_distance_matching = enabled;
+        self
+    }
+
+    /// Asks zstd to emit compressed blocks of about `size` bytes.
+    ///
+    /// Smaller blocks let streaming readers, such as
+    /// [`include_packed_reader!`](`crate::include_packed_reader`), produce their first
+    /// bytes sooner, at a small cost in ratio. This is a target rather than a limit.
+    /// Defaults to zstd's own block sizing.
+    #[must_use]
+    pub const fn zstd_target_block_size(mut self, size: u32) -> Self {
+        self.zstd.target_block_size = Some(size);
+        self
+    }
+
+    /// Caps the number of threads that compress assets in parallel.
+    ///
+    /// Assets are compressed and written concurrently, which cuts build script wall time
+    /// for trees with many files. Defaults to the `NUM_JOBS` Cargo passes to build
+    /// scripts, or the available parallelism outside of them. A value of `1` packs
+    /// assets sequentially.
+    #[must_use]
+    pub const fn jobs(mut self, jobs: usize) -> Self {
+        self.jobs = Some(jobs);
+        self
+    }
+
+    /// Embeds assets as Windows `RT_RCDATA` resources instead of COFF data symbols.
+    ///
+    /// Some packaging, code-signing, and antivirus tooling handles PE resources better
+    /// than large data symbols. When enabled and targeting Windows, each asset is written
+    /// to a `.res` file that the linker merges into the executable's resource section, and
+    /// [`include_packed!`](`crate::include_packed`) resolves the data through the Win32
+    /// resource API at runtime. The runtime API is identical in both modes.
+    ///
+    /// `.res` inputs are supported by the MSVC linker and by `lld-link`. This setting is
+    /// ignored on other targets. Defaults to `false`.
+    #[must_use]
+    pub const fn windows_resources(mut self, enabled: bool) -> Self {
+        self.windows_resources = enabled;
+        self
+    }
+
+    /// Places asset data in a custom Mach-O segment and section on Apple targets.
+    ///
+    /// By default, assets land in `__TEXT,__const`. Choosing a dedicated location such
+    /// as `__ASSETS,__data` keeps them apart from code for code-signing layouts and for
+    /// tools that scan specific segments. Both names are limited to 16 bytes. This
+    /// setting is ignored for other object formats.
+    #[must_use]
+    pub fn macho_section(mut self, segment: impl Into<String>, section: impl Into<String>) -> Self {
+        self.macho_section = Some((segment.into(), section.into()));
+        self
+    }
+
+    /// Gives asset symbols hidden visibility instead of exporting them.
+    ///
+    /// The generated object files contain no debug sections, so asset bytes never end up
+    /// in DWARF or PDB output. Their symbols, however, are exported by default, which puts
+    /// them in dynamic symbol tables, Mach-O export tries, and linker maps. Hidden symbols
+    /// still link normally but are neither exported nor retained by `strip` or
+    /// `objcopy --strip-unneeded`, so split-debuginfo flows only ever see the section data
+    /// once, in the binary itself. Defaults to `false`.
+    #[must_use]
+    pub const fn hidden_symbols(mut self, hidden: bool) -> Self {
+        self.hidden_symbols = hidden;
+        self
+    }
+
+    /// Places each asset in an ELF input section named `{prefix}.{symbol}`.
+    ///
+    /// This is intended for firmware and other targets built with custom linker scripts.
+    /// Every asset keeps its own input section, so `--gc-sections` can still discard
+    /// unused assets, while a single wildcard places all of them in a dedicated output
+    /// section, e.g. one located in external flash:
+    ///
+    /// ```text
+    /// .assets : {
+    ///     KEEP(*(.assets.*)) /* omit KEEP to let --gc-sections drop unused assets */
+    /// } > EXTERNAL_FLASH
+    /// ```
+    ///
+    /// This setting is ignored for non-ELF targets.
+    #[must_use]
+    pub fn link_section(mut self, prefix: impl Into<String>) -> Self {
+        self.link_section = Some(prefix.into());
+        self
+    }
+
+    /// Marks asset sections with `SHF_GNU_RETAIN` so `--gc-sections` never discards them.
+    ///
+    /// This has the same effect as wrapping the sections in `KEEP(...)` in a linker
+    /// script, without needing one. This setting is ignored for non-ELF targets.
+    /// Defaults to `false`.
+    #[must_use]
+    pub const fn retain_sections(mut self, retain: bool) -> Self {
+        self.retain_sections = retain;
+        self
+    }
+
+    /// Links the assets only into the binary target named `name`.
+    ///
+    /// By default, assets are linked into every binary, example, test, and benchmark of
+    /// the package. In packages with several `[[bin]]` targets, this can be called once
+    /// per binary that uses the assets, so that each executable only carries what it
+    /// needs. Using one of these assets from a target it is not linked into results in an
+    /// "undefined symbol" link error.
+    #[must_use]
+    pub fn bin(mut self, name: impl Into<String>) -> Self {
+        self.link_targets.push(LinkTarget::Bin(name.into()));
+        self
+    }
+
+    /// Links the assets into every binary target of the package.
+    ///
+    /// Use this to keep production assets out of examples and benchmarks.
+    #[must_use]
+    pub fn bins(mut self) -> Self {
+        self.link_targets.push(LinkTarget::Bins);
+        self
+    }
+
+    /// Links the assets into every example target of the package.
+    ///
+    /// Use this for demo data, so that it doesn't end up in production binaries.
+    #[must_use]
+    pub fn examples(mut self) -> Self {
+        self.link_targets.push(LinkTarget::Examples);
+        self
+    }
+
+    /// Links the assets into every benchmark target of the package.
+    ///
+    /// Use this for benchmark fixtures, so that they don't end up in production binaries.
+    #[must_use]
+    pub fn benches(mut self) -> Self {
+        self.link_targets.push(LinkTarget::Benches);
+        self
+    }
+
+    /// Links the assets into every crate that depends on this library.
+    ///
+    /// Linker arguments from a build script only apply to the package's own targets, so
+    /// by default, assets used by a library crate are missing when a downstream binary
+    /// is linked. This links them as a [static archive](Self::static_archive) instead,
+    /// which Cargo bundles into the library and passes on to every binary depending on
+    /// it, without any setup in the binary's crate. Other link targets are ignored.
+    ///
+    /// When the crate's `Cargo.toml` has a `links` key, the assets are also shared with
+    /// the crates depending on it, whose macros can include them after calling
+    /// [`use_shared_assets`].
+    ///
+    /// Windows resources can't be linked this way, so [`windows_resources`] is an error
+    /// when targeting Windows.
+    ///
+    /// [`windows_resources`]: Self::windows_resources
+    /// [`use_shared_assets`]: crate::use_shared_assets
+    #[must_use]
+    pub const fn library(mut self) -> Self {
+        self.library = true;
+        self
+    }
+
+    /// Verifies every asset after packing by reading it back from the written artifact.
+    ///
+    /// The freshly written object file is parsed again, the asset's symbol data is
+    /// decompressed, and the result is compared against the source file, failing the
+    /// build on any mismatch. This catches codec and object-writing bugs before they ship,
+    /// at the cost of one extra decompression per asset. Defaults to `false`.
+    #[must_use]
+    pub const fn verify_roundtrip(mut self, verify: bool) -> Self {
+        self.verify_roundtrip = verify;
+        self
+    }
+
+    /// Records a BLAKE3 checksum of every asset's contents and verifies it at runtime.
+    ///
+    /// Every decompression hashes the result and compares it with the checksum, which
+    /// catches object or linker corruption and makes tampering with the embedded data
+    /// evident, at the cost of hashing each asset once more. Mismatches panic in the
+    /// panicking APIs and are reported as `Error::ChecksumMismatch` by the `try_` APIs.
+    /// Zero-copy access through `include_packed_bytes!` is not verified. Defaults to
+    /// `false`.
+    #[cfg(feature = "checksums")]
+    #[must_use]
+    pub const fn verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    /// Signs every asset with the ed25519 key whose 32-byte secret is `signing_key`.
+    ///
+    /// The BLAKE3 checksum of each asset's contents is recorded as with
+    /// [`verify`](Self::verify), along with its signature, which is exposed at runtime
+    /// through `PackedAsset::signature`. Every decompression then verifies both against
+    /// the public key the application passes to `include_packed::set_trusted_key`
+    /// before handing out the data, so assets such as ML models can't be swapped after
+    /// the build without the signing key. Invalid signatures panic in the panicking APIs
+    /// and are reported as `Error::SignatureMismatch` by the `try_` APIs. Signed assets
+    /// can't be included with `include_packed_bytes!` or `include_packed_compressed!`,
+    /// which hand out their data unverified.
+    ///
+    /// Keep the secret out of the source tree, e.g. by reading it from a file
+    /// provisioned by CI.
+    #[cfg(feature = "signatures")]
+    #[must_use]
+    pub fn sign(mut self, signing_key: [u8; 32]) -> Self {
+        self.signing_key = Some(ed25519_dalek::SigningKey::from_bytes(&signing_key));
+        self
+    }
+
+    /// Records build provenance for every asset.
+    ///
+    /// The packing crate's name and version, the git commit checked out in
+    /// `CARGO_MANIFEST_DIR` (if any), and the packing time are stored alongside each asset
+    /// and exposed at runtime through `PackedAsset::provenance`, so support teams can
+    /// verify in the field exactly which asset snapshot a binary contains. The packing
+    /// time honors `SOURCE_DATE_EPOCH` for reproducible builds. Defaults to `false`.
+    #[must_use]
+    pub const fn provenance(mut self, provenance: bool) -> Self {
+        self.provenance = provenance;
+        self
+    }
+
+    /// Sets the compression algorithm. Defaults to [`Algorithm::Zstd`].
+    ///
+    /// The algorithm is recorded for every asset, so the macros pick the matching decoder
+    /// automatically. LZ4 trades compression ratio for much faster decompression, which
+    /// helps startup times on low-end devices. For Brotli, the compression level is
+    /// clamped to its 0-11 quality range, while LZ4 and [`Algorithm::Store`] ignore it.
+    #[must_use]
+    pub const fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Sets the file extensions of assets that are stored with [`Algorithm::Store`]
+    /// instead of being compressed. Defaults to `png`, `jpg`, `jpeg`, `ogg`, and `zip`.
+    ///
+    /// Formats such as PNG, JPEG, Ogg, and ZIP are compressed already, so compressing
+    /// them again costs build time without making them smaller. Extensions match
+    /// case-insensitively and may start with a dot. The algorithm is recorded for each
+    /// asset, so the macros read stored assets without decompressing them. Assets
+    /// rewritten by [`transform`](Self::transform) or [`image_transform`](Self::image_transform)
+    /// are compressed as configured, since their contents may no longer be compressed.
+    /// An empty list compresses every asset.
+    ///
+    /// ```no_run
+    /// // in build.rs
+    /// include_packed::Config::new("assets")
+    ///     .store_extensions(["png", "jpg", "jpeg", "ogg", "zip", "mp3"])
+    ///     .build()
+    ///     .expect("Failed to pack assets");
+    /// ```
+    #[must_use]
+    pub fn store_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.store_extensions = extensions
+            .into_iter()
+            .map(|extension| extension.as_ref().trim_start_matches('.').to_owned())
+            .collect();
+        self
+    }
+
+    /// Sets the compression algorithm used instead of [`algorithm`](Self::algorithm) in
+    /// debug builds, i.e. when Cargo's `PROFILE` is `debug`.
+    ///
+    /// [`Algorithm::Store`] skips compression in debug builds altogether, at the cost
+    /// of larger debug binaries.
+    #[must_use]
+    pub const fn debug_algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.debug_algorithm = Some(algorithm);
+        self
+    }
+
+    /// Picks the algorithm and level of each asset automatically, spending at most about
+    /// `time_budget` on compressing it.
+    ///
+    /// A sample of each asset is compressed with a few algorithms and levels, from the
+    /// fastest to the most thorough, and the one that compresses it best is used. Those
+    /// estimated to take longer than `time_budget` for the whole asset are skipped, and
+    /// assets that don't compress at all are stored. LZ4 and Brotli are only considered
+    /// with their features enabled, which must then be enabled for the regular
+    /// dependency as well.
+    ///
+    /// Assets whose level or algorithm is set with [`file`](Self::file), or that are
+    /// stored by [`store_extensions`](Self::store_extensions), are packed as configured,
+    /// and the profile's level and algorithm as well as the `INCLUDE_PACKED_LEVEL`
+    /// environment variables take precedence. `BuildSummary` reports the choice for each
+    /// asset.
+    ///
+    /// The choice depends on the speed of the build machine and how busy it is, so
+    /// without a [`cache_dir`](Self::cache_dir), builds of the same sources may pack
+    /// assets differently. Set one to keep rebuilds reproducible: each choice is
+    /// remembered there, keyed by the asset's contents, the dictionary, the zstd
+    /// parameters, and `time_budget`.
+    ///
+    /// ```no_run
+    /// // in build.rs
+    /// include_packed::Config::new("assets")
+    ///     .auto(std::time::Duration::from_millis(500))
+    ///     .build()
+    ///     .expect("Failed to pack assets");
+    /// ```
+    #[must_use]
+    pub const fn auto(mut self, time_budget: Duration) -> Self {
+        self.auto = Some(time_budget);
+        self
+    }
+
+    /// Folds a namespace into the names of all asset symbols.
+    ///
+    /// Symbol names are derived from the package name and each asset's path and
+    /// contents. When several statically linked components that use `include_packed` end
+    /// up in one final link, e.g. different versions of the same crate, identical assets
+    /// would produce clashing symbols. Giving each its own namespace guarantees
+    /// isolation. The namespace may only contain ASCII letters, digits, and underscores.
+    ///
+    /// The resulting names are recorded for the macros, so nothing needs to be mirrored
+    /// where the assets are included.
+    #[must_use]
+    pub fn symbol_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.symbol_namespace = Some(namespace.into());
+        self
+    }
+
+    /// Prefixes the names of all asset symbols, e.g. with `"mycrate_"`.
+    ///
+    /// The prefix is stored as the [`symbol_namespace`](Self::symbol_namespace), with one
+    /// trailing underscore removed, since symbol names already separate the namespace
+    /// from what follows with one. The rest must be a valid namespace, so
+    /// `symbol_prefix("mycrate_")` and `symbol_namespace("mycrate")` both produce
+    /// symbols named `include_packed_mycrate_<hash>`. The last of the two calls wins.
+    #[must_use]
+    pub fn symbol_prefix(self, prefix: impl Into<String>) -> Self {
+        let prefix = prefix.into();
+        let namespace = prefix.strip_suffix('_').unwrap_or(&prefix).to_owned();
+        self.symbol_namespace(namespace)
+    }
+
+    /// Hides the layout of the assets in the compiled binary.
+    ///
+    /// The symbols of assets, bundles, and dictionaries get opaque names, keyed by a
+    /// secret drawn anew on each run of the build script, or derived from
+    /// `SOURCE_DATE_EPOCH` when it is set to keep builds reproducible. Assets included
+    /// with `include_packed!`, `include_packed_asset!`, and `include_packed_info!` don't
+    /// embed their paths, so `PackedAsset::path` and `AssetInfo::path` return an empty
+    /// string, and development passthrough is disabled. The macros still find each
+    /// asset through the metadata recorded in `OUT_DIR`.
+    ///
+    /// Listings such as `include_packed_manifest!`, `include_packed_dir!`, and the
+    /// registry name their assets by design, and still embed the paths. Patch packs
+    /// overlay assets by path, so obfuscated assets can't be patched, and
+    /// [`write_pack`](Self::write_pack) refuses this option. Defaults to `false`.
+    #[must_use]
+    pub const fn obfuscate(mut self, obfuscate: bool) -> Self {
+        self.obfuscate = obfuscate;
+        self
+    }
+
+    /// Splits zstd-compressed assets into independently compressed chunks of
+    /// `chunk_size` bytes.
+    ///
+    /// `PackedAsset::decompress_range` then only decompresses the chunks overlapping the
+    /// requested range, so slices of huge assets, such as map tiles, can be read without
+    /// decompressing everything. Smaller chunks make range reads cheaper but compress
+    /// worse. Assets no larger than one chunk, and assets compressed with other
+    /// algorithms, are left whole.
+    #[must_use]
+    pub const fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Places the packed data of every asset at an address that is a multiple of
+    /// `align` bytes, which must be a power of two.
+    ///
+    /// Stored assets (see [`Algorithm::Store`]) can then be reinterpreted in place, e.g.
+    /// as `&[u32]` or SIMD vectors, through `PackedAsset::as_stored`. The alignment is
+    /// also recorded for the runtime, so `PackedAsset::decompress_aligned`,
+    /// `include_packed_aligned!`, and `include_packed_lazy!` hand out decompressed data
+    /// at the same alignment. Alignment can't be guaranteed for
+    /// [Windows resources](Self::windows_resources). Defaults to `1`.
+    #[must_use]
+    pub const fn align(mut self, align: usize) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Encrypts every asset with `cipher`, so it is only stored encrypted in the binary.
+    ///
+    /// The 256-bit key is read from the `INCLUDE_PACKED_ENCRYPTION_KEY` environment
+    /// variable as 64 hexadecimal digits, or if that is not set, from the file named by
+    /// `INCLUDE_PACKED_KEY_FILE` as 32 raw bytes or 64 hexadecimal digits, so CI can
+    /// provision it as a secret file. It must be provided at runtime with
+    /// `include_packed::unlock` before the assets are decompressed. Assets are
+    /// compressed before they are encrypted. To keep builds reproducible, nonces are
+    /// derived from the data, so identical assets have identical ciphertexts, which
+    /// reveals that they are equal. Dictionaries trained with
+    /// [`train_dictionary`](Self::train_dictionary) are derived from the assets but
+    /// stored unencrypted, so don't combine them with encryption for sensitive data.
+    #[cfg(feature = "encryption")]
+    #[must_use]
+    pub const fn encrypt(mut self, cipher: Cipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Reads assets from their source files at runtime in debug builds.
+    ///
+    /// When the build script runs for Cargo's `debug` profile, the macros record each
+    /// asset's absolute source path, and decompressing the asset reads the file from disk
+    /// instead, so edits show up in a running program without rebuilding it. If the file
+    /// is missing, e.g. when the binary runs on another machine, the embedded data is
+    /// used. Checksums are not verified for files read from disk. Release builds, Wasm
+    /// targets, `no_std` runtimes, and `include_packed_bytes!` always use the embedded
+    /// data. Defaults to `false`.
+    #[must_use]
+    pub const fn dev_passthrough(mut self, passthrough: bool) -> Self {
+        self.dev_passthrough = passthrough;
+        self
+    }
+
+    /// Packs all assets of this configuration into a single bundle symbol.
+    ///
+    /// By default, every asset is written to an object file with a symbol of its own,
+    /// each of which is passed to the linker separately. Projects with thousands of
+    /// assets can instead concatenate the compressed assets into one bundle, so only a
+    /// single object file is linked. The offset of each asset within the bundle is
+    /// recorded for the macros, which resolve assets to slices of the bundle, so there
+    /// is no lookup at runtime. Assets served with [`wasm_fetch`](Self::wasm_fetch) are
+    /// not bundled. Defaults to `false`.
+    #[must_use]
+    pub const fn bundle(mut self, bundle: bool) -> Self {
+        self.bundle = bundle;
+        self
+    }
+
+    /// Links the object files of this configuration as a single static archive.
+    ///
+    /// By default, every object file is passed to the linker with a
+    /// `cargo:rustc-link-arg` directive of its own, and thousands of assets can exceed
+    /// command-line length limits, notably on Windows. Archiving them emits a single
+    /// `cargo:rustc-link-search` and `cargo:rustc-link-lib` pair instead, or a single
+    /// linker argument when [link targets](Self::bin) are configured.
+    ///
+    /// The linker only pulls in archive members whose symbols are referenced, unless
+    /// [`retain_sections`](Self::retain_sections) is set, which links the whole
+    /// archive. This has no effect on Wasm, or when assets are embedded as Windows
+    /// resources. Defaults to `false`.
+    #[must_use]
+    pub const fn static_archive(mut self, archive: bool) -> Self {
+        self.static_archive = archive;
+        self
+    }
+
+    /// Writes a JSON report of the compression results to `path` after
+    /// [`build`](Self::build).
+    ///
+    /// The report lists every asset with its logical path, original and compressed
+    /// sizes, compression ratio, level, and the milliseconds spent compressing it, so
+    /// release pipelines can track how assets contribute to binary size over time:
+    ///
+    /// ```json
+    /// {"assets":[{"path":"assets/logo.png","size":1024,"compressed_size":512,
+    /// "ratio":0.5,"level":6,"time_ms":0.25}]}
+    /// ```
+    ///
+    /// Assets read from the [compression cache](Self::cache_dir) report the time it took
+    /// to read them. Each call to `build` replaces the report at `path`, so
+    /// configurations built by the same script should report to different files.
+    #[must_use]
+    pub fn report(mut self, path: impl AsRef<Path>) -> Self {
+        self.report = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Also declares each asset in a Rust module written to `OUT_DIR/packed_assets.rs`,
+    /// so application code can refer to assets by name instead of by path:
+    ///
+    /// ```text
+    /// mod assets {
+    ///     include!(concat!(env!("OUT_DIR"), "/packed_assets.rs"));
+    /// }
+    ///
+    /// let logo = assets::ASSETS_LOGO_PNG.decompress();
+    /// ```
+    ///
+    /// Each asset becomes a `pub static` [`PackedAsset`](crate::PackedAsset) handle
+    /// named after its logical path in upper case, with every other character replaced
+    /// by `_` and a numeric suffix for paths that would otherwise share a name. The
+    /// handles are documented with the asset's path, its original and compressed sizes,
+    /// and the BLAKE3 hash of its contents. The module also declares an enum named
+    /// `PackedAsset` with a variant for each asset, e.g. `AssetsLogoPng`, whose `bytes`
+    /// method decompresses it, so code matching over it exhaustively is flagged by the
+    /// compiler when assets are added. Every configuration built with this option adds
+    /// its assets to the same module, which refers to the crate as
+    /// `::include_packed`. Assets served with [`wasm_fetch`](Self::wasm_fetch) can't be
+    /// declared when building for Wasm.
+    #[must_use]
+    pub const fn generate_module(mut self, generate: bool) -> Self {
+        self.generate_module = generate;
+        self
+    }
+
+    /// Fails the build if the compressed assets of this configuration add up to more
+    /// than `bytes`.
+    ///
+    /// The error lists the largest assets, so budgets catch accidentally committed
+    /// files before they bloat a release. The budget applies to each call to
+    /// [`build`](Self::build) on its own.
+    #[must_use]
+    pub const fn max_total_compressed(mut self, bytes: u64) -> Self {
+        self.max_total_compressed = Some(bytes);
+        self
+    }
+
+    /// Fails the build if any asset is larger than `bytes` before compression.
+    ///
+    /// Sizes are checked before anything is compressed, and the error lists every
+    /// offending asset.
+    #[must_use]
+    pub const fn max_asset_size(mut self, bytes: u64) -> Self {
+        self.max_asset_size = Some(bytes);
+        self
+    }
+
+    /// Serves assets for Wasm targets over HTTP instead of embedding them.
+    ///
+    /// When building for `wasm32`, each asset's compressed data is written to `dir`
+    /// under a name derived from its contents, and only its URL below `base_url` ends up
+    /// in the `.wasm` binary. `include_packed_fetch!` then downloads and decompresses
+    /// the asset at runtime, which requires the `fetch` feature. Deploy `dir` alongside
+    /// the application; since file names change with the contents, they can be cached
+    /// forever. The other macros can't access fetched assets, and native builds embed
+    /// assets as usual.
+    #[must_use]
+    pub fn wasm_fetch(mut self, dir: impl AsRef<Path>, base_url: impl Into<String>) -> Self {
+        self.wasm_fetch = Some((dir.as_ref().to_path_buf(), base_url.into()));
+        self
+    }
+
+    /// Runs the asset packing process with the specified configuration.
+    ///
+    /// This is the final method that should be called in the builder chain.
+    /// It handles all platform-specific logic internally, preparing assets for
+    /// the [`include_packed!`](`crate::include_packed`) macro, and returns a
+    /// [`BuildSummary`] of the packed assets for build scripts to log or check.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if any part of the build process fails, such as file I/O
+    /// or object file creation.
+    pub fn build(
+        #[cfg_attr(not(feature = "remote"), allow(unused_mut))] mut self,
+    ) -> Result<BuildSummary> {
+        let started = Instant::now();
+        // Get the target architecture from the environment variable Cargo provides.
+        let target_arch =
+            env::var("CARGO_CFG_TARGET_ARCH").map_err(|_| Error::Var("CARGO_CFG_TARGET_ARCH"))?;
+
+        forward_target(&target_arch);
+        if uses_include_bytes() && target_arch != "wasm32" {
+            println!(
+                "cargo:warning=include_packed: no object file support for this target, embedding assets with `include_bytes!` instead"
+            );
+        }
+
+        // Forward path remapping rules so the macro derives the same asset keys.
+        let remap_rules = remap_path_prefixes();
+        if !remap_rules.is_empty() {
+            let encoded = remap_rules
+                .iter()
+                .map(|(from, to)| format!("{}={}", from.display(), to.display()))
+                .collect::<Vec<_>>()
+                .join("\x1f");
+            println!("cargo:rustc-env={REMAP_ENV}={encoded}");
+        }
+
+        if let Some(namespace) = &self.symbol_namespace
+            && (namespace.is_empty()
+                || !namespace
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'_'))
+        {
+            return Err(Error::InvalidNamespace(namespace.clone()));
+        }
+
+        let chunk_sizes = self.files.iter().map(|(_, file)| file.chunk_size);
+        if std::iter::once(self.chunk_size)
+            .chain(chunk_sizes)
+            .any(|chunk_size| chunk_size == Some(0))
+        {
+            return Err(Error::Generic("chunk size must not be zero".into()));
+        }
+
+        let aligns = std::iter::once(self.align)
+            .chain(self.files.iter().filter_map(|(_, file)| file.align))
+            .collect::<Vec<_>>();
+        if let Some(align) = aligns.iter().find(|align| !align.is_power_of_two()) {
+            return Err(Error::Generic(format!(
+                "alignment {align} is not a power of two"
+            )));
+        }
+
+        if let Some((segment, section)) = &self.macho_section {
+            for (kind, name) in [("segment", segment), ("section", section)] {
+                if name.len() > 16 {
+                    return Err(Error::MachONameTooLong(kind, name.clone()));
+                }
+            }
+        }
+
+        if uses_windows_resources(&self) {
+            if self.library {
+                return Err(Error::Generic(
+                    "Windows resources can't be linked into crates depending on a library".into(),
+                ));
+            }
+            if aligns.iter().any(|align| *align > 1) {
+                return Err(Error::Generic(
+                    "Windows resources can't be aligned; disable them to use `Config::align`"
+                        .into(),
+                ));
+            }
+            println!("cargo:rustc-env=INCLUDE_PACKED_WINDOWS_RESOURCES=1");
+        }
+        for var in level_vars() {
+            println!("cargo:rerun-if-env-changed={var}");
+        }
+        #[cfg(feature = "remote")]
+        if let Some((url, sha256)) = &self.remote {
+            if self.root.is_some() {
+                return Err(Error::Generic(
+                    "`root` cannot be combined with `Config::remote`, whose download is resolved like assets below a root".into(),
+                ));
+            }
+            // The downloaded file is resolved like assets below a root.
+            self.root = Some(download(
+                url,
+                sha256,
+                &self.paths[0],
+                self.cache_dir.as_deref(),
+            )?);
+        }
+        let mut packer = Packer::new(&self)?;
+        packer.source_date_epoch = source_date_epoch()?;
+        #[cfg(feature = "encryption")]
+        if let Some(cipher) = self.cipher {
+            packer.encryption = Some((cipher, encryption_key()?));
+        }
+        if self.provenance {
+            packer
+                .common_meta
+                .extend(provenance_meta(packer.source_date_epoch)?);
+        }
+        remove_stale_files()?;
+        if let Some(root) = &self.root {
+            forward_root(root)?;
+        }
+        let mut files = Vec::new();
+        let mut fingerprints = Vec::new();
+        let mut visited = HashSet::new();
+        for source_path in self.source_paths() {
+            #[cfg(feature = "archives")]
+            if self.archive {
+                let archive = canonical_source(&source_path)?;
+                println!("cargo:rerun-if-changed={}", archive.display());
+                let metadata = fs::metadata(&archive)?;
+                for (name, data) in read_archive(&archive)? {
+                    let path = archive.join(name);
+                    if visited.insert(path.clone()) {
+                        packer.members.insert(path.clone(), data);
+                        files.push((path, metadata.clone()));
+                    }
+                }
+                packer.canonical_roots.push(archive);
+                continue;
+            }
+            collect_asset_files(
+                &source_path,
+                self.follow_symlinks,
+                &mut visited,
+                &mut files,
+                &mut fingerprints,
+            )?;
+            packer.canonical_roots.push(source_path.canonicalize()?);
+        }
+        // Sorting keeps the link order independent of the file system.
+        files.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut included = Vec::with_capacity(files.len());
+        for (path, metadata) in files {
+            let key = asset_key(&path, self.root.as_deref())?;
+            if packer.includes(&key) {
+                included.push((path, metadata));
+            } else {
+                // Lets the macros skip the file when listing directories.
+                let meta_path = meta_path(&key, &metadata)?;
+                write_meta(&meta_path, &[("excluded", "true".into())])?;
+            }
+        }
+        let files = included;
+        let aliases = self.resolve_aliases(&packer, &files)?;
+        let mut artifacts = Vec::new();
+        if let Some(max_size) = self.max_asset_size {
+            let oversized = files
+                .iter()
+                .map(|(path, metadata)| (path, packer.source_len(path, metadata)))
+                .filter(|(_, len)| *len > max_size)
+                .map(|(path, len)| Ok((self.logical_path(&packer.canonical_roots, path)?, len)))
+                .collect::<Result<Vec<_>>>()?;
+            if !oversized.is_empty() {
+                return Err(Error::AssetsTooLarge(oversized, max_size));
+            }
+        }
+        if let Some(max_size) = self.dictionary_size
+            && packer.algorithm == Algorithm::Zstd
+        {
+            let dictionary = Dictionary::train(&packer, &files, max_size)?;
+            artifacts.push(dictionary.artifact.clone());
+            packer.dictionary = Some(dictionary);
+        }
+        packer.delta_bases = packer.read_delta_bases(&files)?;
+
+        let mut packed_files = process_files(&files, &packer)?;
+        share_identical(&mut packed_files)?;
+        if let Some(budget) = self.max_total_compressed {
+            let total = packed_files
+                .iter()
+                .filter(|packed| !packed.duplicate)
+                .map(|packed| packed.compressed_len as u64)
+                .sum::<u64>();
+            if total > budget {
+                let mut largest = files
+                    .iter()
+                    .zip(&packed_files)
+                    .map(|((path, _), packed)| {
+                        Ok((
+                            self.logical_path(&packer.canonical_roots, path)?,
+                            packed.compressed_len as u64,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                largest.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+                largest.truncate(10);
+                return Err(Error::BudgetExceeded(largest, total, budget));
+            }
+        }
+
+        let mut manifest = Vec::new();
+        let mut module = Vec::new();
+        let mut assets = Vec::new();
+        let mut output_bytes = 0;
+        let mut bundled = Vec::new();
+        for ((path, _), packed) in files.iter().zip(packed_files) {
+            artifacts.extend(packed.artifact);
+            let logical_path = self.logical_path(&packer.canonical_roots, path)?;
+            assets.push(AssetSummary {
+                path: logical_path.clone(),
+                len: packed.len as u64,
+                compressed_len: packed.compressed_len as u64,
+                algorithm: packed.algorithm,
+                level: packed.level,
+                elapsed: packed.elapsed,
+            });
+            if !packed.duplicate {
+                output_bytes += packed.compressed_len as u64;
+            }
+            if self.generate_module {
+                module.push(ModuleItem {
+                    path: logical_path.clone(),
+                    len: packed.len,
+                    compressed_len: packed.compressed_len,
+                    hash: packed.hash,
+                });
+            }
+            manifest.push(ManifestEntry {
+                path: logical_path,
+                len: packed.len,
+                compressed_len: packed.compressed_len,
+                algorithm: packed.algorithm,
+                modified: packed.modified,
+            });
+            if packed.bundled {
+                bundled.push(BundledFile {
+                    data: packed.bundle_data,
+                    symbol: packed.symbol,
+                    meta: packed.meta,
+                    meta_path: packed.meta_path,
+                    align: packed.align,
+                });
+            } else {
+                write_meta(&packed.meta_path, &packed.meta)?;
+            }
+        }
+        if !bundled.is_empty() {
+            artifacts.push(write_bundle(&self, bundled)?);
+        }
+        link_artifacts(&self, &artifacts)?;
+        if self.generate_module {
+            append_module(module)?;
+        }
+        if self.library && env::var_os("CARGO_MANIFEST_LINKS").is_some() {
+            advertise_shared_assets()?;
+        }
+        if let Some(path) = &self.report {
+            write_report(path, &assets)?;
+        }
+        // Assets scoped to some targets would be missing from the others' registries,
+        // and fetched assets aren't linked at all.
+        let registered = self.link_targets.is_empty() && !(self.wasm_fetch.is_some() && is_wasm());
+        append_manifest(&manifest, &fingerprints, &aliases, registered)?;
+        Ok(BuildSummary {
+            input_bytes: assets.iter().map(|asset| asset.len).sum(),
+            output_bytes,
+            assets,
+            elapsed: started.elapsed(),
+        })
+    }
+
+    /// Returns the logical path of the asset at `path`, i.e. the first configured path
+    /// containing it joined with the asset's path below it. `canonical_roots` holds the
+    /// canonical forms of the configured paths.
+    ///
+    /// Assets reached through symbolic links that leave the configured paths fall back to
+    /// their key.
+    fn logical_path(&self, canonical_roots: &[PathBuf], path: &Path) -> Result<String> {
+        let relative =
+            self.paths
+                .iter()
+                .zip(canonical_roots)
+                .find_map(|(configured, canonical)| {
+                    Some(configured.join(path.strip_prefix(canonical).ok()?))
+                });
+        match relative {
+            Some(relative) => Ok(logical_path(&relative)),
+            None => Ok(logical_path(&asset_key(path, self.root.as_deref())?)),
+        }
+    }
+
+    /// Checks that the aliases of [`alias`](Self::alias) name assets among `files`,
+    /// returning them with normalized paths.
+    fn resolve_aliases(
+        &self,
+        packer: &Packer<'_>,
+        files: &[(PathBuf, fs::Metadata)],
+    ) -> Result<Vec<(String, String)>> {
+        if self.aliases.is_empty() {
+            return Ok(Vec::new());
+        }
+        let packed = files
+            .iter()
+            .map(|(path, _)| self.logical_path(&packer.canonical_roots, path))
+            .collect::<Result<HashSet<_>>>()?;
+        self.aliases
+            .iter()
+            .map(|(name, path)| {
+                if name.is_empty() || name.contains(char::is_whitespace) {
+                    return Err(Error::Generic(format!(
+                        "alias '{name}' must be non-empty and not contain whitespace"
+                    )));
+                }
+                let path = logical_path(Path::new(path));
+                if !packed.contains(&path) {
+                    return Err(Error::Generic(format!(
+                        "alias '{name}' refers to '{path}', which is not packed by this configuration"
+                    )));
+                }
+                Ok((name.clone(), path))
+            })
+            .collect()
+    }
+
+    /// Returns the configured paths, joined to the [`root`](Self::root) if one is set.
+    fn source_paths(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        self.paths.iter().map(|path| match &self.root {
+            Some(root) => root.join(path),
+            None => path.clone(),
+        })
+    }
+
+    /// Returns whether the assets are signed with [`sign`](Self::sign).
+    #[cfg(feature = "checksums")]
+    fn signs(&self) -> bool {
+        #[cfg(feature = "signatures")]
+        if self.signing_key.is_some() {
+            return true;
+        }
+        false
+    }
+
+    /// Returns whether any transforms rewrite the assets before they are compressed.
+    fn transforms(&self) -> bool {
+        #[cfg(feature = "image-pipeline")]
+        if !self.image_transforms.is_empty() {
+            return true;
+        }
+        !self.transforms.is_empty() || !self.transcodes.is_empty()
+    }
+
+    /// Writes the configured assets into an external patch pack instead of embedding them.
+    ///
+    /// Each asset is keyed by its logical path, i.e. the configured path joined with the
+    /// file's path below it, which is how the macros refer to it when the configured
+    /// path is relative to the crate root. At runtime, `PatchPack::overlay` replaces
+    /// embedded assets with the pack's versions, so hotfixes or downloadable content can
+    /// ship without rebuilding the binary. Only the compression level applies to packs.
+    ///
+    /// Unlike [`build`](Self::build), this does not rely on Cargo's build script
+    /// environment and can be called from any tool.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the assets could not be read or the pack could not be
+    /// written, or if [`obfuscate`](Self::obfuscate) is set, since obfuscated assets have
+    /// no paths to overlay them by.
+    pub fn write_pack(&self, output: impl AsRef<Path>) -> Result<()> {
+        if self.obfuscate {
+            return Err(Error::Generic(
+                "patch packs key assets by path, which `obfuscate` hides".into(),
+            ));
+        }
+        #[cfg_attr(not(feature = "archives"), allow(unused_mut))]
+        let mut packer = Packer::new(self)?;
+        let mut files = Vec::new();
+        for source_path in self.source_paths() {
+            #[cfg(feature = "archives")]
+            if self.archive {
+                for (name, data) in read_archive(&canonical_source(&source_path)?)? {
+                    let path = source_path.join(name);
+                    files.push((logical_path(&path), path.clone()));
+                    packer.members.insert(path, data);
+                }
+                continue;
+            }
+            collect_pack_files(
+                &source_path,
+                self.follow_symlinks,
+                &mut Vec::new(),
+                &mut files,
+            )?;
+        }
+        if let Some(root) = &self.root {
+            // Keys are relative to the root, like the paths the macros accept.
+            for (key, path) in &mut files {
+                *key = logical_path(path.strip_prefix(root).unwrap_or(path));
+            }
+        }
+        files.retain(|(key, _)| packer.includes(Path::new(key)));
+        files.sort();
+        // Files reached through several of the configured paths are packed once.
+        files.dedup();
+
+        let count = u32::try_from(files.len())
+            .map_err(|_| Error::Generic("too many assets for one pack".into()))?;
+        let mut pack = Vec::new();
+        pack.extend_from_slice(crate::runtime::pack::MAGIC);
+        pack.extend_from_slice(&count.to_le_bytes());
+        for (key, path) in files {
+            let level = packer.level_for(Path::new(&key));
+            let content = packer.transform(Path::new(&key), packer.read_source(&path)?)?;
+            let compressed = packer.compress(Algorithm::Zstd, &content, level, None)?;
+            let key_len = u32::try_from(key.len())
+                .map_err(|_| Error::Generic(format!("asset path '{key}' is too long")))?;
+            pack.extend_from_slice(&key_len.to_le_bytes());
+            pack.extend_from_slice(key.as_bytes());
+            pack.extend_from_slice(&(compressed.len() as u64).to_le_bytes());
+            pack.extend_from_slice(&compressed);
+        }
+        fs::write(output, pack)?;
+        Ok(())
+    }
+}
+
+/// Options overriding those of a [`Config`] for the assets matching a pattern, set with
+/// [`Config::file`].
+#[derive(Debug, Clone, Default)]
+pub struct FileConfig {
+    level: Option<i32>,
+    algorithm: Option<Algorithm>,
+    align: Option<usize>,
+    chunk_size: Option<usize>,
+    delta_base: Option<String>,
+    #[cfg(feature = "checksums")]
+    verify: Option<bool>,
+}
+
+impl FileConfig {
+    /// Sets the compression level, overriding [`Config::level`] and the levels of the
+    /// profile and environment.
+    #[must_use]
+    pub const fn level(mut self, level: i32) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    /// Sets the compression algorithm, overriding [`Config::algorithm`],
+    /// [`Config::debug_algorithm`], and [`Config::store_extensions`].
+    #[must_use]
+    pub const fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = Some(algorithm);
+        self
+    }
+
+    /// Sets the alignment of the packed data, like [`Config::align`].
+    #[must_use]
+    pub const fn align(mut self, align: usize) -> Self {
+        self.align = Some(align);
+        self
+    }
+
+    /// Splits the assets into chunks of `chunk_size` bytes, like [`Config::chunk_size`].
+    #[must_use]
+    pub const fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Packs the assets as patches against the asset `base`, like [`Config::delta`].
+    ///
+    /// Patches are always compressed with zstd and never split into chunks, so the
+    /// algorithm and chunk size don't apply to them.
+    #[must_use]
+    pub fn delta_from(mut self, base: impl Into<String>) -> Self {
+        self.delta_base = Some(base.into());
+        self
+    }
+
+    /// Sets whether the assets' checksums are verified at runtime, like
+    /// [`Config::verify`].
+    #[cfg(feature = "checksums")]
+    #[must_use]
+    pub const fn verify(mut self, verify: bool) -> Self {
+        self.verify = Some(verify);
+        self
+    }
+}
+
+/// The results of a [`Config::build`] call.
+#[derive(Debug, Clone)]
+pub struct BuildSummary {
+    assets: Vec<AssetSummary>,
+    input_bytes: u64,
+    output_bytes: u64,
+    elapsed: Duration,
+}
+
+impl BuildSummary {
+    /// Returns the packed assets, in the order they were packed.
+    #[must_use]
+    pub fn assets(&self) -> &[AssetSummary] {
+        &self.assets
+    }
+
+    /// Returns the number of packed assets. Files skipped by [`Config::include`] or
+    /// [`Config::exclude`] are not counted.
+    #[must_use]
+    pub const fn file_count(&self) -> usize {
+        self.assets.len()
+    }
+
+    /// Returns the total size of the assets before compression.
+    #[must_use]
+    pub const fn input_bytes(&self) -> u64 {
+        self.input_bytes
+    }
+
+    /// Returns the total size of the compressed assets, counting data shared by
+    /// identical assets once.
+    #[must_use]
+    pub const fn output_bytes(&self) -> u64 {
+        self.output_bytes
+    }
+
+    /// Returns the time the build took, including reading, compressing, and linking the
+    /// assets.
+    #[must_use]
+    pub const fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+/// An asset packed by [`Config::build`], as listed by [`BuildSummary::assets`].
+#[derive(Debug, Clone)]
+pub struct AssetSummary {
+    path: String,
+    len: u64,
+    compressed_len: u64,
+    algorithm: Algorithm,
+    level: i32,
+    elapsed: Duration,
+}
+
+impl AssetSummary {
+    /// Returns the asset's logical path, i.e. the path the macros accept for it.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the size of the asset before compression.
+    #[must_use]
+    pub const fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns whether the asset is empty.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the size of the compressed asset.
+    #[must_use]
+    pub const fn compressed_len(&self) -> u64 {
+        self.compressed_len
+    }
+
+    /// Returns the algorithm the asset was compressed with.
+    #[must_use]
+    pub const fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Returns the compression level the asset was compressed with.
+    #[must_use]
+    pub const fn level(&self) -> i32 {
+        self.level
+    }
+
+    /// Returns the time spent compressing the asset, or reading it from the
+    /// [compression cache](Config::cache_dir).
+    #[must_use]
+    pub const fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+/// The default compression level of [`Config::level`].
+pub const DEFAULT_COMPRESSION_LEVEL: i32 = 6;
+
+/// The extensions of already compressed formats that are stored instead of compressed
+/// by default, see [`Config::store_extensions`].
+const DEFAULT_STORE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "ogg", "zip"];
+
+/// A specialized `Result` type for build script operations.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// An error that can occur during the asset packing process in a build script.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("I/O error")]
+    Io(#[from] std::io::Error),
+    #[error("Object writing error")]
+    Object(#[from] object::write::Error),
+    #[error("Environment variable '{0}' not set by Cargo")]
+    Var(&'static str),
+    #[error("Path '{0}' not found (current directory is '{1}')")]
+    PathNotFound(String, String),
+    #[error("Path '{0}' has unsupported file type")]
+    UnsupportedFileType(String),
+    #[error("Mach-O {0} name '{1}' exceeds 16 bytes")]
+    MachONameTooLong(&'static str, String),
+    #[error(
+        "Symbol namespace '{0}' must be non-empty and only contain ASCII letters, digits, and underscores"
+    )]
+    InvalidNamespace(String),
+    #[error("Dictionary training failed")]
+    Dictionary(#[source] std::io::Error),
+    #[error("Invalid glob pattern '{0}'")]
+    InvalidPattern(String, #[source] glob::PatternError),
+    #[error("Object reading error")]
+    ObjectRead(#[from] object::read::Error),
+    #[error("Round-trip verification failed for '{0}': {1}")]
+    Verification(String, &'static str),
+    #[error("Could not convert object file name to UTF-8")]
+    FromUtf8(#[from] FromUtf8Error),
+    #[error(
+        "Environment variable '{0}' must hold a 256-bit encryption key as 64 hexadecimal digits"
+    )]
+    InvalidKey(&'static str),
+    #[error(
+        "Key file '{0}' must exist and hold a 256-bit encryption key as 32 bytes or 64 hexadecimal digits"
+    )]
+    InvalidKeyFile(String),
+    #[error("Environment variable '{0}' must hold a compression level, not '{1}'")]
+    InvalidLevel(String, String),
+    #[error("Assets exceed the maximum size of {1} bytes: {assets}", assets = format_sizes(.0))]
+    AssetsTooLarge(Vec<(String, u64)>, u64),
+    #[error(
+        "Compressed assets total {1} bytes, exceeding the budget of {2} bytes; largest: {assets}",
+        assets = format_sizes(.0)
+    )]
+    BudgetExceeded(Vec<(String, u64)>, u64, u64),
+    #[cfg(feature = "remote")]
+    #[error("Failed to download '{0}'")]
+    Download(String, #[source] Box<ureq::Error>),
+    #[cfg(feature = "remote")]
+    #[error("'{0}' has the SHA-256 hash {2}, but {1} was expected")]
+    HashMismatch(String, String, String),
+    #[cfg(feature = "archives")]
+    #[error("Failed to read archive '{0}'")]
+    Archive(String, #[source] std::io::Error),
+    #[error(
+        "Asset '{0}' is an unfetched Git LFS pointer; run `git lfs pull` or enable `Config::lfs_pull`"
+    )]
+    LfsPointer(String),
+    #[error("Asset '{0}' is not valid {1:?} text")]
+    Transcode(String, Encoding),
+    #[cfg(feature = "image-pipeline")]
+    #[error("Image transform failed for '{0}'")]
+    Image(String, #[source] image::ImageError),
+    #[error("A generic build error occurred: {0}")]
+    Generic(String),
+}
+
+/// Formats the assets listed by [`Error::AssetsTooLarge`] and [`Error::BudgetExceeded`].
+fn format_sizes(assets: &[(String, u64)]) -> String {
+    assets
+        .iter()
+        .map(|(path, size)| format!("'{path}' ({size} bytes)"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Forwards the target to the macros, which reject being expanded before the build
+/// script ran.
+fn forward_target(target_arch: &str) {
+    // Set an environment variable for the procedural macro to read. This is the
+    // primary communication channel to determine the build strategy (native vs. wasm).
+    println!("cargo:rustc-env=INCLUDE_PACKED_TARGET_ARCH={target_arch}");
+    if uses_include_bytes() {
+        println!("cargo:rustc-env=INCLUDE_PACKED_INCLUDE_BYTES=1");
+    }
+}
+
+/// Environment variable used to tell the macros which roots assets are resolved
+/// relative to, see [`Config::root`].
+const ROOTS_ENV: &str = "INCLUDE_PACKED_ROOTS";
+
+/// The canonical paths of the roots forwarded to the macros so far.
+static ROOTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Lets the macros resolve asset paths relative to `root`.
+fn forward_root(root: &Path) -> Result<()> {
+    let root = root.canonicalize().map_err(|_| {
+        Error::PathNotFound(
+            root.display().to_string(),
+            env::current_dir().map_or_else(|_| "unknown".into(), |p| p.display().to_string()),
+        )
+    })?;
+    let mut roots = ROOTS.lock().unwrap_or_else(PoisonError::into_inner);
+    let root = root.display().to_string();
+    if !roots.contains(&root) {
+        roots.push(root);
+    }
+    // Later values replace earlier ones, so each call forwards every root so far.
+    println!("cargo:rustc-env={ROOTS_ENV}={}", roots.join("\x1f"));
+    Ok(())
+}
+
+/// Environment variable used to tell the macros where shared assets were packed.
+const SHARED_ENV: &str = "INCLUDE_PACKED_SHARED";
+
+/// The crates whose shared assets this build script's crate uses, each given as its
+/// manifest directory and `OUT_DIR` separated by `\x1e`.
+static SHARED_CRATES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Advertises the crate's assets to the build scripts of crates depending on it, as
+/// the `DEP_<links>_INCLUDE_PACKED_*` variables read by [`use_shared_assets`].
+fn advertise_shared_assets() -> Result<()> {
+    let manifest_dir =
+        env::var("CARGO_MANIFEST_DIR").map_err(|_| Error::Var("CARGO_MANIFEST_DIR"))?;
+    let out_dir = env::var("OUT_DIR").map_err(|_| Error::Var("OUT_DIR"))?;
+    println!("cargo:include_packed_manifest_dir={manifest_dir}");
+    println!("cargo:include_packed_out_dir={out_dir}");
+    Ok(())
+}
+
+/// Lets the macros of this crate include the assets packed by a dependency with
+/// [`Config::library`], so that several crates share a single packing of the same assets.
+///
+/// `links` is the value of the `links` key in the dependency's `Cargo.toml`, which
+/// Cargo requires for passing information between build scripts. The dependency
+/// advertises its assets when it has one; this crate must depend on it directly.
+/// Paths that don't exist in this crate are then looked up relative to the
+/// dependency's root, without packing or compressing anything again:
+///
+/// ```no_run
+/// // build.rs of a crate depending on `game_assets`, which has `links = "game_assets"`
+/// include_packed::use_shared_assets("game_assets").expect("game_assets is not a dependency");
+/// ```
+///
+/// The dependency's assets are linked through the dependency itself, so its library
+/// must be linked, e.g. by using any of its items.
+///
+/// # Errors
+/// Returns an [`Error`] if the dependency didn't advertise any assets, e.g. because it
+/// doesn't have the `links` key, isn't a direct dependency, or didn't use
+/// [`Config::library`].
+pub fn use_shared_assets(links: &str) -> Result<()> {
+    let var = |key: &str| {
+        let name = format!(
+            "DEP_{}_INCLUDE_PACKED_{key}",
+            links.to_uppercase().replace('-', "_")
+        );
+        env::var(&name).map_err(|_| {
+            Error::Generic(format!(
+                "no assets shared by a dependency with `links = \"{links}\"` ({name} is not set)"
+            ))
+        })
+    };
+    let shared = format!("{}\x1e{}", var("MANIFEST_DIR")?, var("OUT_DIR")?);
+    let target_arch =
+        env::var("CARGO_CFG_TARGET_ARCH").map_err(|_| Error::Var("CARGO_CFG_TARGET_ARCH"))?;
+    forward_target(&target_arch);
+
+    let mut crates = SHARED_CRATES.lock().unwrap_or_else(PoisonError::into_inner);
+    if !crates.contains(&shared) {
+        crates.push(shared);
+    }
+    // Later values replace earlier ones, so each call forwards every crate so far.
+    println!("cargo:rustc-env={SHARED_ENV}={}", crates.join("\x1f"));
+    Ok(())
+}
+
+/// Environment variable used to forward `--remap-path-prefix` rules to the macro.
+const REMAP_ENV: &str = "INCLUDE_PACKED_REMAP_PATH_PREFIX";
+
+/// Reads the `--remap-path-prefix` rules passed to rustc for this build.
+fn remap_path_prefixes() -> Vec<(PathBuf, PathBuf)> {
+    let flags = env::var("CARGO_ENCODED_RUSTFLAGS").unwrap_or_default();
+    let mut args = flags.split('\x1f');
+    let mut rules = Vec::new();
+    while let Some(arg) = args.next() {
+        let rule = if arg == "--remap-path-prefix" {
+            args.next()
+        } else {
+            arg.strip_prefix("--remap-path-prefix=")
+        };
+        // Like rustc, split at the last `=` so that `FROM` may itself contain one.
+        if let Some((from, to)) = rule.and_then(|rule| rule.rsplit_once('=')) {
+            rules.push((PathBuf::from(from), PathBuf::from(to)));
+        }
+    }
+    rules
+}
+
+/// Derives the key identifying an asset in symbol names from its canonical path.
+///
+/// Assets inside the crate, or below the [`Config::root`] given as `root`, are keyed by
+/// their path relative to `CARGO_MANIFEST_DIR` or `root`. Assets elsewhere are keyed by
+/// their absolute path after applying any `--remap-path-prefix` rules, so that builds
+/// from different checkout roots agree. The macro performs the same derivation.
+fn asset_key(canonical_path: &Path, root: Option<&Path>) -> Result<PathBuf> {
+    let root = match root {
+        Some(root) => root.to_path_buf(),
+        None => env::var("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .map_err(|_| Error::Var("CARGO_MANIFEST_DIR"))?,
+    };
+    let root = root.canonicalize().unwrap_or(root);
+
+    if let Ok(relative) = canonical_path.strip_prefix(&root) {
+        return Ok(relative.to_path_buf());
+    }
+    // As in rustc, later rules take precedence over earlier ones.
+    for (from, to) in remap_path_prefixes().iter().rev() {
+        if let Ok(rest) = canonical_path.strip_prefix(from) {
+            return Ok(to.join(rest));
+        }
+    }
+    Ok(canonical_path.to_path_buf())
+}
+
+/// State shared by every asset processed in a single [`Config::build`] call.
+struct Packer<'a> {
+    config: &'a Config,
+    /// The level of [`Config::level`], or of the profile's level or the environment
+    /// variables overriding it.
+    level: i32,
+    /// The algorithm of [`Config::algorithm`], or of [`Config::debug_algorithm`] in
+    /// debug builds.
+    algorithm: Algorithm,
+    /// The time budget of [`Config::auto`], unless the level or algorithm is overridden.
+    auto: Option<Duration>,
+    /// Metadata recorded for every asset, such as build provenance.
+    common_meta: Vec<(&'static str, String)>,
+    /// The compiled patterns of [`Config::file`], with their options.
+    files: Vec<(glob::Pattern, &'a FileConfig)>,
+    /// The compiled patterns of [`Config::include`].
+    includes: Vec<glob::Pattern>,
+    /// The compiled patterns of [`Config::exclude`].
+    excludes: Vec<glob::Pattern>,
+    /// The dictionary trained by [`Config::train_dictionary`].
+    dictionary: Option<Dictionary>,
+    /// The base assets of [`Config::delta`], by the paths they were configured with.
+    delta_bases: HashMap<String, DeltaBase>,
+    /// The value of `SOURCE_DATE_EPOCH`, if set.
+    source_date_epoch: Option<u64>,
+    /// The canonical forms of the configured paths, for the assets' logical paths.
+    canonical_roots: Vec<PathBuf>,
+    /// The cipher of [`Config::encrypt`] and the key read for it.
+    #[cfg(feature = "encryption")]
+    encryption: Option<(Cipher, [u8; 32])>,
+    /// The contents of the archive members found by [`Config::from_archive`], by the
+    /// archive's canonical path joined with their path inside it.
+    members: HashMap<PathBuf, Vec<u8>>,
+    /// The compiled patterns of [`Config::transcode`], with their encodings.
+    transcodes: Vec<(glob::Pattern, Encoding)>,
+    /// The compiled patterns of [`Config::image_transform`], with their transforms.
+    #[cfg(feature = "image-pipeline")]
+    image_transforms: Vec<(glob::Pattern, ImageTransform)>,
+    /// The data compressed so far, by the hash of its contents and compression level,
+    /// so that identical files are only compressed once.
+    compressed: Mutex<HashMap<blake3::Hash, Arc<OnceLock<Option<Compressed>>>>>,
+}
+
+/// Data compressed by [`Packer::compress_once`].
+#[derive(Clone)]
+struct Compressed {
+    /// The compressed, and possibly encrypted, data.
+    data: Vec<u8>,
+    /// The end offset of each chunk, if the data was compressed in chunks.
+    chunk_ends: Option<Vec<String>>,
+}
+
+/// The options an asset is packed with, after applying those of [`Config::file`].
+struct FileOptions {
+    level: i32,
+    algorithm: Algorithm,
+    /// The time budget of [`Config::auto`], if it picks the level and algorithm.
+    auto: Option<Duration>,
+    /// The base asset of [`Config::delta`], as configured, if the asset is a patch.
+    delta: Option<String>,
+    align: usize,
+    chunk_size: Option<usize>,
+    #[cfg(feature = "checksums")]
+    verify: bool,
+}
+
+/// A base asset of [`Config::delta`], which its variants are patches against.
+struct DeltaBase {
+    /// The path the macros accept for the base.
+    logical_path: String,
+    /// The base's contents, after any transforms.
+    content: Vec<u8>,
+    /// The BLAKE3 hash of `content`.
+    hash: blake3::Hash,
+}
+
+/// A zstd dictionary shared by all assets of a [`Config::build`] call.
+struct Dictionary {
+    data: Vec<u8>,
+    symbol: String,
+    /// The linkable artifact holding the dictionary.
+    artifact: PathBuf,
+}
+
+impl Dictionary {
+    /// Trains a dictionary on the contents of `files` and writes it to an artifact.
+    fn train(
+        packer: &Packer<'_>,
+        files: &[(PathBuf, fs::Metadata)],
+        max_size: usize,
+    ) -> Result<Self> {
+        let config = packer.config;
+        let samples = files
+            .iter()
+            .map(|(path, _)| packer.read(path))
+            .collect::<Result<Vec<_>>>()?;
+        let data = zstd::dict::from_samples(&samples, max_size).map_err(Error::Dictionary)?;
+
+        let hash = blake3::hash(&data).to_hex();
+        let symbol = symbol_name(
+            config,
+            match &config.symbol_namespace {
+                Some(namespace) => format!("include_packed_{namespace}_dict_{}", &hash[..16]),
+                None => format!("include_packed_dict_{}", &hash[..16]),
+            },
+        );
+        let out_dir = env::var("OUT_DIR").map_err(|_| Error::Var("OUT_DIR"))?;
+        let artifact = write_artifact(config, Path::new(&out_dir), &symbol, &data, 1)?;
+        Ok(Self {
+            data,
+            symbol,
+            artifact,
+        })
+    }
+}
+
+impl<'a> Packer<'a> {
+    fn new(config: &'a Config) -> Result<Self> {
+        let profile = env::var("PROFILE");
+        let (profile_level, profile_algorithm) = match profile.as_deref() {
+            Ok("debug") => (config.debug_level, config.debug_algorithm),
+            Ok("release") => (config.release_level, None),
+            _ => (None, None),
+        };
+        let mut files = Vec::new();
+        for (pattern, file) in &config.files {
+            let compiled = compile_pattern(pattern)?;
+            files.extend(compiled.into_iter().map(|compiled| (compiled, file)));
+        }
+        let compile_all = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|pattern| compile_pattern(pattern))
+                .collect::<Result<Vec<_>>>()
+                .map(|compiled| compiled.concat())
+        };
+        let env_level = env_level()?;
+        let overridden =
+            env_level.is_some() || profile_level.is_some() || profile_algorithm.is_some();
+        Ok(Self {
+            config,
+            level: env_level.or(profile_level).unwrap_or(config.level),
+            algorithm: profile_algorithm.unwrap_or(config.algorithm),
+            auto: config.auto.filter(|_| !overridden),
+            common_meta: Vec::new(),
+            files,
+            includes: compile_all(&config.includes)?,
+            excludes: compile_all(&config.excludes)?,
+            dictionary: None,
+            delta_bases: HashMap::new(),
+            source_date_epoch: None,
+            canonical_roots: Vec::new(),
+            #[cfg(feature = "encryption")]
+            encryption: None,
+            members: HashMap::new(),
+            transcodes: {
+                let mut transcodes = Vec::new();
+                for (pattern, encoding) in &config.transcodes {
+                    let compiled = compile_pattern(pattern)?;
+                    transcodes.extend(compiled.into_iter().map(|compiled| (compiled, *encoding)));
+                }
+                transcodes
+            },
+            #[cfg(feature = "image-pipeline")]
+            image_transforms: {
+                let mut image_transforms = Vec::new();
+                for (pattern, transform) in &config.image_transforms {
+                    let compiled = compile_pattern(pattern)?;
+                    image_transforms
+                        .extend(compiled.into_iter().map(|compiled| (compiled, *transform)));
+                }
+                image_transforms
+            },
+            compressed: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Reads the asset at `path` and applies the functions of [`Config::transform`].
+    fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        let content = self.read_source(path)?;
+        if !self.config.transforms() {
+            return Ok(content);
+        }
+        let logical_path = self.config.logical_path(&self.canonical_roots, path)?;
+        self.transform(Path::new(&logical_path), content)
+    }
+
+    /// Reads the file or archive member at `path`, failing if it is an unfetched Git LFS
+    /// pointer.
+    fn read_source(&self, path: &Path) -> Result<Vec<u8>> {
+        if let Some(data) = self.members.get(path) {
+            return Ok(data.clone());
+        }
+        let content = fs::read(path)?;
+        if !is_lfs_pointer(&content) {
+            return Ok(content);
+        }
+        if self.config.lfs_pull && lfs_pull(path) {
+            let content = fs::read(path)?;
+            if !is_lfs_pointer(&content) {
+                return Ok(content);
+            }
+        }
+        Err(Error::LfsPointer(
+            self.config.logical_path(&self.canonical_roots, path)?,
+        ))
+    }
+
+    /// Returns the size of the file or archive member at `path`, whose file system
+    /// metadata, or that of its archive, is `metadata`.
+    fn source_len(&self, path: &Path, metadata: &fs::Metadata) -> u64 {
+        self.members
+            .get(path)
+            .map_or(metadata.len(), |data| data.len() as u64)
+    }
+
+    /// Applies the transforms of [`Config::transcode`], [`Config::image_transform`], and
+    /// [`Config::transform`] to the asset at `logical_path`.
+    fn transform(&self, logical_path: &Path, mut content: Vec<u8>) -> Result<Vec<u8>> {
+        for (pattern, encoding) in &self.transcodes {
+            if pattern.matches_path_with(logical_path, PATTERN_OPTIONS) {
+                content = encoding
+                    .decode(&content)
+                    .ok_or_else(|| Error::Transcode(logical_path.display().to_string(), *encoding))?
+                    .into_bytes();
+            }
+        }
+        #[cfg(feature = "image-pipeline")]
+        let content = {
+            let mut content = content;
+            for (pattern, transform) in &self.image_transforms {
+                if pattern.matches_path_with(logical_path, PATTERN_OPTIONS) {
+                    content = transform
+                        .apply(&content)
+                        .map_err(|err| Error::Image(logical_path.display().to_string(), err))?;
+                }
+            }
+            content
+        };
+        Ok(self
+            .config
+            .transforms
+            .iter()
+            .fold(content, |content, transform| {
+                (transform.0)(logical_path, content)
+            }))
+    }
+
+    /// Compresses and encrypts `content` with `options`, whose contents and options hash
+    /// to `hash`, unless a file with identical contents was already compressed by this
+    /// build.
+    fn compress_once(
+        &self,
+        hash: blake3::Hash,
+        options: &FileOptions,
+        content: &[u8],
+    ) -> Result<Compressed> {
+        let slot = Arc::clone(
+            self.compressed
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry(hash)
+                .or_default(),
+        );
+        let mut failure = None;
+        let compressed = slot.get_or_init(|| {
+            self.compress_chunks(options, content)
+                .map_err(|err| failure = Some(err))
+                .ok()
+        });
+        if let Some(err) = failure {
+            return Err(err);
+        }
+        compressed.clone().ok_or_else(|| {
+            Error::Generic("an asset with identical contents failed to compress".into())
+        })
+    }
+
+    /// Compresses `content` in chunks of [`Config::chunk_size`] and encrypts it.
+    fn compress_chunks(&self, options: &FileOptions, content: &[u8]) -> Result<Compressed> {
+        let FileOptions {
+            algorithm, level, ..
+        } = *options;
+        let base = options
+            .delta
+            .as_ref()
+            .map(|base| &*self.delta_bases[base].content);
+        let (data, chunk_ends) = match options.chunk_size {
+            Some(chunk_size) if algorithm == Algorithm::Zstd && content.len() > chunk_size => {
+                // Every chunk is a zstd frame of its own, and the frames concatenated still
+                // decompress to the whole asset.
+                let mut compressed = Vec::new();
+                let mut ends = Vec::new();
+                for chunk in content.chunks(chunk_size) {
+                    compressed.extend(self.compress(algorithm, chunk, level, base)?);
+                    ends.push(compressed.len().to_string());
+                }
+                (compressed, Some(ends))
+            }
+            _ => (self.compress(algorithm, content, level, base)?, None),
+        };
+        #[cfg(feature = "encryption")]
+        let data = match &self.encryption {
+            Some((cipher, key)) => crypto::seal(*cipher, key, &data),
+            None => data,
+        };
+        Ok(Compressed { data, chunk_ends })
+    }
+
+    /// Compresses `content`, or a patch against `base` if given, reusing the result of an
+    /// earlier build from the cache.
+    fn compress(
+        &self,
+        algorithm: Algorithm,
+        content: &[u8],
+        level: i32,
+        base: Option<&[u8]>,
+    ) -> Result<Vec<u8>> {
+        let (dictionary, zstd) = match base {
+            Some(base) => (
+                codec::Dictionary::Raw(base),
+                self.config.zstd.for_patch(base.len() + content.len()),
+            ),
+            None => (
+                codec::Dictionary::Trained(self.dictionary_for(algorithm)),
+                self.config.zstd,
+            ),
+        };
+        let Some(cache_dir) = &self.config.cache_dir else {
+            return compress(algorithm, dictionary, content, level, &zstd);
+        };
+
+        let hash = blake3::hash(content);
+        let mut name = format!("{}-{}-{level}", hash.to_hex(), algorithm.name());
+        match dictionary {
+            codec::Dictionary::Trained([]) => {}
+            codec::Dictionary::Trained(dictionary) => {
+                name.push_str(&format!("-{}", &blake3::hash(dictionary).to_hex()[..16]));
+            }
+            codec::Dictionary::Raw(base) => {
+                name.push_str(&format!("-base-{}", &blake3::hash(base).to_hex()[..16]));
+            }
+        }
+        if algorithm == Algorithm::Zstd && zstd != ZstdOptions::default() {
+            let options = format!("{zstd:?}");
+            name.push_str(&format!(
+                "-{}",
+                &blake3::hash(options.as_bytes()).to_hex()[..16]
+            ));
+        }
+        let entry = cache_dir.join(name);
+        if let Ok(compressed) = fs::read(&entry) {
+            return Ok(compressed);
+        }
+
+        let compressed = compress(algorithm, dictionary, content, level, &zstd)?;
+        fs::create_dir_all(cache_dir)?;
+        // Write to a temporary file first, so that concurrent builds sharing the cache
+        // never observe a partially written entry.
+        let temporary = entry.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&temporary, &compressed)?;
+        fs::rename(&temporary, &entry)?;
+        Ok(compressed)
+    }
+
+    /// Returns the dictionary that assets compressed with `algorithm` use, if any.
+    fn dictionary_for(&self, algorithm: Algorithm) -> &[u8] {
+        match &self.dictionary {
+            Some(dictionary) if algorithm == Algorithm::Zstd => &dictionary.data,
+            _ => &[],
+        }
+    }
+
+    /// Returns the compression level for the asset with the given key.
+    fn level_for(&self, key: &Path) -> i32 {
+        self.files_for(key)
+            .filter_map(|file| file.level)
+            .last()
+            .unwrap_or(self.level)
+    }
+
+    /// Returns the options of the asset with the given key and logical path, after
+    /// applying those of [`Config::file`].
+    fn options_for(&self, key: &Path, logical_path: &Path) -> FileOptions {
+        let config = self.config;
+        let mut algorithm = None;
+        let mut tuned = false;
+        let mut options = FileOptions {
+            level: self.level,
+            algorithm: self.algorithm,
+            auto: None,
+            delta: None,
+            align: config.align,
+            chunk_size: config.chunk_size,
+            #[cfg(feature = "checksums")]
+            verify: config.verify,
+        };
+        for file in self.files_for(key) {
+            algorithm = file.algorithm.or(algorithm);
+            tuned |= file.algorithm.is_some() || file.level.is_some();
+            options.level = file.level.unwrap_or(options.level);
+            options.align = file.align.unwrap_or(options.align);
+            options.chunk_size = file.chunk_size.or(options.chunk_size);
+            options.delta = file.delta_base.clone().or(options.delta);
+            #[cfg(feature = "checksums")]
+            {
+                options.verify = file.verify.unwrap_or(options.verify);
+            }
+        }
+        options.algorithm = algorithm.unwrap_or_else(|| self.algorithm_for(logical_path));
+        // The base itself may match the pattern of its variants.
+        options.delta = options.delta.filter(|base| Path::new(base) != key);
+        if options.delta.is_some() {
+            options.algorithm = Algorithm::Zstd;
+            options.chunk_size = None;
+        } else if !tuned && options.algorithm != Algorithm::Store {
+            options.auto = self.auto;
+        }
+        options
+    }
+
+    /// Reads the base assets of [`Config::delta`] from among `files`, failing if any
+    /// is missing or a patch itself.
+    fn read_delta_bases(
+        &self,
+        files: &[(PathBuf, fs::Metadata)],
+    ) -> Result<HashMap<String, DeltaBase>> {
+        let mut bases = HashMap::new();
+        let wanted = self
+            .files
+            .iter()
+            .filter_map(|(_, file)| file.delta_base.as_deref())
+            .collect::<Vec<_>>();
+        if wanted.is_empty() {
+            return Ok(bases);
+        }
+        for (path, _) in files {
+            let key = asset_key(path, self.config.root.as_deref())?;
+            let Some(base) = wanted.iter().find(|base| Path::new(base) == key) else {
+                continue;
+            };
+            let logical_path = self.config.logical_path(&self.canonical_roots, path)?;
+            if self
+                .options_for(&key, Path::new(&logical_path))
+                .delta
+                .is_some()
+            {
+                return Err(Error::Generic(format!(
+                    "delta base '{base}' is a patch against another asset itself"
+                )));
+            }
+            let content = self.read(path)?;
+            let delta_base = DeltaBase {
+                logical_path,
+                hash: blake3::hash(&content),
+                content,
+            };
+            bases.insert((*base).to_owned(), delta_base);
+        }
+        if let Some(missing) = wanted.iter().find(|base| !bases.contains_key(**base)) {
+            return Err(Error::Generic(format!(
+                "delta base '{missing}' is not among the packed assets"
+            )));
+        }
+        Ok(bases)
+    }
+
+    /// Returns the algorithm and level that [`Config::auto`] picks for `content`: the
+    /// candidate compressing a sample of it best among those estimated to compress all
+    /// of it within `budget`.
+    fn auto_select(&self, budget: Duration, content: &[u8]) -> Result<(Algorithm, i32)> {
+        // The choice also depends on the dictionary and zstd parameters the candidates
+        // are compressed with.
+        let entry = self.config.cache_dir.as_ref().map(|dir| {
+            let dictionary = self.dictionary_for(Algorithm::Zstd);
+            let mut hasher = blake3::Hasher::new();
+            hasher.update(&(content.len() as u64).to_le_bytes());
+            hasher.update(content);
+            hasher.update(&(dictionary.len() as u64).to_le_bytes());
+            hasher.update(dictionary);
+            hasher.update(format!("{:?}", self.config.zstd).as_bytes());
+            let hash = hasher.finalize().to_hex();
+            dir.join(format!("auto-{hash}-{}", budget.as_millis()))
+        });
+        if let Some(entry) = &entry
+            && let Ok(choice) = fs::read_to_string(entry)
+            && let Some(candidate) = AUTO_CANDIDATES
+                .iter()
+                .find(|(algorithm, level)| choice == format!("{} {level}", algorithm.name()))
+        {
+            return Ok(*candidate);
+        }
+
+        let sample = auto_sample(content);
+        let scale = content.len() as f64 / sample.len().max(1) as f64;
+        let mut best = (AUTO_CANDIDATES[0], sample.len());
+        for &(algorithm, level) in AUTO_CANDIDATES {
+            let started = Instant::now();
+            let dictionary = codec::Dictionary::Trained(self.dictionary_for(algorithm));
+            let len = compress(algorithm, dictionary, &sample, level, &self.config.zstd)?.len();
+            // The candidates grow slower, so the remaining ones exceed the budget as well.
+            if started.elapsed().mul_f64(scale) > budget {
+                break;
+            }
+            if len < best.1 {
+                best = ((algorithm, level), len);
+            }
+        }
+
+        let ((algorithm, level), _) = best;
+        if let Some(entry) = &entry {
+            fs::create_dir_all(entry.parent().unwrap_or(Path::new(".")))?;
+            fs::write(entry, format!("{} {level}", algorithm.name()))?;
+        }
+        Ok((algorithm, level))
+    }
+
+    /// Returns the options of [`Config::file`] whose patterns match the given key, in
+    /// the order they were set.
+    fn files_for<'b>(&'b self, key: &'b Path) -> impl Iterator<Item = &'a FileConfig> + 'b {
+        self.files
+            .iter()
+            .filter(|(pattern, _)| pattern.matches_path_with(key, PATTERN_OPTIONS))
+            .map(|(_, file)| *file)
+    }
+
+    /// Returns the algorithm for the asset at `logical_path`, which is
+    /// [`Algorithm::Store`] for the extensions of [`Config::store_extensions`] unless the
+    /// asset is transformed.
+    fn algorithm_for(&self, logical_path: &Path) -> Algorithm {
+        let stored = logical_path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                self.config
+                    .store_extensions
+                    .iter()
+                    .any(|stored| stored.eq_ignore_ascii_case(extension))
+            });
+        if stored && !self.rewrites(logical_path) {
+            Algorithm::Store
+        } else {
+            self.algorithm
+        }
+    }
+
+    /// Returns whether any transform may rewrite the asset at `logical_path`.
+    fn rewrites(&self, logical_path: &Path) -> bool {
+        if self
+            .transcodes
+            .iter()
+            .any(|(pattern, _)| pattern.matches_path_with(logical_path, PATTERN_OPTIONS))
+        {
+            return true;
+        }
+        #[cfg(feature = "image-pipeline")]
+        if self
+            .image_transforms
+            .iter()
+            .any(|(pattern, _)| pattern.matches_path_with(logical_path, PATTERN_OPTIONS))
+        {
+            return true;
+        }
+        !self.config.transforms.is_empty()
+    }
+
+    /// Returns whether the asset with the given key passes the patterns of
+    /// [`Config::include`] and [`Config::exclude`].
+    fn includes(&self, key: &Path) -> bool {
+        let matches = |pattern: &glob::Pattern| pattern.matches_path_with(key, PATTERN_OPTIONS);
+        (self.includes.is_empty() || self.includes.iter().any(matches))
+            && !self.excludes.iter().any(matches)
+    }
+}
+
+/// How asset keys are matched against glob patterns: `*` does not match across `/`.
+const PATTERN_OPTIONS: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Compiles the glob `pattern`, expanding its brace alternatives into a pattern each.
+fn compile_pattern(pattern: &str) -> Result<Vec<glob::Pattern>> {
+    expand_braces(pattern)
+        .iter()
+        .map(|expanded| {
+            glob::Pattern::new(expanded)
+                .map_err(|err| Error::InvalidPattern(pattern.to_owned(), err))
+        })
+        .collect()
+}
+
+/// Expands the `{a,b}` alternatives of `pattern`, which may be nested.
+fn expand_braces(pattern: &str) -> Vec<String> {
+    let Some(open) = pattern.find('{') else {
+        return vec![pattern.to_owned()];
+    };
+    // Find the matching closing brace and the commas at the outermost level.
+    let mut depth = 0;
+    let mut splits = vec![open];
+    let mut close = None;
+    for (index, c) in pattern
+        .char_indices()
+        .skip_while(|(index, _)| *index <= open)
+    {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => {
+                close = Some(index);
+                break;
+            }
+            '}' => depth -= 1,
+            ',' if depth == 0 => splits.push(index),
+            _ => {}
+        }
+    }
+    let Some(close) = close else {
+        return vec![pattern.to_owned()];
+    };
+    splits.push(close);
+
+    let (prefix, suffix) = (&pattern[..open], &pattern[close + 1..]);
+    splits
+        .windows(2)
+        .flat_map(|bounds| {
+            expand_braces(&format!(
+                "{prefix}{}{suffix}",
+                &pattern[bounds[0] + 1..bounds[1]]
+            ))
+        })
+        .collect()
+}
+
+/// The algorithms and levels that [`Config::auto`] tries, from the fastest to the most
+/// thorough.
+const AUTO_CANDIDATES: &[(Algorithm, i32)] = &[
+    (Algorithm::Store, 0),
+    #[cfg(feature = "lz4")]
+    (Algorithm::Lz4, 0),
+    (Algorithm::Zstd, 3),
+    (Algorithm::Zstd, 9),
+    #[cfg(feature = "brotli")]
+    (Algorithm::Brotli, 9),
+    (Algorithm::Zstd, 19),
+    #[cfg(feature = "brotli")]
+    (Algorithm::Brotli, 11),
+];
+
+/// The number of bytes of each asset that [`Config::auto`] compresses with every
+/// candidate.
+const AUTO_SAMPLE_LEN: usize = 256 * 1024;
+
+/// Returns up to [`AUTO_SAMPLE_LEN`] bytes of `content`, taken from evenly spaced
+/// slices of it, so that the sample represents the whole asset.
+fn auto_sample(content: &[u8]) -> Vec<u8> {
+    const SLICES: usize = 4;
+    const SLICE_LEN: usize = AUTO_SAMPLE_LEN / SLICES;
+
+    if content.len() <= AUTO_SAMPLE_LEN {
+        return content.to_vec();
+    }
+    let stride = (content.len() - SLICE_LEN) / (SLICES - 1);
+    (0..SLICES)
+        .flat_map(|slice| &content[slice * stride..][..SLICE_LEN])
+        .copied()
+        .collect()
+}
+
+/// Environment variable overriding [`Config::level`].
+const LEVEL_ENV: &str = "INCLUDE_PACKED_LEVEL";
+
+/// Returns the environment variables overriding [`Config::level`], most specific first:
+/// the one for the current profile, e.g. `INCLUDE_PACKED_LEVEL_RELEASE`, and
+/// `INCLUDE_PACKED_LEVEL`.
+fn level_vars() -> Vec<String> {
+    let mut vars = Vec::new();
+    if let Ok(profile) = env::var("PROFILE") {
+        vars.push(format!("{LEVEL_ENV}_{}", profile.to_uppercase()));
+    }
+    vars.push(LEVEL_ENV.to_owned());
+    vars
+}
+
+/// Reads the compression level set by the first of the [`level_vars`] that is set.
+fn env_level() -> Result<Option<i32>> {
+    for var in level_vars() {
+        if let Ok(value) = env::var(&var) {
+            return value
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(|_| Error::InvalidLevel(var, value));
+        }
+    }
+    Ok(None)
+}
+
+/// Reads `SOURCE_DATE_EPOCH`, which caps recorded timestamps for reproducible builds.
+fn source_date_epoch() -> Result<Option<u64>> {
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    match env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => epoch
+            .parse()
+            .map(Some)
+            .map_err(|_| Error::Generic(format!("invalid SOURCE_DATE_EPOCH '{epoch}'"))),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Returns the name of the symbol `symbol`, replaced by an opaque one if `config`
+/// obfuscates its assets.
+fn symbol_name(config: &Config, symbol: String) -> String {
+    if !config.obfuscate {
+        return symbol;
+    }
+    let hash = blake3::keyed_hash(obfuscation_key(), symbol.as_bytes()).to_hex();
+    format!("_{}", &hash[..32])
+}
+
+/// Returns the secret that obfuscated symbol names are keyed by, drawn once per run of
+/// the build script, or derived from `SOURCE_DATE_EPOCH` for reproducible builds.
+fn obfuscation_key() -> &'static [u8; 32] {
+    static KEY: OnceLock<[u8; 32]> = OnceLock::new();
+    KEY.get_or_init(|| {
+        use std::hash::{BuildHasher as _, RandomState};
+
+        let mut hasher = blake3::Hasher::new_derive_key("include_packed obfuscated symbols");
+        hasher.update(env::var("CARGO_PKG_NAME").unwrap_or_default().as_bytes());
+        hasher.update(&[0]);
+        if let Ok(Some(epoch)) = source_date_epoch() {
+            hasher.update(&epoch.to_le_bytes());
+        } else {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos();
+            hasher.update(&now.to_le_bytes());
+            hasher.update(&std::process::id().to_le_bytes());
+            hasher.update(&RandomState::new().hash_one(now).to_le_bytes());
+        }
+        hasher.finalize().into()
+    })
+}
+
+/// Returns the modification time recorded for an asset, in seconds since the Unix
+/// epoch, clamped to `source_date_epoch` so that fresh checkouts build reproducibly.
+fn modified_secs(metadata: &fs::Metadata, source_date_epoch: Option<u64>) -> Option<u64> {
+    let modified = metadata
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+    Some(source_date_epoch.map_or(modified, |epoch| modified.min(epoch)))
+}
+
+/// Collects the build provenance metadata recorded by [`Config::provenance`].
+fn provenance_meta(source_date_epoch: Option<u64>) -> Result<Vec<(&'static str, String)>> {
+    let manifest_dir =
+        env::var("CARGO_MANIFEST_DIR").map_err(|_| Error::Var("CARGO_MANIFEST_DIR"))?;
+    let crate_name = env::var("CARGO_PKG_NAME").map_err(|_| Error::Var("CARGO_PKG_NAME"))?;
+    let crate_version =
+        env::var("CARGO_PKG_VERSION").map_err(|_| Error::Var("CARGO_PKG_VERSION"))?;
+
+    let build_timestamp = source_date_epoch.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs())
+    });
+
+    let mut meta = vec![
+        ("crate_name", crate_name),
+        ("crate_version", crate_version),
+        ("build_timestamp", build_timestamp.to_string()),
+    ];
+
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(&manifest_dir)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map(|stdout| stdout.trim().to_owned())
+    };
+    if let Some(commit) = git(&["rev-parse", "HEAD"]) {
+        // Repack when the checked-out commit changes. The reflog is updated on every
+        // commit and checkout; fall back to HEAD itself if it is disabled.
+        if let Some(git_dir) = git(&["rev-parse", "--absolute-git-dir"]) {
+            let git_dir = PathBuf::from(git_dir);
+            let reflog = git_dir.join("logs").join("HEAD");
+            let watched = if reflog.exists() {
+                reflog
+            } else {
+                git_dir.join("HEAD")
+            };
+            println!("cargo:rerun-if-changed={}", watched.display());
+        }
+        meta.push(("git_commit", commit));
+    }
+
+    Ok(meta)
+}
+
+/// Returns whether `content` is a Git LFS pointer rather than the file it points to.
+///
+/// Pointers are text files of less than 1024 bytes that start with the spec version.
+fn is_lfs_pointer(content: &[u8]) -> bool {
+    content.len() < 1024 && content.starts_with(b"version https://git-lfs.github.com/spec/v1\n")
+}
+
+/// Runs `git lfs pull` for the file at `path`, returning whether it succeeded.
+fn lfs_pull(path: &Path) -> bool {
+    let Ok(path) = fs::canonicalize(path) else {
+        return false;
+    };
+    let Some(dir) = path.parent() else {
+        return false;
+    };
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+    };
+    // `--include` takes paths relative to the root of the working tree.
+    let Some(top_level) = git(&["rev-parse", "--show-toplevel"])
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+    else {
+        return false;
+    };
+    let Ok(relative) = path.strip_prefix(fs::canonicalize(top_level.trim()).unwrap_or_default())
+    else {
+        return false;
+    };
+    let include = relative.to_string_lossy().replace('\\', "/");
+    git(&["lfs", "pull", "--include", &include]).is_some()
+}
+
+/// Writes an asset's `.meta` file, which the macro reads to resolve the asset.
+///
+/// The file consists of `key=value` lines. Values must not contain line breaks.
+fn write_meta(path: &Path, entries: &[(&str, String)]) -> Result<()> {
+    let mut contents = String::new();
+    for (key, value) in entries {
+        debug_assert!(!value.contains(['\n', '\r']));
+        contents.push_str(&format!("{key}={value}\n"));
+    }
+    record_owned_file(path)?;
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+/// Formats `path` the way the macros refer to assets, with `/` as separator.
+fn logical_path(path: &Path) -> String {
+    path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Name of the file in `OUT_DIR` that lists every asset for `include_packed_manifest!`.
+const MANIFEST_FILE: &str = "include_packed.manifest";
+
+/// Name of the file in `OUT_DIR` that lists the logical paths of the assets for
+/// `include_packed_registry!`.
+const REGISTRY_FILE: &str = "include_packed.registry";
+
+/// Name of the file in `OUT_DIR` that lists the fingerprint of every directory walked by
+/// the build script, see [`directory_fingerprint`].
+const FINGERPRINTS_FILE: &str = "include_packed.fingerprints";
+
+/// Name of the file in `OUT_DIR` that maps the aliases of [`Config::alias`] to logical
+/// paths.
+const ALIASES_FILE: &str = "include_packed.aliases";
+
+/// Name of the file in `OUT_DIR` holding the module of [`Config::generate_module`].
+const MODULE_FILE: &str = "packed_assets.rs";
+
+/// Name of the file in `OUT_DIR` that lists the files written by the last run of the
+/// build script, see [`remove_stale_files`].
+const OWNED_FILE: &str = "include_packed.owned";
+
+/// Set once the current build script run has removed the files of the previous run.
+static STALE_REMOVED: AtomicBool = AtomicBool::new(false);
+
+/// The names of the files recorded by [`record_owned_file`] in the current build script
+/// run.
+static OWNED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Set once the current build script run has started a fresh manifest and registry.
+static MANIFEST_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// The assets declared in the module of [`Config::generate_module`] by the current build
+/// script run.
+static MODULE: Mutex<Option<Module>> = Mutex::new(None);
+
+/// An asset listed in the manifest.
+struct ManifestEntry {
+    path: String,
+    len: usize,
+    compressed_len: usize,
+    algorithm: Algorithm,
+    modified: Option<u64>,
+}
+
+/// Removes the artifacts and metadata written by the previous run of the build script,
+/// unless this run already did.
+///
+/// Every run writes the files of the assets it packs again, so this only removes those
+/// of renamed and deleted assets, which would otherwise be left behind in `OUT_DIR`.
+fn remove_stale_files() -> Result<()> {
+    if STALE_REMOVED.swap(true, Ordering::Relaxed) {
+        return Ok(());
+    }
+    let out_dir = PathBuf::from(env::var("OUT_DIR").map_err(|_| Error::Var("OUT_DIR"))?);
+    let owned = out_dir.join(OWNED_FILE);
+    let names = match fs::read_to_string(&owned) {
+        Ok(names) => names,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    for name in names.lines() {
+        match fs::remove_file(out_dir.join(name)) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+            _ => {}
+        }
+    }
+    fs::remove_file(owned)?;
+    Ok(())
+}
+
+/// Records the file at `path` as written by this run of the build script, for the next
+/// run to remove. Only files directly in `OUT_DIR` are recorded.
+///
+/// Files are recorded before they are written, so that those of a run that fails
+/// halfway are removed as well.
+fn record_owned_file(path: &Path) -> Result<()> {
+    use std::io::Write as _;
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").map_err(|_| Error::Var("OUT_DIR"))?);
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return Ok(());
+    };
+    if path.parent() != Some(&out_dir) {
+        return Ok(());
+    }
+    let mut owned = OWNED.lock().unwrap_or_else(PoisonError::into_inner);
+    if owned.iter().any(|owned| owned == name) {
+        return Ok(());
+    }
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(out_dir.join(OWNED_FILE))?
+        .write_all(format!("{name}\n").as_bytes())?;
+    owned.push(name.to_owned());
+    Ok(())
+}
+
+/// Adds `entries` to the manifest read by `include_packed_manifest!`, and if
+/// `registered`, to the registry read by `include_packed_registry!`, along with the
+/// `fingerprints` of the directories they were found in and their `aliases`.
+///
+/// A build script may call [`Config::build`] several times, so the first call of each
+/// run replaces the files of the previous run and later calls append to them. Each line
+/// of the manifest holds an asset's size, compressed size, algorithm, modification time
+/// (empty if unknown), and logical path, separated by tabs. Each line of the registry
+/// holds a logical path, each line of the fingerprints a hexadecimal fingerprint and a
+/// directory's canonical path, and each line of the aliases a name and a logical path,
+/// separated by a tab.
+fn append_manifest(
+    entries: &[ManifestEntry],
+    fingerprints: &[(PathBuf, u64)],
+    aliases: &[(String, String)],
+    registered: bool,
+) -> Result<()> {
+    use std::io::Write as _;
+
+    let out_dir = env::var("OUT_DIR").map_err(|_| Error::Var("OUT_DIR"))?;
+    let started = MANIFEST_STARTED.swap(true, Ordering::Relaxed);
+    let open = |name| {
+        let path = Path::new(&out_dir).join(name);
+        if started {
+            fs::OpenOptions::new().append(true).open(path)
+        } else {
+            fs::File::create(path)
+        }
+    };
+    let mut file = open(MANIFEST_FILE)?;
+    let mut registry = open(REGISTRY_FILE)?;
+    let mut fingerprints_file = open(FINGERPRINTS_FILE)?;
+    let mut aliases_file = open(ALIASES_FILE)?;
+    let mut contents = String::new();
+    for entry in entries {
+        debug_assert!(!entry.path.contains(['\n', '\r']));
+        let modified = entry.modified.map(|secs| secs.to_string());
+        contents.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\n",
+            entry.len,
+            entry.compressed_len,
+            entry.algorithm.name(),
+            modified.unwrap_or_default(),
+            entry.path
+        ));
+    }
+    file.write_all(contents.as_bytes())?;
+    if registered {
+        let paths = entries
+            .iter()
+            .map(|entry| format!("{}\n", entry.path))
+            .collect::<String>();
+        registry.write_all(paths.as_bytes())?;
+    }
+    let fingerprints = fingerprints
+        .iter()
+        .map(|(dir, fingerprint)| format!("{fingerprint:016x}\t{}\n", dir.display()))
+        .collect::<String>();
+    fingerprints_file.write_all(fingerprints.as_bytes())?;
+    let aliases = aliases
+        .iter()
+        .map(|(name, path)| format!("{name}\t{path}\n"))
+        .collect::<String>();
+    aliases_file.write_all(aliases.as_bytes())?;
+    Ok(())
 }
 
-impl Config {
-    /// Creates a new configuration for a given asset path.
-    ///
-    /// The path can be a single file or a directory and should be relative to
-    /// the crate root (`CARGO_MANIFEST_DIR`).
-    pub fn new(path: impl AsRef<Path>) -> Self {
-        Self {
-            path: path.as_ref().to_path_buf(),
-            level: DEFAULT_COMPRESSION_LEVEL,
+/// An asset declared in the module of [`Config::generate_module`].
+struct ModuleItem {
+    path: String,
+    len: usize,
+    compressed_len: usize,
+    hash: blake3::Hash,
+}
+
+/// The assets declared in the module of [`Config::generate_module`].
+#[derive(Default)]
+struct Module {
+    /// The declared assets, with the names of their statics and enum variants.
+    items: Vec<(String, String, ModuleItem)>,
+    statics: HashSet<String>,
+    variants: HashSet<String>,
+}
+
+impl Module {
+    /// Declares `item`, naming its static and variant after its path.
+    fn declare(&mut self, item: ModuleItem) {
+        let words = item
+            .path
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>();
+        let mut name = words.join("_").to_ascii_uppercase();
+        let mut variant = words
+            .iter()
+            .map(|word| word[..1].to_ascii_uppercase() + &word[1..])
+            .collect::<String>();
+        if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+            name.insert_str(0, "ASSET_");
+            variant.insert_str(0, "Asset");
         }
+        let name = unique_name(&mut self.statics, &name, "_");
+        let variant = unique_name(&mut self.variants, &variant, "");
+        self.items.push((name, variant, item));
     }
 
-    /// Sets the zstd compression level (1-21).
-    ///
-    /// Higher levels provide better compression at the cost of slower build times.
-    /// If not set, a default level of `6` is used.
-    #[must_use]
-    pub const fn level(mut self, level: i32) -> Self {
-        self.level = level;
-        self
-    }
+    /// Returns the Rust source of the module.
+    fn source(&self) -> String {
+        let mut source = String::from("// @generated by the build script of include_packed.\n");
+        let mut variants = String::new();
+        let mut arms = String::new();
+        for (name, variant, item) in &self.items {
+            let docs = [
+                format!(" The packed asset `{}`.", item.path),
+                String::new(),
+                format!(
+                    " {} bytes, {} bytes compressed. BLAKE3 hash: `{}`.",
+                    item.len,
+                    item.compressed_len,
+                    item.hash.to_hex()
+                ),
+            ]
+            .iter()
+            .map(|doc| format!("#[doc = {doc:?}]\n"))
+            .collect::<String>();
+            source.push_str(&format!(
+                "\n{docs}#[allow(dead_code)]\npub static {name}: ::include_packed::PackedAsset =\n    ::include_packed::include_packed_asset!({:?});\n",
+                item.path
+            ));
+            for line in docs.lines() {
+                variants.push_str(&format!("    {line}\n"));
+            }
+            variants.push_str(&format!("    {variant},\n"));
+            arms.push_str(&format!("            Self::{variant} => &{name},\n"));
+        }
+        let all = self
+            .items
+            .iter()
+            .map(|(_, variant, _)| format!("Self::{variant}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        source.push_str(&format!(
+            r#"
+/// Every asset declared in this module, for matching exhaustively over them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(dead_code, clippy::enum_variant_names)]
+pub enum PackedAsset {{
+{variants}}}
 
-    /// Runs the asset packing process with the specified configuration.
-    ///
-    /// This is the final method that should be called in the builder chain.
-    /// It handles all platform-specific logic internally, preparing assets for
-    /// the [`include_packed!`](`crate::include_packed`) macro.
-    ///
-    /// # Errors
-    /// Returns an [`Error`] if any part of the build process fails, such as file I/O
-    /// or object file creation.
-    pub fn build(self) -> Result<()> {
-        // Get the target architecture from the environment variable Cargo provides.
-        let target_arch =
-            env::var("CARGO_CFG_TARGET_ARCH").map_err(|_| Error::Var("CARGO_CFG_TARGET_ARCH"))?;
+#[allow(dead_code)]
+impl PackedAsset {{
+    /// Every asset, in the order they were packed.
+    pub const ALL: &'static [Self] = &[{all}];
 
-        // Set an environment variable for the procedural macro to read. This is the
-        // primary communication channel to determine the build strategy (native vs. wasm).
-        println!("cargo:rustc-env=INCLUDE_PACKED_TARGET_ARCH={target_arch}");
+    /// Returns the asset's handle.
+    pub const fn asset(self) -> &'static ::include_packed::PackedAsset {{
+        match self {{
+{arms}        }}
+    }}
 
-        // Run the native asset packer ONLY if the target is not wasm32.
-        if target_arch != "wasm32" {
-            make_includable_with_level(&self.path, self.level)?;
-        }
-        Ok(())
+    /// Returns the asset's logical path.
+    pub const fn path(self) -> &'static str {{
+        self.asset().path()
+    }}
+
+    /// Decompresses the asset.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as `include_packed::PackedAsset::decompress`.
+    pub fn bytes(self) -> ::std::vec::Vec<u8> {{
+        self.asset().decompress()
+    }}
+}}
+"#
+        ));
+        source
     }
 }
 
-/// The default compression level used by [`make_includable`].
-pub const DEFAULT_COMPRESSION_LEVEL: i32 = 6;
+/// Returns `base`, or if `names` already contains it, `base` followed by `separator`
+/// and the first numeric suffix that makes it unique, after adding it to `names`.
+fn unique_name(names: &mut HashSet<String>, base: &str, separator: &str) -> String {
+    let mut name = base.to_owned();
+    let mut suffix = 1;
+    while !names.insert(name.clone()) {
+        suffix += 1;
+        name = format!("{base}{separator}{suffix}");
+    }
+    name
+}
 
-/// A specialized `Result` type for build script operations.
-pub type Result<T> = std::result::Result<T, Error>;
+/// Declares `items` in the module of [`Config::generate_module`].
+///
+/// A build script may call [`Config::build`] several times, so each call writes the
+/// module again with the assets declared by every call of the current run.
+fn append_module(items: Vec<ModuleItem>) -> Result<()> {
+    let out_dir = env::var("OUT_DIR").map_err(|_| Error::Var("OUT_DIR"))?;
+    let path = Path::new(&out_dir).join(MODULE_FILE);
+    let mut module = MODULE.lock().unwrap_or_else(PoisonError::into_inner);
+    let module = module.get_or_insert_with(Module::default);
+    for item in items {
+        module.declare(item);
+    }
+    record_owned_file(&path)?;
+    fs::write(&path, module.source())?;
+    Ok(())
+}
 
-/// An error that can occur during the asset packing process in a build script.
-#[derive(Debug, thiserror::Error)]
-pub enum Error {
-    #[error("I/O error")]
-    Io(#[from] std::io::Error),
-    #[error("Object writing error")]
-    Object(#[from] object::write::Error),
-    #[error("Environment variable '{0}' not set by Cargo")]
-    Var(&'static str),
-    #[error("Path '{0}' not found (current directory is '{1}')")]
-    PathNotFound(String, String),
-    #[error("Path '{0}' has unsupported file type")]
-    UnsupportedFileType(String),
-    #[error("Could not convert object file name to UTF-8")]
-    FromUtf8(#[from] FromUtf8Error),
-    #[error("A generic build error occurred: {0}")]
-    Generic(String),
+/// Writes the report of [`Config::report`] for `assets`.
+fn write_report(path: &Path, assets: &[AssetSummary]) -> Result<()> {
+    let assets = assets
+        .iter()
+        .map(|asset| {
+            // Empty assets have nothing to compress, so they are reported as unchanged.
+            let ratio = if asset.is_empty() {
+                1.0
+            } else {
+                asset.compressed_len as f64 / asset.len as f64
+            };
+            format!(
+                "{{\"path\":{},\"size\":{},\"compressed_size\":{},\"ratio\":{ratio},\"level\":{},\"time_ms\":{}}}",
+                json_string(&asset.path),
+                asset.len,
+                asset.compressed_len,
+                asset.level,
+                asset.elapsed.as_secs_f64() * 1000.0
+            )
+        })
+        .collect::<Vec<_>>();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, format!("{{\"assets\":[{}]}}\n", assets.join(",")))?;
+    Ok(())
 }
 
-/// Internal implementation that prepares a path for inclusion on native targets.
-fn make_includable_with_level<P: AsRef<Path>>(path: P, level: i32) -> Result<()> {
-    make_includable_impl(path.as_ref(), level)
+/// Formats `value` as a JSON string literal.
+fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
 }
 
-/// Recursively processes files and directories.
-fn make_includable_impl(path: &Path, level: i32) -> Result<()> {
+/// Recursively collects the files below `path`, along with their metadata, and the
+/// fingerprints of the directories below it.
+///
+/// Files and directories are identified by their canonical paths, and each is only
+/// visited once, which breaks cycles of symbolic links. Cargo is told to rerun the build
+/// script when any of them change.
+fn collect_asset_files(
+    path: &Path,
+    follow_symlinks: bool,
+    visited: &mut HashSet<PathBuf>,
+    files: &mut Vec<(PathBuf, fs::Metadata)>,
+    fingerprints: &mut Vec<(PathBuf, u64)>,
+) -> Result<()> {
     let canonical_path = path.canonicalize().map_err(|_| {
         Error::PathNotFound(
             path.display().to_string(),
             std::env::current_dir().map_or_else(|_| "unknown".into(), |p| p.display().to_string()),
         )
     })?;
+    if !visited.insert(canonical_path.clone()) {
+        return Ok(());
+    }
     println!("cargo:rerun-if-changed={}", canonical_path.display());
 
     let metadata = fs::metadata(&canonical_path)?;
     if metadata.is_dir() {
+        fingerprints.push((
+            canonical_path.clone(),
+            directory_fingerprint(&canonical_path)?,
+        ));
         for entry in fs::read_dir(&canonical_path)? {
-            make_includable_impl(&entry?.path(), level)?;
+            let path = entry?.path();
+            if is_skipped_link(&path, follow_symlinks)? {
+                continue;
+            }
+            collect_asset_files(&path, follow_symlinks, visited, files, fingerprints)?;
         }
         Ok(())
     } else if metadata.is_file() {
-        process_file(&canonical_path, &metadata, level)
+        files.push((canonical_path, metadata));
+        Ok(())
     } else {
         Err(Error::UnsupportedFileType(path.display().to_string()))
     }
 }
 
-/// Internal implementation that compresses and packs a single file into an object file.
-fn process_file(path: &Path, metadata: &fs::Metadata, level: i32) -> Result<()> {
-    let manifest_dir = env::var("CARGO_MANIFEST_DIR")
-        .map(PathBuf::from)
-        .map_err(|_| Error::Var("CARGO_MANIFEST_DIR"))?;
+/// Returns the canonical form of the configured path `path`.
+#[cfg(feature = "archives")]
+fn canonical_source(path: &Path) -> Result<PathBuf> {
+    path.canonicalize().map_err(|_| {
+        Error::PathNotFound(
+            path.display().to_string(),
+            std::env::current_dir().map_or_else(|_| "unknown".into(), |p| p.display().to_string()),
+        )
+    })
+}
+
+/// Reads the regular files of the zip or tar archive at `path`, returning their paths
+/// inside the archive and their contents.
+#[cfg(feature = "archives")]
+fn read_archive(path: &Path) -> Result<Vec<(PathBuf, Vec<u8>)>> {
+    use std::io::Read as _;
+
+    let error = |err: std::io::Error| Error::Archive(path.display().to_string(), err);
+    let name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_ascii_lowercase();
+    let file = fs::File::open(path)?;
+    let mut members = Vec::new();
+    if name.ends_with(".zip") {
+        let mut archive = zip::ZipArchive::new(file).map_err(|err| error(err.into()))?;
+        for index in 0..archive.len() {
+            let mut member = archive.by_index(index).map_err(|err| error(err.into()))?;
+            if !member.is_file() {
+                continue;
+            }
+            let member_path = member.enclosed_name().ok_or_else(|| {
+                Error::Generic(format!(
+                    "archive '{}' contains the unsafe path '{}'",
+                    path.display(),
+                    member.name()
+                ))
+            })?;
+            let mut data = Vec::new();
+            member.read_to_end(&mut data).map_err(error)?;
+            members.push((member_path, data));
+        }
+    } else if name.ends_with(".tar") || name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        let reader: Box<dyn std::io::Read> = if name.ends_with(".tar") {
+            Box::new(file)
+        } else {
+            Box::new(flate2::read::GzDecoder::new(file))
+        };
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries().map_err(error)? {
+            let mut entry = entry.map_err(error)?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let member_path = entry.path().map_err(error)?.into_owned();
+            if !member_path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+            {
+                return Err(Error::Generic(format!(
+                    "archive '{}' contains the unsafe path '{}'",
+                    path.display(),
+                    member_path.display()
+                )));
+            }
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data).map_err(error)?;
+            members.push((member_path, data));
+        }
+    } else {
+        return Err(Error::Generic(format!(
+            "'{}' is not a .zip, .tar, .tar.gz, or .tgz archive",
+            path.display()
+        )));
+    }
+    Ok(members)
+}
+
+/// Hashes the sorted names of the entries of the directory at `path`.
+///
+/// Cargo doesn't notice files added to a directory on every platform, e.g. when their
+/// modification time is older than the last build. The macros compute the same
+/// fingerprint to report such directories instead of expanding to stale assets.
+fn directory_fingerprint(path: &Path) -> Result<u64> {
+    let mut names = fs::read_dir(path)?
+        .map(|entry| Ok(entry?.file_name()))
+        .collect::<Result<Vec<_>>>()?;
+    names.sort();
+    let mut hasher = DefaultHasher::new();
+    names.hash(&mut hasher);
+    Ok(hasher.finish())
+}
+
+/// Recursively collects the files below `path`, keyed by their logical paths.
+///
+/// `ancestors` holds the canonical paths of the directories being walked, so cycles of
+/// symbolic links are only followed once.
+fn collect_pack_files(
+    path: &Path,
+    follow_symlinks: bool,
+    ancestors: &mut Vec<PathBuf>,
+    files: &mut Vec<(String, PathBuf)>,
+) -> Result<()> {
+    let metadata = fs::metadata(path).map_err(|_| {
+        Error::PathNotFound(
+            path.display().to_string(),
+            std::env::current_dir().map_or_else(|_| "unknown".into(), |p| p.display().to_string()),
+        )
+    })?;
+    if metadata.is_dir() {
+        let canonical_path = path.canonicalize()?;
+        if ancestors.contains(&canonical_path) {
+            return Ok(());
+        }
+        ancestors.push(canonical_path);
+        for entry in fs::read_dir(path)? {
+            let path = entry?.path();
+            if is_skipped_link(&path, follow_symlinks)? {
+                continue;
+            }
+            collect_pack_files(&path, follow_symlinks, ancestors, files)?;
+        }
+        ancestors.pop();
+    } else if metadata.is_file() {
+        files.push((logical_path(path), path.to_path_buf()));
+    } else {
+        return Err(Error::UnsupportedFileType(path.display().to_string()));
+    }
+    Ok(())
+}
+
+/// Returns whether the directory entry at `path` is a symbolic link to skip, either
+/// because links aren't followed or because its target doesn't exist.
+fn is_skipped_link(path: &Path, follow_symlinks: bool) -> Result<bool> {
+    if !fs::symlink_metadata(path)?.is_symlink() {
+        return Ok(false);
+    }
+    // The macros skip the same links when listing directories.
+    Ok(!follow_symlinks || fs::metadata(path).is_err())
+}
+
+/// Packs `files` on up to [`Config::jobs`] threads.
+///
+/// Returns the paths of the artifacts to link, in the same order as `files`.
+fn process_files(
+    files: &[(PathBuf, fs::Metadata)],
+    packer: &Packer<'_>,
+) -> Result<Vec<PackedFile>> {
+    let jobs = packer
+        .config
+        .jobs
+        .or_else(|| env::var("NUM_JOBS").ok()?.parse().ok())
+        .or_else(|| thread::available_parallelism().ok().map(NonZeroUsize::get))
+        .unwrap_or(1)
+        .clamp(1, files.len().max(1));
+
+    let next = AtomicUsize::new(0);
+    let mut results = thread::scope(|scope| {
+        let workers = (0..jobs)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some((path, metadata)) = files.get(index) else {
+                            return results;
+                        };
+                        results.push((index, process_file(path, metadata, packer)));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            // Re-raise panics from user transforms with their original payload.
+            .flat_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+            })
+            .collect::<Vec<_>>()
+    });
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// A file packed by [`process_file`].
+struct PackedFile {
+    /// The path of the artifact to link, unless the file is bundled.
+    artifact: Option<PathBuf>,
+    /// Whether the file is packed into the bundle written by [`write_bundle`].
+    bundled: bool,
+    /// The file's compressed data, if it is waiting to be written by [`write_bundle`].
+    bundle_data: Option<Vec<u8>>,
+    /// The symbol holding the file's data.
+    symbol: String,
+    /// The hash of the file's contents and compression level, which identical files share.
+    data_hash: blake3::Hash,
+    /// The BLAKE3 hash of the file's contents, after any transforms.
+    hash: blake3::Hash,
+    /// The file's metadata.
+    meta: Vec<(&'static str, String)>,
+    /// The path the metadata is written to.
+    meta_path: PathBuf,
+    /// The size of the file.
+    len: usize,
+    /// The size of the compressed data.
+    compressed_len: usize,
+    /// The file's modification time, as recorded by [`modified_secs`].
+    modified: Option<u64>,
+    /// The algorithm the file was compressed with.
+    algorithm: Algorithm,
+    /// The compression level the file was compressed with.
+    level: i32,
+    /// The alignment of the file's data.
+    align: usize,
+    /// The time spent compressing the file.
+    elapsed: Duration,
+    /// Whether the file shares the data of an identical file packed before it.
+    duplicate: bool,
+}
 
-    let path_for_hashing = path
-        .strip_prefix(&manifest_dir)
-        .unwrap_or(path)
-        .to_path_buf();
+/// A file packed with [`Config::bundle`], whose metadata is only complete once its
+/// offset within the bundle is known.
+struct BundledFile {
+    /// The compressed data, or `None` if the file shares that of an identical file.
+    data: Option<Vec<u8>>,
+    /// The symbol the data was packed under.
+    symbol: String,
+    /// The metadata recorded so far.
+    meta: Vec<(&'static str, String)>,
+    /// The path the metadata is written to.
+    meta_path: PathBuf,
+    /// The alignment of the data within the bundle.
+    align: usize,
+}
 
+/// Returns the path of the metadata for the asset with the given key.
+///
+/// The macros locate the metadata by the asset's key and modification time.
+fn meta_path(key: &Path, metadata: &fs::Metadata) -> Result<PathBuf> {
     let mut hasher = DefaultHasher::new();
-    path_for_hashing.hash(&mut hasher);
+    key.hash(&mut hasher);
     metadata.modified()?.hash(&mut hasher);
-    let unique_name = format!("include_packed_{:016x}", hasher.finish());
+    let out_dir = env::var("OUT_DIR").map_err(|_| Error::Var("OUT_DIR"))?;
+    Ok(PathBuf::from(out_dir).join(format!("include_packed_{:016x}.meta", hasher.finish())))
+}
+
+/// Internal implementation that compresses and packs a single file into an object file.
+fn process_file(path: &Path, metadata: &fs::Metadata, packer: &Packer<'_>) -> Result<PackedFile> {
+    let config = packer.config;
+    let path_for_hashing = asset_key(path, config.root.as_deref())?;
+    let meta_path = meta_path(&path_for_hashing, metadata)?;
+
+    // Symbols end up in the binary, so they only depend on the package, the asset's key,
+    // and its contents, which keeps builds reproducible regardless of modification times.
+    let content = packer.read(path)?;
+    let mut content_hasher = blake3::Hasher::new();
+    content_hasher.update(env::var("CARGO_PKG_NAME").unwrap_or_default().as_bytes());
+    content_hasher.update(&[0]);
+    content_hasher.update(path_for_hashing.to_string_lossy().as_bytes());
+    content_hasher.update(&[0]);
+    content_hasher.update(&content);
+    let content_hash = content_hasher.finalize().to_hex();
+    let content_hash = &content_hash[..16];
+    let symbol = symbol_name(
+        config,
+        match &config.symbol_namespace {
+            Some(namespace) => format!("include_packed_{namespace}_{content_hash}"),
+            None => format!("include_packed_{content_hash}"),
+        },
+    );
+    let logical_path = config.logical_path(&packer.canonical_roots, path)?;
+    let mut options = packer.options_for(&path_for_hashing, Path::new(&logical_path));
+    if let Some(budget) = options.auto {
+        (options.algorithm, options.level) = packer.auto_select(budget, &content)?;
+    }
+    let FileOptions {
+        level,
+        algorithm,
+        align,
+        ..
+    } = options;
+    let mut data_hasher = blake3::Hasher::new();
+    data_hasher.update(&level.to_le_bytes());
+    data_hasher.update(algorithm.name().as_bytes());
+    data_hasher.update(&align.to_le_bytes());
+    data_hasher.update(&options.chunk_size.unwrap_or(0).to_le_bytes());
+    let base = options.delta.as_ref().map(|base| &packer.delta_bases[base]);
+    if let Some(base) = base {
+        data_hasher.update(base.hash.as_bytes());
+    }
+    data_hasher.update(&content);
+    let data_hash = data_hasher.finalize();
+    let hash = blake3::hash(&content);
+    let started = Instant::now();
+    let Compressed {
+        data: compressed_content,
+        chunk_ends,
+    } = packer.compress_once(data_hash, &options, &content)?;
+    let elapsed = started.elapsed();
+
+    let out_dir = env::var("OUT_DIR").map_err(|_| Error::Var("OUT_DIR"))?;
+    let mut meta = vec![
+        ("symbol", symbol.clone()),
+        ("compressed_len", compressed_content.len().to_string()),
+        ("len", content.len().to_string()),
+        ("utf8", std::str::from_utf8(&content).is_ok().to_string()),
+        ("algorithm", algorithm.name().to_owned()),
+        ("source", logical_path),
+    ];
+    let modified = modified_secs(metadata, packer.source_date_epoch);
+    if let Some(modified) = modified {
+        meta.push(("modified", modified.to_string()));
+    }
+    if let Some(namespace) = &config.symbol_namespace {
+        meta.push(("namespace", namespace.clone()));
+    }
+    if config.obfuscate {
+        meta.push(("obfuscated", "true".to_owned()));
+    }
+    if let (Some(chunk_size), Some(ends)) = (options.chunk_size, chunk_ends) {
+        meta.push(("chunk_size", chunk_size.to_string()));
+        meta.push(("chunks", ends.join(",")));
+    }
+    if align > 1 {
+        meta.push(("align", align.to_string()));
+    }
+    if let Some(base) = base {
+        meta.push(("delta_base", base.logical_path.clone()));
+    } else if let Some(dictionary) = &packer.dictionary
+        && algorithm == Algorithm::Zstd
+    {
+        meta.push(("dictionary", dictionary.symbol.clone()));
+        meta.push(("dictionary_len", dictionary.data.len().to_string()));
+    }
+    if config.dev_passthrough
+        && !config.obfuscate
+        && !config.archive
+        && !config.transforms()
+        && env::var("PROFILE").as_deref() == Ok("debug")
+        && !uses_include_bytes()
+        && let Some(source) = path.to_str()
+    {
+        meta.push(("passthrough", source.to_owned()));
+    }
+    #[cfg(feature = "checksums")]
+    if options.verify || config.signs() {
+        meta.push(("checksum", hash.to_hex().to_string()));
+    }
+    #[cfg(feature = "signatures")]
+    if let Some(signing_key) = &config.signing_key {
+        use ed25519_dalek::Signer as _;
+
+        let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{b:02x}")).collect();
+        let signature = signing_key.sign(hash.as_bytes());
+        meta.push(("signature", hex(&signature.to_bytes())));
+        meta.push(("public_key", hex(signing_key.verifying_key().as_bytes())));
+    }
+    #[cfg(feature = "encryption")]
+    if let Some((cipher, _)) = &packer.encryption {
+        meta.push(("cipher", cipher.name().to_owned()));
+    }
+    meta.extend(packer.common_meta.iter().cloned());
+
+    let artifact = match &config.wasm_fetch {
+        Some((dir, base_url)) if is_wasm() => {
+            // Naming the file by its contents lets it be cached indefinitely.
+            let hash = blake3::hash(&compressed_content).to_hex();
+            let file_name = format!("{}.bin", &hash[..16]);
+            meta.push((
+                "url",
+                format!("{}/{file_name}", base_url.trim_end_matches('/')),
+            ));
+            fs::create_dir_all(dir)?;
+            let path = dir.join(file_name);
+            fs::write(&path, &compressed_content)?;
+            Some(path)
+        }
+        _ if config.bundle => None,
+        _ => Some(write_artifact(
+            config,
+            Path::new(&out_dir),
+            &symbol,
+            &compressed_content,
+            align,
+        )?),
+    };
+
+    if config.verify_roundtrip {
+        // Bundles are verified separately by `write_bundle`.
+        let written = match &artifact {
+            Some(artifact) => fs::read(artifact)?,
+            None => Vec::new(),
+        };
+        let data = if artifact.is_none() || uses_windows_resources(config) || uses_include_bytes() {
+            &compressed_content
+        } else {
+            symbol_data(&written, &symbol).ok_or_else(|| {
+                Error::Verification(path.display().to_string(), "symbol not found")
+            })?
+        };
+        #[cfg(feature = "encryption")]
+        let decrypted = match &packer.encryption {
+            Some((cipher, key)) => Some(crypto::open(*cipher, key, data).ok_or_else(|| {
+                Error::Verification(path.display().to_string(), "decryption failed")
+            })?),
+            None => None,
+        };
+        #[cfg(feature = "encryption")]
+        let data = decrypted.as_deref().unwrap_or(data);
+        let dictionary = match base {
+            Some(base) => codec::Dictionary::Raw(&base.content),
+            None => codec::Dictionary::Trained(packer.dictionary_for(algorithm)),
+        };
+        verify_data(path, algorithm, dictionary, data, &content)?;
+    }
+
+    Ok(PackedFile {
+        bundled: artifact.is_none(),
+        bundle_data: artifact.is_none().then(|| compressed_content.clone()),
+        artifact,
+        symbol,
+        data_hash,
+        hash,
+        meta,
+        meta_path,
+        len: content.len(),
+        compressed_len: compressed_content.len(),
+        modified,
+        algorithm,
+        level,
+        align,
+        elapsed,
+        duplicate: false,
+    })
+}
+
+/// Points the files of `packed_files` whose packed data is identical to that of an
+/// earlier file at the earlier file's symbol, and removes the artifacts they no longer
+/// need, so that identical assets are only embedded once.
+fn share_identical(packed_files: &mut [PackedFile]) -> Result<()> {
+    let mut originals = HashMap::new();
+    for packed in packed_files {
+        let Some((symbol, artifact)) = originals.get(&packed.data_hash) else {
+            originals.insert(
+                packed.data_hash,
+                (packed.symbol.clone(), packed.artifact.clone()),
+            );
+            continue;
+        };
+        // Files fetched on Wasm are named by their contents and already shared.
+        if let Some(own) = packed.artifact.take()
+            && Some(&own) != artifact.as_ref()
+        {
+            fs::remove_file(own)?;
+        }
+        for (key, value) in &mut packed.meta {
+            if *key == "symbol" {
+                value.clone_from(symbol);
+            }
+        }
+        packed.bundle_data = None;
+        packed.symbol.clone_from(symbol);
+        packed.duplicate = true;
+    }
+    Ok(())
+}
+
+/// Concatenates the data of `files` into a single bundle artifact, and writes each
+/// file's metadata with its offset within the bundle.
+///
+/// Returns the path of the artifact to link.
+fn write_bundle(config: &Config, files: Vec<BundledFile>) -> Result<PathBuf> {
+    let mut bundle = Vec::new();
+    // Identical files share the data of the first of them.
+    let mut offsets = HashMap::new();
+    for file in &files {
+        if let Some(data) = &file.data {
+            bundle.resize(bundle.len().next_multiple_of(file.align), 0);
+            offsets.insert(file.symbol.clone(), bundle.len());
+            bundle.extend_from_slice(data);
+        }
+    }
+
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(env::var("CARGO_PKG_NAME").unwrap_or_default().as_bytes());
+    hasher.update(&[0]);
+    hasher.update(&bundle);
+    let hash = hasher.finalize().to_hex();
+    let hash = &hash[..16];
+    let symbol = symbol_name(
+        config,
+        match &config.symbol_namespace {
+            Some(namespace) => format!("include_packed_{namespace}_bundle_{hash}"),
+            None => format!("include_packed_bundle_{hash}"),
+        },
+    );
+
+    let out_dir = env::var("OUT_DIR").map_err(|_| Error::Var("OUT_DIR"))?;
+    let align = files.iter().map(|file| file.align).max().unwrap_or(1);
+    let artifact = write_artifact(config, Path::new(&out_dir), &symbol, &bundle, align)?;
+    if config.verify_roundtrip && !uses_windows_resources(config) && !uses_include_bytes() {
+        let written = fs::read(&artifact)?;
+        if symbol_data(&written, &symbol) != Some(&bundle[..]) {
+            return Err(Error::Verification(symbol, "bundle data does not match"));
+        }
+    }
+
+    for file in files {
+        let mut meta = file.meta;
+        meta.push(("bundle", symbol.clone()));
+        meta.push(("bundle_offset", offsets[&file.symbol].to_string()));
+        meta.push(("bundle_len", bundle.len().to_string()));
+        write_meta(&file.meta_path, &meta)?;
+    }
+    Ok(artifact)
+}
+
+/// Downloads the file at `url` to `name` in a directory keyed by its SHA-256 hash
+/// `sha256`, unless it is already there, and returns the directory.
+#[cfg(feature = "remote")]
+fn download(url: &str, sha256: &str, name: &Path, cache_dir: Option<&Path>) -> Result<PathBuf> {
+    let expected = sha256.to_ascii_lowercase();
+    if expected.len() != 64 || !expected.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(Error::Generic(format!(
+            "'{sha256}' is not a SHA-256 hash of 64 hexadecimal digits"
+        )));
+    }
+    let dir = match cache_dir {
+        Some(cache_dir) => cache_dir.join("remote"),
+        None => {
+            let out_dir = env::var("OUT_DIR").map_err(|_| Error::Var("OUT_DIR"))?;
+            PathBuf::from(out_dir).join("include_packed_remote")
+        }
+    }
+    .join(&expected);
+    let path = dir.join(name);
+    if path.is_file() && sha256_file(&path)? == expected {
+        return Ok(dir);
+    }
+
+    fs::create_dir_all(&dir)?;
+    let temporary = path.with_extension(format!("tmp{}", std::process::id()));
+    let downloaded = download_to(url, &expected, &temporary, &path);
+    if downloaded.is_err() {
+        // Partial downloads would otherwise be left behind.
+        let _ = fs::remove_file(&temporary);
+    }
+    downloaded.map(|()| dir)
+}
+
+/// Downloads the file at `url` to `temporary`, and moves it to `path` if its SHA-256
+/// hash is `expected`.
+#[cfg(feature = "remote")]
+fn download_to(url: &str, expected: &str, temporary: &Path, path: &Path) -> Result<()> {
+    let response = ureq::get(url)
+        .call()
+        .map_err(|err| Error::Download(url.to_owned(), Box::new(err)))?;
+    std::io::copy(
+        &mut response.into_body().into_reader(),
+        &mut fs::File::create(temporary)?,
+    )?;
+    let actual = sha256_file(temporary)?;
+    if actual != expected {
+        return Err(Error::HashMismatch(
+            url.to_owned(),
+            expected.to_owned(),
+            actual,
+        ));
+    }
+    fs::rename(temporary, path)?;
+    Ok(())
+}
+
+/// Returns the SHA-256 hash of the file at `path`, as lowercase hexadecimal digits.
+#[cfg(feature = "remote")]
+fn sha256_file(path: &Path) -> Result<String> {
+    use sha2::{Digest as _, Sha256};
+
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect())
+}
+
+/// Environment variable holding the key of [`Config::encrypt`].
+#[cfg(feature = "encryption")]
+const KEY_ENV: &str = "INCLUDE_PACKED_ENCRYPTION_KEY";
+
+/// Environment variable naming a file that holds the key of [`Config::encrypt`], read
+/// if [`KEY_ENV`] is not set.
+#[cfg(feature = "encryption")]
+const KEY_FILE_ENV: &str = "INCLUDE_PACKED_KEY_FILE";
+
+/// Reads the key of [`Config::encrypt`] from the environment, or from the file it names.
+#[cfg(feature = "encryption")]
+fn encryption_key() -> Result<[u8; 32]> {
+    println!("cargo:rerun-if-env-changed={KEY_ENV}");
+    println!("cargo:rerun-if-env-changed={KEY_FILE_ENV}");
+    if let (Err(_), Some(path)) = (env::var(KEY_ENV), env::var_os(KEY_FILE_ENV)) {
+        let path = PathBuf::from(path);
+        println!("cargo:rerun-if-changed={}", path.display());
+        let invalid = || Error::InvalidKeyFile(path.display().to_string());
+        let contents = fs::read(&path).map_err(|_| invalid())?;
+        if let Ok(key) = <[u8; 32]>::try_from(contents.as_slice()) {
+            return Ok(key);
+        }
+        let hex = std::str::from_utf8(&contents).map_err(|_| invalid())?;
+        return parse_key(hex.trim()).ok_or_else(invalid);
+    }
+    let hex = env::var(KEY_ENV).map_err(|_| Error::InvalidKey(KEY_ENV))?;
+    parse_key(&hex).ok_or(Error::InvalidKey(KEY_ENV))
+}
+
+/// Parses a 256-bit key from 64 hexadecimal digits.
+#[cfg(feature = "encryption")]
+fn parse_key(hex: &str) -> Option<[u8; 32]> {
+    if hex.len() != 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut key = [0; 32];
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = hex
+            .get(i * 2..i * 2 + 2)
+            .and_then(|digits| u8::from_str_radix(digits, 16).ok())?;
+    }
+    Some(key)
+}
+
+/// Returns `true` if the macros `include_bytes!` the raw compressed data from `OUT_DIR`
+/// instead of linking object files, which is the case on Wasm and on targets whose
+/// object files can't be written.
+fn uses_include_bytes() -> bool {
+    is_wasm() || TargetInfo::from_build_script_vars().is_none()
+}
+
+/// Returns `true` if the target is Wasm.
+fn is_wasm() -> bool {
+    env::var("CARGO_CFG_TARGET_ARCH").as_deref() == Ok("wasm32")
+}
+
+/// Returns `true` if assets are embedded as Windows resources for the current target.
+fn uses_windows_resources(config: &Config) -> bool {
+    config.windows_resources && env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("windows")
+}
 
-    let content = fs::read(path)?;
-    let compressed_content = zstd::encode_all(&*content, level)?;
+/// Writes `data` under the name `symbol` into a linkable artifact in `out_dir`, aligned
+/// to `align` bytes.
+///
+/// Returns the path of the artifact, which is either a Windows resource file, an object
+/// file, or on Wasm, the raw data.
+fn write_artifact(
+    config: &Config,
+    out_dir: &Path,
+    symbol: &str,
+    data: &[u8],
+    align: usize,
+) -> Result<PathBuf> {
+    if uses_include_bytes() {
+        let path = out_dir.join(format!("{symbol}.bin"));
+        record_owned_file(&path)?;
+        fs::write(&path, data)?;
+        return Ok(path);
+    }
+    if uses_windows_resources(config) {
+        let res_path = out_dir.join(format!("{symbol}.res"));
+        record_owned_file(&res_path)?;
+        fs::write(&res_path, resource_file(symbol, data))?;
+        return Ok(res_path);
+    }
 
     // Create the object file
-    let info = TargetInfo::from_build_script_vars();
+    let Some(info) = TargetInfo::from_build_script_vars() else {
+        unreachable!("assets for unsupported targets are embedded with `include_bytes!`");
+    };
     let mut object = Object::new(info.binfmt, info.arch, info.endian);
-    let section = object.add_subsection(StandardSection::ReadOnlyData, unique_name.as_bytes());
+    let section = match &config.macho_section {
+        Some((segment, section)) if info.binfmt == BinaryFormat::MachO => object.add_section(
+            segment.as_bytes().to_vec(),
+            section.as_bytes().to_vec(),
+            SectionKind::ReadOnlyData,
+        ),
+        _ => match &config.link_section {
+            Some(prefix) if info.binfmt == BinaryFormat::Elf => object.add_section(
+                Vec::new(),
+                format!("{prefix}.{symbol}").into_bytes(),
+                SectionKind::ReadOnlyData,
+            ),
+            _ => object.add_subsection(StandardSection::ReadOnlyData, symbol.as_bytes()),
+        },
+    };
+    if info.binfmt == BinaryFormat::Elf {
+        let mut sh_flags = elf::SHF_ALLOC;
+        if config.retain_sections {
+            sh_flags |= elf::SHF_GNU_RETAIN;
+        }
+        object.section_mut(section).flags = SectionFlags::Elf {
+            sh_flags: sh_flags.into(),
+        };
+    }
 
     let sym = object.add_symbol(Symbol {
-        name: unique_name.as_bytes().to_vec(),
+        name: symbol.as_bytes().to_vec(),
         value: 0,
-        size: compressed_content.len() as u64,
+        size: data.len() as u64,
         kind: SymbolKind::Data,
-        scope: SymbolScope::Dynamic,
+        scope: if config.hidden_symbols {
+            SymbolScope::Linkage
+        } else {
+            SymbolScope::Dynamic
+        },
         weak: false,
         section: SymbolSection::Section(section),
         flags: SymbolFlags::None,
     });
-    object.add_symbol_data(sym, section, &compressed_content, 1);
+    object.add_symbol_data(sym, section, data, align as u64);
     let obj_buf = object.write()?;
 
-    // Write the object file and instruct Cargo to link it
-    let out_dir = env::var("OUT_DIR").map_err(|_| Error::Var("OUT_DIR"))?;
-
-    let object_file_name = format!("{unique_name}.o");
-    let object_path = PathBuf::from(&out_dir).join(object_file_name);
+    // Write the object file
+    let object_file_name = format!("{symbol}.o");
+    let object_path = out_dir.join(object_file_name);
+    record_owned_file(&object_path)?;
     fs::write(&object_path, obj_buf)?;
 
-    let len_file_path = format!("{out_dir}/{unique_name}.len");
-    fs::write(len_file_path, compressed_content.len().to_string())?;
+    Ok(object_path)
+}
+
+/// Compresses `data` with `algorithm` at the given level.
+///
+/// `dictionary` is the zstd dictionary to compress with, and `options` are the advanced
+/// zstd parameters.
+fn compress(
+    algorithm: Algorithm,
+    dictionary: codec::Dictionary<'_>,
+    data: &[u8],
+    level: i32,
+    options: &ZstdOptions,
+) -> Result<Vec<u8>> {
+    Ok(match algorithm {
+        Algorithm::Zstd => {
+            let mut compressor = match dictionary {
+                codec::Dictionary::Trained(dictionary) => {
+                    zstd::bulk::Compressor::with_dictionary(level, dictionary)?
+                }
+                codec::Dictionary::Raw(prefix) => {
+                    // A referenced prefix is never parsed as a dictionary, and stays
+                    // referenced for the one frame `compress` produces.
+                    let mut compressor: zstd::bulk::Compressor<'_> =
+                        zstd::bulk::Compressor::new(level)?;
+                    compressor
+                        .context_mut()
+                        .ref_prefix(prefix)
+                        .map_err(|code| {
+                            std::io::Error::other(zstd::zstd_safe::get_error_name(code))
+                        })?;
+                    compressor
+                }
+            };
+            options.apply(&mut compressor)?;
+            compressor.compress(data)?
+        }
+        #[cfg(feature = "lz4")]
+        Algorithm::Lz4 => {
+            use std::io::Write;
+            let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+            encoder.write_all(data)?;
+            encoder.finish().map_err(std::io::Error::other)?
+        }
+        #[cfg(feature = "brotli")]
+        Algorithm::Brotli => {
+            let params = brotli::enc::BrotliEncoderParams {
+                quality: level.clamp(0, 11),
+                ..Default::default()
+            };
+            let mut compressed = Vec::new();
+            brotli::BrotliCompress(&mut &data[..], &mut compressed, &params)?;
+            compressed
+        }
+        Algorithm::Store => data.to_vec(),
+    })
+}
+
+/// Returns the data of the symbol `name` defined in the object file `object`.
+fn symbol_data<'a>(object: &'a [u8], name: &str) -> Option<&'a [u8]> {
+    use object::{Object as _, ObjectSection as _, ObjectSymbol as _};
+
+    let file = object::File::parse(object).ok()?;
+    // Mach-O and 32-bit COFF prefix C symbol names with an underscore. Elsewhere, a
+    // leading underscore is part of the name, as in obfuscated symbols.
+    let prefixed = match file.format() {
+        object::BinaryFormat::MachO => true,
+        object::BinaryFormat::Coff => !file.is_64(),
+        _ => false,
+    };
+    let symbol = file.symbols().find(|symbol| {
+        symbol.name().is_ok_and(|sym| {
+            let sym = if prefixed {
+                sym.strip_prefix('_').unwrap_or(sym)
+            } else {
+                sym
+            };
+            sym == name
+        })
+    })?;
+    let section = file.section_by_index(symbol.section_index()?).ok()?;
+    section
+        .data_range(symbol.address(), symbol.size())
+        .ok()
+        .flatten()
+}
+
+/// Checks that `compressed` decompresses to exactly `original`.
+fn verify_data(
+    path: &Path,
+    algorithm: Algorithm,
+    dictionary: codec::Dictionary<'_>,
+    compressed: &[u8],
+    original: &[u8],
+) -> Result<()> {
+    let fail = |reason| Error::Verification(path.display().to_string(), reason);
+    let decompressed = codec::decode_all(algorithm, dictionary, compressed)
+        .map_err(|_| fail("decompression failed"))?;
+    if decompressed != original {
+        return Err(fail("decompressed data differs from the source file"));
+    }
+    Ok(())
+}
+
+/// Instructs Cargo to link `artifacts`, either one by one or, with
+/// [`Config::static_archive`] or [`Config::library`], as a single static archive.
+fn link_artifacts(config: &Config, artifacts: &[PathBuf]) -> Result<()> {
+    let info = TargetInfo::from_build_script_vars();
+    let Some(info) = info.filter(|_| {
+        (config.static_archive || config.library)
+            && !artifacts.is_empty()
+            && !uses_include_bytes()
+            && !uses_windows_resources(config)
+    }) else {
+        for artifact in artifacts {
+            emit_link_arg(config, artifact);
+        }
+        return Ok(());
+    };
+
+    let members = artifacts
+        .iter()
+        .map(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            Ok(NewArchiveMember::new(
+                fs::read(path)?,
+                &ar_archive_writer::DEFAULT_OBJECT_READER,
+                name.into_owned(),
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let msvc = env::var("CARGO_CFG_TARGET_ENV").as_deref() == Ok("msvc");
+    let kind = match info.binfmt {
+        BinaryFormat::MachO => ArchiveKind::Darwin,
+        BinaryFormat::Coff if msvc => ArchiveKind::Coff,
+        _ => ArchiveKind::Gnu,
+    };
+    let mut archive = std::io::Cursor::new(Vec::new());
+    write_archive_to_stream(&mut archive, &members, kind, false, None)?;
 
-    println!("cargo:rustc-link-arg={}", object_path.display());
+    // The artifacts are named by their contents, and so is the archive.
+    let mut hasher = blake3::Hasher::new();
+    for path in artifacts {
+        hasher.update(path.file_name().unwrap_or_default().as_encoded_bytes());
+        hasher.update(&[0]);
+    }
+    let name = format!("include_packed_{}", &hasher.finalize().to_hex()[..16]);
+    let out_dir = env::var("OUT_DIR").map_err(|_| Error::Var("OUT_DIR"))?;
+    let file_name = if msvc {
+        format!("{name}.lib")
+    } else {
+        format!("lib{name}.a")
+    };
+    let path = Path::new(&out_dir).join(file_name);
+    record_owned_file(&path)?;
+    fs::write(&path, archive.into_inner())?;
 
+    if config.library || config.link_targets.is_empty() {
+        // Retained sections must be linked even if nothing references them.
+        let modifiers = if config.retain_sections {
+            ":+whole-archive"
+        } else {
+            ""
+        };
+        println!("cargo:rustc-link-search=native={out_dir}");
+        println!("cargo:rustc-link-lib=static{modifiers}={name}");
+    } else {
+        emit_link_arg(config, &path);
+    }
     Ok(())
 }
 
+/// Instructs Cargo to pass `path` to the linker for every configured link target.
+///
+/// Nothing is linked on Wasm and other targets where the macros include the artifacts
+/// directly.
+fn emit_link_arg(config: &Config, path: &Path) {
+    if uses_include_bytes() {
+        return;
+    }
+    if config.link_targets.is_empty() {
+        println!("cargo:rustc-link-arg={}", path.display());
+    }
+    for target in &config.link_targets {
+        println!("cargo:{}{}", target.directive(), path.display());
+    }
+}
+
+/// Builds a Win32 `.res` file holding `data` as a single `RT_RCDATA` resource.
+///
+/// Resource names are case-insensitive and stored upper-case, so `name` is upper-cased
+/// here and by the runtime lookup.
+fn resource_file(name: &str, data: &[u8]) -> Vec<u8> {
+    const RT_RCDATA: u16 = 10;
+    const MEMORY_FLAGS: u16 = 0x0030; // MOVEABLE | PURE
+
+    fn pad_to_dword(buf: &mut Vec<u8>) {
+        while !buf.len().is_multiple_of(4) {
+            buf.push(0);
+        }
+    }
+
+    fn push_entry(buf: &mut Vec<u8>, type_id: u16, name: &str, data: &[u8]) {
+        let mut header = Vec::new();
+        header.extend_from_slice(&[0xFF, 0xFF]);
+        header.extend_from_slice(&type_id.to_le_bytes());
+        for unit in name.to_uppercase().encode_utf16().chain([0]) {
+            header.extend_from_slice(&unit.to_le_bytes());
+        }
+        pad_to_dword(&mut header);
+        header.extend_from_slice(&0u32.to_le_bytes()); // DataVersion
+        header.extend_from_slice(&MEMORY_FLAGS.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes()); // LanguageId (neutral)
+        header.extend_from_slice(&0u32.to_le_bytes()); // Version
+        header.extend_from_slice(&0u32.to_le_bytes()); // Characteristics
+
+        let data_size = u32::try_from(data.len()).expect("resource exceeds 4 GiB");
+        let header_size = u32::try_from(header.len() + 8).expect("resource header too large");
+        buf.extend_from_slice(&data_size.to_le_bytes());
+        buf.extend_from_slice(&header_size.to_le_bytes());
+        buf.extend_from_slice(&header);
+        buf.extend_from_slice(data);
+        pad_to_dword(buf);
+    }
+
+    // Every `.res` file starts with an empty entry marking it as a 32-bit resource file.
+    let mut buf = vec![
+        0, 0, 0, 0, 0x20, 0, 0, 0, 0xFF, 0xFF, 0, 0, 0xFF, 0xFF, 0, 0, //
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    push_entry(&mut buf, RT_RCDATA, name, data);
+    buf
+}
+
 /// Internal helper to get target-specific information for object file creation.
 struct TargetInfo {
     binfmt: BinaryFormat,
@@ -195,33 +4343,77 @@ struct TargetInfo {
 }
 
 impl TargetInfo {
-    fn from_build_script_vars() -> Self {
+    /// Returns the object file parameters of the target, or `None` if object files
+    /// can't be written for it.
+    fn from_build_script_vars() -> Option<Self> {
         let binfmt = match env::var("CARGO_CFG_TARGET_OS")
             .expect("CARGO_CFG_TARGET_OS not set")
             .as_str()
         {
-            "macos" | "ios" => BinaryFormat::MachO,
-            "windows" => BinaryFormat::Coff,
+            "macos" | "ios" | "tvos" | "watchos" | "visionos" => BinaryFormat::MachO,
+            "windows" | "uefi" => BinaryFormat::Coff,
             "linux" | "android" | "freebsd" | "netbsd" | "openbsd" | "dragonfly" | "solaris"
-            | "illumos" => BinaryFormat::Elf,
-            unk => panic!("unhandled operating system '{unk}' for include-packed"),
+            | "illumos" | "haiku" | "fuchsia" | "redox" | "hurd" | "vxworks" | "nto" | "espidf"
+            | "horizon" | "vita" | "psp" | "l4re" | "hermit" | "rtems" | "nuttx" | "none" => {
+                BinaryFormat::Elf
+            }
+            _ => return None,
         };
+        let pointer_width = env::var("CARGO_CFG_TARGET_POINTER_WIDTH");
+        let ilp32 = pointer_width.as_deref() == Ok("32");
         let arch = match env::var("CARGO_CFG_TARGET_ARCH")
             .expect("CARGO_CFG_TARGET_ARCH not set")
             .as_str()
         {
             "x86" => Architecture::I386,
+            "x86_64" if ilp32 => Architecture::X86_64_X32,
             "x86_64" => Architecture::X86_64,
             "arm" => Architecture::Arm,
+            "aarch64" if ilp32 => Architecture::Aarch64_Ilp32,
             "aarch64" => Architecture::Aarch64,
             "riscv32" => Architecture::Riscv32,
             "riscv64" => Architecture::Riscv64,
             "mips" => Architecture::Mips,
+            "mips64" if ilp32 => Architecture::Mips64_N32,
             "mips64" => Architecture::Mips64,
             "powerpc" => Architecture::PowerPc,
             "powerpc64" => Architecture::PowerPc64,
-            unk => panic!("unhandled architecture '{unk}' for include-packed"),
+            "s390x" => Architecture::S390x,
+            "loongarch32" => Architecture::LoongArch32,
+            "loongarch64" => Architecture::LoongArch64,
+            "sparc" => Architecture::Sparc,
+            "sparc64" => Architecture::Sparc64,
+            "m68k" => Architecture::M68k,
+            "csky" => Architecture::Csky,
+            "hexagon" => Architecture::Hexagon,
+            "xtensa" => Architecture::Xtensa,
+            "avr" => Architecture::Avr,
+            "msp430" => Architecture::Msp430,
+            "bpf" => Architecture::Bpf,
+            _ => return None,
         };
+        // Apple and Windows object files only support a few architectures.
+        let supported = match binfmt {
+            BinaryFormat::MachO => matches!(
+                arch,
+                Architecture::I386
+                    | Architecture::X86_64
+                    | Architecture::Arm
+                    | Architecture::Aarch64
+                    | Architecture::Aarch64_Ilp32
+            ),
+            BinaryFormat::Coff => matches!(
+                arch,
+                Architecture::I386
+                    | Architecture::X86_64
+                    | Architecture::Arm
+                    | Architecture::Aarch64
+            ),
+            _ => true,
+        };
+        if !supported {
+            return None;
+        }
         let endian = match env::var("CARGO_CFG_TARGET_ENDIAN")
             .expect("CARGO_CFG_TARGET_ENDIAN not set")
             .as_str()
@@ -231,10 +4423,10 @@ impl TargetInfo {
             unk => unreachable!("unhandled endianness '{unk}'"),
         };
 
-        Self {
+        Some(Self {
             binfmt,
             arch,
             endian,
-        }
+        })
     }
 }
@@ -9,12 +9,12 @@
 //! Instead of embedding file contents directly into your source code, this crate
 //! processes files in a build script.
 //!
-//! 1.  **Build Script:** You use the [`build::Config`] builder in your `build.rs` script. For native
-//!     targets, it reads your asset files, compresses them with `zstd`, and creates
-//!     linkable object files.
+//! 1.  **Build Script:** You use the [`build::Config`] builder in your `build.rs` script. It
+//!     reads your asset files, compresses them with `zstd`, and creates linkable object
+//!     files (or plain compressed files on Wasm).
 //! 2.  **Macro Expansion:** The `include_packed!` macro in your code expands to an
-//!     expression that links to the compressed data (on native) or embeds the compressed
-//!     data directly (on Wasm).
+//!     expression that links to the compressed data (on native) or embeds it with
+//!     `include_bytes!` (on Wasm).
 //! 3.  **Runtime:** At runtime, the expression decompresses the data and returns it
 //!     as a `Vec<u8>`. Decompression is performed on each call.
 //!
@@ -39,6 +39,7 @@
 //! ```no_run
 //! // build.rs
 //! // This handles all platform-specific logic automatically.
+//! # #[cfg(feature = "build")]
 //! include_packed::Config::new("assets")
 //!   .level(10) // Set a custom zstd compression level (optional)
 //!   .build()
@@ -49,39 +50,111 @@
 //!
 //! ```no_run
 //! // src/main.rs
+//! # #![allow(unused_imports)]
 //! use include_packed::include_packed;
 //!
+//! # // The asset only exists in crates whose build script packed it.
+//! # #[cfg(any())]
+//! # fn main() {
 //! // This returns a Vec<u8> with the decompressed file content.
 //! let data: Vec<u8> = include_packed!("assets/my_file.txt");
 //! println!("Decompressed data is {} bytes long.", data.len());
-//!
+//! # }
+//! # #[cfg(not(any()))]
+//! # fn main() {}
 //! ```
+//!
+//! ## Runtime API
+//!
+//! The macros expand to calls into the [`runtime`] module, which is documented and
+//! covered by semver guarantees, so it can also be used by code that embeds
+//! zstd-compressed data by other means.
 
 #![doc(html_root_url = "https://docs.rs/include_packed")]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
-// Re-export the procedural macro.
-pub use include_packed_macros::include_packed;
+#[cfg(not(any(feature = "std", feature = "ruzstd")))]
+compile_error!("include_packed needs either the `std` or the `ruzstd` feature to decode assets");
+
+// Re-export the procedural macros.
+pub use include_packed_macros::{
+    PackedAssets, include_packed, include_packed_aligned, include_packed_all,
+    include_packed_archived, include_packed_as, include_packed_asset, include_packed_async,
+    include_packed_bytes, include_packed_compressed, include_packed_cow, include_packed_dir,
+    include_packed_fetch, include_packed_glob, include_packed_info, include_packed_into,
+    include_packed_lazy, include_packed_len, include_packed_manifest, include_packed_opt,
+    include_packed_reader, include_packed_registry, include_packed_shared, include_packed_str,
+    include_packed_try,
+};
 
 //
 // ===== RUNTIME CODE =====
 //
 
-/// Decompresses data that was compressed at compile time.
-///
-/// This function is an implementation detail of the [`include_packed!`] macro and is not
-/// intended to be called directly by user code. Its signature is not guaranteed to be stable.
+pub mod runtime;
+
+#[cfg(any(feature = "json", feature = "postcard", feature = "bincode"))]
+pub use runtime::Format;
+#[cfg(feature = "bevy")]
+pub use runtime::PackedAssetReader;
+#[cfg(feature = "actix")]
+pub use runtime::PackedFiles;
+#[cfg(feature = "tauri")]
+pub use runtime::PackedProtocol;
+#[cfg(feature = "axum")]
+pub use runtime::PackedServeDir;
+#[cfg(feature = "signatures")]
+pub use runtime::set_trusted_key;
+pub use runtime::{
+    Algorithm, AlignedBytes, AssetInfo, CompressedAsset, DecompressInto, Error, PackedAsset,
+    PackedDir, RawPackedAsset, assets, get,
+};
+#[cfg(feature = "mmap")]
+pub use runtime::{AssetMmap, MmapOptions};
+#[cfg(feature = "std")]
+pub use runtime::{AssetReader, CancellationToken, Cancelled, PatchPack, prewarm};
+#[cfg(feature = "dev")]
+pub use runtime::{AssetWatcher, watch};
+#[cfg(feature = "encryption")]
+pub use runtime::{Cipher, set_key, unlock};
+
+// Kept at the crate root for code expanded by earlier versions of the macros.
+#[doc(hidden)]
+pub use runtime::decompress;
+
+/// Reports that the build script has not run, from code expanded by the macros.
 ///
-/// # Panics
+/// rust-analyzer sees no error here, so its analysis of the surrounding code carries on
+/// with the placeholders the macros expand to instead of assets.
+#[cfg(not(rust_analyzer))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __build_script_missing {
+    ($msg:literal) => {
+        ::core::compile_error!($msg);
+    };
+}
+
+#[cfg(rust_analyzer)]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __build_script_missing {
+    ($msg:literal) => {};
+}
+
+/// Entry points for the fuzzing harness in the repository's `fuzz` directory.
 ///
-/// Panics if the provided data is not valid zstd-compressed data. This indicates a bug in
-/// `include_packed` itself, as the data should always be valid if generated correctly.
+/// These are the non-panicking decoding primitives that every runtime API is built on.
+/// They are not part of the public API.
+#[cfg(feature = "fuzzing")]
 #[doc(hidden)]
-#[track_caller]
-#[must_use]
-pub fn decompress(compressed_data: &'static [u8]) -> Vec<u8> {
-    zstd::decode_all(compressed_data).expect(
-        "BUG: include_packed: failed to decompress compile-time data. This indicates a bug in the crate.",
-    )
+pub mod fuzzing {
+    pub use crate::runtime::codec::{
+        Dictionary, decode_all, decode_chunked, decode_into, decoded_len,
+    };
+    pub use crate::runtime::{Algorithm, PatchPack};
 }
 
 //
@@ -90,5 +163,7 @@ pub fn decompress(compressed_data: &'static [u8]) -> Vec<u8> {
 
 #[cfg(feature = "build")]
 mod build;
+#[cfg(feature = "image-pipeline")]
+pub use build::ImageTransform;
 #[cfg(feature = "build")]
-pub use build::Config;
+pub use build::{AssetSummary, BuildSummary, Config, Encoding, FileConfig, use_shared_assets};
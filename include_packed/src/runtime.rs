@@ -0,0 +1,143 @@
+//! The runtime support behind the `include_packed` macros.
+//!
+//! Everything the macros expand to is built on the items in this module, and they are
+//! covered by the crate's semver guarantees. Code that doesn't use the macros, such as
+//! code generated by other tools that embed zstd-compressed data themselves, can rely on
+//! them directly:
+//!
+//! ```
+//! use include_packed::runtime::{PackedAsset, decompress};
+//!
+//! // Any zstd frame works, e.g. one produced by an external asset pipeline.
+//! static COMPRESSED: &[u8] = &[
+//!     0x28, 0xb5, 0x2f, 0xfd, 0x20, 0x05, 0x29, 0x00, 0x00, 0x68, 0x65, 0x6c, 0x6c, 0x6f,
+//! ];
+//!
+//! assert_eq!(decompress(COMPRESSED), b"hello");
+//! assert_eq!(PackedAsset::from_compressed(COMPRESSED).decompress(), b"hello");
+//! ```
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "actix")]
+mod actix;
+mod aligned;
+#[cfg(feature = "rkyv")]
+mod archived;
+mod asset;
+#[cfg(feature = "axum")]
+mod axum;
+#[cfg(feature = "bevy")]
+mod bevy;
+#[cfg(feature = "std")]
+mod cache;
+#[cfg(feature = "std")]
+mod cancel;
+pub(crate) mod codec;
+mod compressed;
+#[cfg(feature = "encryption")]
+pub(crate) mod crypto;
+#[cfg(any(feature = "json", feature = "postcard", feature = "bincode"))]
+mod deserialize;
+mod dir;
+mod error;
+#[cfg(all(feature = "fetch", target_arch = "wasm32"))]
+mod fetch;
+mod ffi;
+mod into;
+mod manifest;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(feature = "std")]
+pub(crate) mod pack;
+mod provenance;
+#[cfg(feature = "std")]
+mod reader;
+mod registry;
+#[cfg(any(feature = "axum", feature = "actix", feature = "tauri"))]
+mod serve;
+#[cfg(feature = "bytes")]
+mod shared;
+#[cfg(feature = "signatures")]
+mod signature;
+#[cfg(feature = "tauri")]
+mod tauri;
+#[cfg(feature = "tokio")]
+mod tokio;
+#[cfg(feature = "dev")]
+mod watch;
+#[cfg(all(windows, feature = "std"))]
+mod windows;
+
+#[cfg(feature = "actix")]
+pub use actix::PackedFiles;
+pub use aligned::AlignedBytes;
+pub use asset::PackedAsset;
+#[cfg(feature = "axum")]
+pub use axum::PackedServeDir;
+#[cfg(feature = "bevy")]
+pub use bevy::PackedAssetReader;
+#[cfg(feature = "std")]
+pub use cache::prewarm;
+#[cfg(feature = "std")]
+pub use cancel::{CancellationToken, Cancelled};
+pub use codec::Algorithm;
+pub use compressed::CompressedAsset;
+#[cfg(feature = "encryption")]
+pub use crypto::{Cipher, set_key, unlock};
+#[cfg(any(feature = "json", feature = "postcard", feature = "bincode"))]
+pub use deserialize::Format;
+pub use dir::PackedDir;
+pub use error::Error;
+pub use ffi::RawPackedAsset;
+pub use into::DecompressInto;
+pub use manifest::AssetInfo;
+#[cfg(feature = "mmap")]
+pub use mmap::{AssetMmap, MmapOptions};
+#[cfg(feature = "std")]
+pub use pack::PatchPack;
+pub use provenance::Provenance;
+#[cfg(feature = "std")]
+pub use reader::AssetReader;
+pub use registry::{assets, get};
+#[cfg(feature = "signatures")]
+pub use signature::{Signature, set_trusted_key};
+#[cfg(feature = "tauri")]
+pub use tauri::PackedProtocol;
+#[cfg(feature = "dev")]
+pub use watch::{AssetWatcher, watch};
+#[cfg(all(windows, feature = "std"))]
+#[doc(hidden)]
+pub use windows::windows_resource;
+
+/// Decompresses zstd-compressed data into a newly allocated `Vec<u8>`.
+///
+/// This is what [`include_packed!`](crate::include_packed) expands to. The data is
+/// expected to be trusted, i.e. produced by the build script, the macros, or an
+/// equivalent zstd encoder.
+///
+/// # Panics
+///
+/// Panics if the provided data is not valid zstd-compressed data. For data packed by
+/// this crate, this indicates a bug in `include_packed` itself.
+#[track_caller]
+#[must_use]
+pub fn decompress(compressed_data: &[u8]) -> Vec<u8> {
+    codec::decode_all(Algorithm::Zstd, codec::Dictionary::NONE, compressed_data)
+        .expect(codec::BUG_DECOMPRESS)
+}
+
+/// Decompresses zstd-compressed data like [`decompress`], but returns an error instead
+/// of panicking if the data is not valid zstd-compressed data.
+///
+/// # Errors
+///
+/// Returns [`Error::Corrupt`] if the data could not be decompressed.
+pub fn try_decompress(compressed_data: &[u8]) -> Result<Vec<u8>, Error> {
+    codec::decode_all(Algorithm::Zstd, codec::Dictionary::NONE, compressed_data).map_err(|source| {
+        Error::Corrupt {
+            path: "",
+            source: source.into(),
+        }
+    })
+}
@@ -0,0 +1,115 @@
+//! Serving packed assets with actix-web.
+use core::future::ready;
+
+use actix_web::{
+    HttpRequest, HttpResponse, Responder,
+    body::BoxBody,
+    dev::{AppService, HttpServiceFactory},
+    http::{Method, header},
+    web,
+};
+
+use super::{
+    PackedAsset, PackedDir,
+    serve::{Reply, serve_asset, serve_dir},
+};
+
+/// An actix-web service serving the assets of a [`PackedDir`] below a mount path,
+/// like `actix_files::Files`:
+///
+/// ```no_run
+/// use include_packed::{PackedFiles, include_packed_dir};
+/// # // The directory only exists in crates whose build script packed it.
+/// # macro_rules! include_packed_dir {
+/// #     ($path:literal) => {
+/// #         include_packed::PackedDir::new(&[], |_| None)
+/// #     };
+/// # }
+///
+/// let app = actix_web::App::new()
+///     .service(PackedFiles::new("/static", include_packed_dir!("static")));
+/// ```
+///
+/// Requests below the mount path are resolved against the directory by the rest of
+/// their path, and paths ending in `/` serve the `index.html` below them. Responses
+/// carry a `Content-Type` guessed from the file extension and an `ETag` derived from
+/// the asset's data, and conditional requests whose `If-None-Match` matches it are
+/// answered with `304 Not Modified` without decompressing anything. Unknown paths are
+/// answered with `404 Not Found`, methods other than `GET` and `HEAD` with
+/// `405 Method Not Allowed`, and assets that fail to decompress with
+/// `500 Internal Server Error`.
+#[derive(Debug, Clone)]
+pub struct PackedFiles {
+    mount_path: String,
+    dir: PackedDir,
+}
+
+impl PackedFiles {
+    /// Creates a service serving the assets of `dir` below `mount_path`.
+    #[must_use]
+    pub fn new(mount_path: impl Into<String>, dir: PackedDir) -> Self {
+        Self {
+            mount_path: mount_path.into(),
+            dir,
+        }
+    }
+}
+
+impl HttpServiceFactory for PackedFiles {
+    fn register(self, config: &mut AppService) {
+        let dir = self.dir;
+        let pattern = format!("{}/{{tail:.*}}", self.mount_path.trim_end_matches('/'));
+        web::resource(pattern)
+            .route(web::route().to(move |request: HttpRequest| ready(respond_dir(&dir, &request))))
+            .register(config);
+    }
+}
+
+/// Serves the asset of `dir` that the tail of `request`'s path refers to.
+fn respond_dir(dir: &PackedDir, request: &HttpRequest) -> HttpResponse {
+    if !matches!(*request.method(), Method::GET | Method::HEAD) {
+        return HttpResponse::MethodNotAllowed()
+            .insert_header((header::ALLOW, "GET, HEAD"))
+            .finish();
+    }
+    // Actix has already percent-decoded the matched tail.
+    let path = request.match_info().query("tail");
+    response(serve_dir(dir, path, if_none_match(request)))
+}
+
+/// Serves the asset with a `Content-Type` guessed from its logical path, and an `ETag`
+/// that conditional requests are checked against.
+impl Responder for PackedAsset {
+    type Body = BoxBody;
+
+    fn respond_to(self, request: &HttpRequest) -> HttpResponse {
+        response(serve_asset(&self, self.path(), if_none_match(request)))
+    }
+}
+
+/// Returns the values of `request`'s `If-None-Match` headers.
+fn if_none_match(request: &HttpRequest) -> impl Iterator<Item = &str> {
+    request
+        .headers()
+        .get_all(header::IF_NONE_MATCH)
+        .filter_map(|value| value.to_str().ok())
+}
+
+/// Turns `reply` into a response.
+fn response(reply: Reply) -> HttpResponse {
+    match reply {
+        Reply::NotFound => HttpResponse::NotFound().finish(),
+        Reply::Failed => HttpResponse::InternalServerError().finish(),
+        Reply::NotModified { etag } => HttpResponse::NotModified()
+            .insert_header((header::ETAG, etag))
+            .finish(),
+        Reply::Found {
+            content_type,
+            etag,
+            data,
+        } => HttpResponse::Ok()
+            .content_type(content_type)
+            .insert_header((header::ETAG, etag))
+            .body(data),
+    }
+}
@@ -0,0 +1,106 @@
+//! Decompressing packed assets into buffers with a guaranteed alignment.
+use alloc::vec::Vec;
+use core::{fmt, ops::Deref};
+
+use super::{Error, PackedAsset};
+
+/// Decompressed data whose first byte lies at a multiple of its alignment, so it can be
+/// reinterpreted as slices of wider types such as `u32` or SIMD vectors.
+///
+/// This is returned by [`PackedAsset::decompress_aligned`] and
+/// [`include_packed_aligned!`](crate::include_packed_aligned), and dereferences to the
+/// data.
+pub struct AlignedBytes {
+    /// The allocation holding the data, which starts at `start`.
+    buf: Vec<u8>,
+    start: usize,
+    align: usize,
+}
+
+impl AlignedBytes {
+    /// Moves `data` into a buffer aligned to `align` bytes, which must be a power of
+    /// two. The data is only copied if its allocation is not aligned already.
+    #[must_use]
+    pub fn new(data: Vec<u8>, align: usize) -> Self {
+        assert!(align.is_power_of_two(), "alignment must be a power of two");
+        if data.as_ptr().addr().is_multiple_of(align) {
+            return Self {
+                buf: data,
+                start: 0,
+                align,
+            };
+        }
+        // The spare bytes guarantee an aligned address within the allocation, which
+        // doesn't move since the buffer is never grown past its capacity.
+        let mut buf = Vec::<u8>::with_capacity(data.len() + align - 1);
+        let start = buf.as_ptr().addr().wrapping_neg() % align;
+        buf.resize(start, 0);
+        buf.extend_from_slice(&data);
+        Self { buf, start, align }
+    }
+
+    /// Returns the alignment of the data.
+    #[must_use]
+    pub const fn align(&self) -> usize {
+        self.align
+    }
+}
+
+impl Deref for AlignedBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.buf[self.start..]
+    }
+}
+
+impl AsRef<[u8]> for AlignedBytes {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl Clone for AlignedBytes {
+    fn clone(&self) -> Self {
+        // A plain copy of the buffer could lose the alignment.
+        Self::new(self.to_vec(), self.align)
+    }
+}
+
+impl fmt::Debug for AlignedBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AlignedBytes")
+            .field("len", &self.len())
+            .field("align", &self.align)
+            .finish()
+    }
+}
+
+impl PackedAsset {
+    /// Decompresses the asset into a buffer aligned to [`align`](Self::align) bytes.
+    ///
+    /// This is what [`include_packed_aligned!`](crate::include_packed_aligned) expands
+    /// to.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`decompress`](Self::decompress).
+    #[track_caller]
+    #[must_use]
+    pub fn decompress_aligned(&self) -> AlignedBytes {
+        self.try_decompress_aligned()
+            .unwrap_or_else(|err| panic!("include_packed: {err}"))
+    }
+
+    /// Decompresses the asset into an aligned buffer like
+    /// [`decompress_aligned`](Self::decompress_aligned), but returns an error instead of
+    /// panicking.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`try_decompress`](Self::try_decompress).
+    pub fn try_decompress_aligned(&self) -> Result<AlignedBytes, Error> {
+        self.try_decompress()
+            .map(|data| AlignedBytes::new(data, self.align()))
+    }
+}
@@ -0,0 +1,54 @@
+//! Accessing rkyv archives in packed assets without deserializing them.
+use rkyv::{Portable, api::high::HighValidator, bytecheck::CheckBytes, rancor};
+
+use super::{Error, PackedAsset};
+
+impl PackedAsset {
+    /// Validates the asset as an rkyv archive of `T` and returns a reference to it.
+    ///
+    /// Stored assets whose data is aligned for `T` are accessed in place, so packing
+    /// archives with `Algorithm::Store` and a sufficient `Config::align` gives access
+    /// without any decompression or copy. Other assets are decompressed once into the
+    /// cache of [`cached`](Self::cached), aligned for `T`.
+    ///
+    /// The archive is validated on every call.
+    /// [`include_packed_archived!`](crate::include_packed_archived) validates it only
+    /// once.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`decompress`](Self::decompress), and if the
+    /// data is not a valid archive of `T`.
+    #[track_caller]
+    #[must_use]
+    pub fn archived<T>(&self) -> &'static T
+    where
+        T: Portable + for<'a> CheckBytes<HighValidator<'a, rancor::Error>>,
+    {
+        self.try_archived()
+            .unwrap_or_else(|err| panic!("include_packed: {err}"))
+    }
+
+    /// Validates the asset as an rkyv archive of `T` like
+    /// [`archived`](Self::archived), but returns an error instead of panicking.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidArchive`] if the data is not a valid archive of `T`, and
+    /// the errors of [`try_decompress`](Self::try_decompress) if it could not be
+    /// decompressed.
+    pub fn try_archived<T>(&self) -> Result<&'static T, Error>
+    where
+        T: Portable + for<'a> CheckBytes<HighValidator<'a, rancor::Error>>,
+    {
+        let align = align_of::<T>();
+        let data = match self.as_stored() {
+            Some(data) if data.as_ptr().addr().is_multiple_of(align) => data,
+            _ => self.try_cached_aligned(self.align().max(align))?,
+        };
+        rkyv::access::<T, rancor::Error>(data).map_err(|err| Error::InvalidArchive {
+            path: self.path(),
+            source: err.into(),
+        })
+    }
+}
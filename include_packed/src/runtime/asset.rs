@@ -0,0 +1,714 @@
+//! The [`PackedAsset`] handle returned by [`include_packed_asset!`](crate::include_packed_asset).
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::{fmt, ops::Range};
+
+#[cfg(feature = "signatures")]
+use super::Signature;
+#[cfg(feature = "encryption")]
+use super::crypto::{self, Cipher};
+use super::{Algorithm, Error, Provenance, codec};
+#[cfg(feature = "std")]
+use super::{
+    AssetReader,
+    cancel::{CancellationToken, Cancelled},
+};
+
+/// Size of the chunks produced between cancellation checks.
+#[cfg(feature = "std")]
+const CANCELLATION_CHUNK_SIZE: usize = 1 << 20;
+
+/// A handle to an asset that was packed at compile time.
+///
+/// Unlike [`include_packed!`](crate::include_packed), which decompresses immediately,
+/// the [`include_packed_asset!`](crate::include_packed_asset) macro expands to this
+/// lightweight, `Copy` handle. It can be stored in a `const` or passed around freely,
+/// and decompressed on demand in whichever way suits the caller.
+#[derive(Debug, Clone, Copy)]
+pub struct PackedAsset {
+    compressed: &'static [u8],
+    path: &'static str,
+    provenance: Option<&'static Provenance>,
+    algorithm: Algorithm,
+    dictionary: &'static [u8],
+    base: Option<&'static PackedAsset>,
+    #[cfg(feature = "encryption")]
+    cipher: Option<Cipher>,
+    #[cfg(feature = "checksums")]
+    checksum: Option<&'static [u8; 32]>,
+    #[cfg(feature = "signatures")]
+    signature: Option<&'static Signature>,
+    passthrough: Option<&'static str>,
+    chunks: Option<Chunks>,
+    align: usize,
+}
+
+/// Panics unless `range` is an increasing range within an asset of length `len`.
+#[track_caller]
+fn check_range(range: &Range<usize>, len: usize) {
+    assert!(
+        range.start <= range.end && range.end <= len,
+        "range {range:?} is out of bounds for asset of length {len}"
+    );
+}
+
+/// The dictionary an asset is decoded with, as returned by
+/// [`PackedAsset::try_decoding_dictionary`].
+pub(crate) enum DecodingDictionary {
+    /// The zstd dictionary the data was compressed with, or empty if none was used.
+    Trained(&'static [u8]),
+    /// The decompressed base of a delta asset.
+    Base(Vec<u8>),
+}
+
+impl DecodingDictionary {
+    /// Returns the dictionary in the form the decoders take.
+    pub(crate) fn get(&self) -> codec::Dictionary<'_> {
+        match self {
+            Self::Trained(dictionary) => codec::Dictionary::Trained(dictionary),
+            Self::Base(base) => codec::Dictionary::Raw(base),
+        }
+    }
+}
+
+/// The layout of an asset split into independently compressed chunks.
+#[derive(Debug, Clone, Copy)]
+struct Chunks {
+    /// The size of every chunk but the last, before compression.
+    size: usize,
+    /// The size of the whole asset, before compression.
+    len: usize,
+    /// The offsets in the compressed data at which each chunk ends.
+    ends: &'static [usize],
+}
+
+impl PackedAsset {
+    /// Creates a handle from zstd-compressed data.
+    ///
+    /// This is what [`include_packed_asset!`](crate::include_packed_asset) expands to,
+    /// but it can also wrap compressed data embedded by other means.
+    #[must_use]
+    pub const fn from_compressed(compressed: &'static [u8]) -> Self {
+        Self {
+            compressed,
+            path: "",
+            provenance: None,
+            algorithm: Algorithm::Zstd,
+            dictionary: &[],
+            base: None,
+            #[cfg(feature = "encryption")]
+            cipher: None,
+            #[cfg(feature = "checksums")]
+            checksum: None,
+            #[cfg(feature = "signatures")]
+            signature: None,
+            passthrough: None,
+            chunks: None,
+            align: 1,
+        }
+    }
+
+    /// Sets the algorithm the data was compressed with. Defaults to [`Algorithm::Zstd`].
+    #[must_use]
+    pub const fn with_algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Returns the algorithm the data was compressed with.
+    #[must_use]
+    pub const fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Sets the zstd dictionary the data was compressed with.
+    ///
+    /// The macros attach the dictionary trained by `Config::train_dictionary`.
+    #[must_use]
+    pub const fn with_dictionary(mut self, dictionary: &'static [u8]) -> Self {
+        self.dictionary = dictionary;
+        self
+    }
+
+    /// Returns the zstd dictionary the data was compressed with, or an empty slice if
+    /// none was used.
+    #[must_use]
+    pub const fn dictionary(&self) -> &'static [u8] {
+        self.dictionary
+    }
+
+    /// Sets the asset the data is a zstd patch against.
+    ///
+    /// The macros attach the base asset of `Config::delta`. Decompressing the asset then
+    /// decompresses the base first, and uses its contents as the dictionary.
+    #[must_use]
+    pub const fn with_delta_base(mut self, base: &'static Self) -> Self {
+        self.base = Some(base);
+        self
+    }
+
+    /// Returns the asset the data is a patch against, if it is a delta asset.
+    #[must_use]
+    pub const fn delta_base(&self) -> Option<&'static Self> {
+        self.base
+    }
+
+    /// Returns the dictionary to decode the data with: the decompressed base of a delta
+    /// asset, and the zstd dictionary otherwise.
+    ///
+    /// # Errors
+    /// Returns the errors of [`try_decompress`](Self::try_decompress) for the base.
+    pub(crate) fn try_decoding_dictionary(&self) -> Result<DecodingDictionary, Error> {
+        let Some(base) = self.base else {
+            return Ok(DecodingDictionary::Trained(self.dictionary));
+        };
+        // The patch was made against the packed contents, not against later edits.
+        let base = Self {
+            passthrough: None,
+            ..*base
+        };
+        base.try_decompress().map(DecodingDictionary::Base)
+    }
+
+    /// Returns the dictionary like
+    /// [`try_decoding_dictionary`](Self::try_decoding_dictionary), but panics if the base
+    /// could not be decompressed.
+    #[track_caller]
+    pub(crate) fn decoding_dictionary(&self) -> DecodingDictionary {
+        self.try_decoding_dictionary()
+            .unwrap_or_else(|err| panic!("include_packed: {err}"))
+    }
+
+    /// Sets the cipher the compressed data was encrypted with.
+    ///
+    /// Encrypted assets are decrypted with the key set by
+    /// [`set_key`](super::set_key) before they are decompressed.
+    #[cfg(feature = "encryption")]
+    #[must_use]
+    pub const fn with_cipher(mut self, cipher: Cipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Returns the cipher the data was encrypted with, if any.
+    #[cfg(feature = "encryption")]
+    #[must_use]
+    pub const fn cipher(&self) -> Option<Cipher> {
+        self.cipher
+    }
+
+    /// Attaches the BLAKE3 hash of the asset's original contents.
+    ///
+    /// The macros attach the checksum recorded by `Config::verify`, and every
+    /// decompression then verifies the result against it.
+    #[cfg(feature = "checksums")]
+    #[must_use]
+    pub const fn with_checksum(mut self, checksum: &'static [u8; 32]) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+
+    /// Returns the BLAKE3 hash of the asset's original contents, if one was recorded.
+    #[cfg(feature = "checksums")]
+    #[must_use]
+    pub const fn checksum(&self) -> Option<&'static [u8; 32]> {
+        self.checksum
+    }
+
+    /// Attaches the ed25519 signature of the asset's checksum.
+    ///
+    /// The macros attach the signature recorded by `Config::sign`, and every
+    /// decompression then verifies it along with the checksum.
+    #[cfg(feature = "signatures")]
+    #[must_use]
+    pub const fn with_signature(mut self, signature: &'static Signature) -> Self {
+        self.signature = Some(signature);
+        self
+    }
+
+    /// Returns the ed25519 signature of the asset, if it was signed.
+    #[cfg(feature = "signatures")]
+    #[must_use]
+    pub const fn signature(&self) -> Option<&'static Signature> {
+        self.signature
+    }
+
+    /// Makes the asset read its contents from the file at `path` while it exists,
+    /// instead of decompressing the embedded data.
+    ///
+    /// The macros set this in debug builds for assets packed with
+    /// `Config::dev_passthrough`. It has no effect without the `std` feature.
+    #[must_use]
+    pub const fn with_passthrough(mut self, path: &'static str) -> Self {
+        self.passthrough = Some(path);
+        self
+    }
+
+    /// Returns the path of the file the asset's contents are read from, if any.
+    #[must_use]
+    pub const fn passthrough(&self) -> Option<&'static str> {
+        self.passthrough
+    }
+
+    /// Records that the data consists of independently compressed chunks.
+    ///
+    /// Each chunk holds `chunk_size` bytes of the `len`-byte original, except for the
+    /// last, and `ends` lists the offsets in the compressed data at which each chunk
+    /// ends. The macros set this for assets packed with `Config::chunk_size`, which
+    /// makes [`decompress_range`](Self::decompress_range) cheap.
+    #[must_use]
+    pub const fn with_chunks(
+        mut self,
+        chunk_size: usize,
+        len: usize,
+        ends: &'static [usize],
+    ) -> Self {
+        self.chunks = Some(Chunks {
+            size: chunk_size,
+            len,
+            ends,
+        });
+        self
+    }
+
+    /// Records the alignment the data was packed at, which must be a power of two.
+    ///
+    /// The macros set this for assets packed with `Config::align`, and the aligned
+    /// decompression APIs such as [`decompress_aligned`](Self::decompress_aligned) align
+    /// their buffers to it.
+    #[must_use]
+    pub const fn with_align(mut self, align: usize) -> Self {
+        assert!(align.is_power_of_two(), "alignment must be a power of two");
+        self.align = align;
+        self
+    }
+
+    /// Returns the alignment of the asset's data, which is `1` unless it was packed with
+    /// `Config::align`.
+    ///
+    /// The data returned by [`as_stored`](Self::as_stored) starts at a multiple of it.
+    #[must_use]
+    pub const fn align(&self) -> usize {
+        self.align
+    }
+
+    /// Returns the size of the decompressed asset if it is known without decompressing
+    /// it.
+    #[cfg(feature = "mmap")]
+    pub(crate) fn known_len(&self) -> Option<usize> {
+        self.chunks.map(|chunks| chunks.len)
+    }
+
+    /// Attaches the asset's logical path, which is used in error and panic messages.
+    ///
+    /// The macros record the path exactly as it was written at the call site.
+    #[must_use]
+    pub const fn with_path(mut self, path: &'static str) -> Self {
+        self.path = path;
+        self
+    }
+
+    /// Returns the asset's logical path, or an empty string if none was recorded.
+    #[must_use]
+    pub const fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// Attaches build provenance to the asset.
+    #[must_use]
+    pub const fn with_provenance(mut self, provenance: &'static Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    /// Returns the build provenance recorded for the asset.
+    ///
+    /// This is only available for assets packed with `Config::provenance` enabled.
+    #[must_use]
+    pub const fn provenance(&self) -> Option<&'static Provenance> {
+        self.provenance
+    }
+
+    /// Returns the embedded data without copying if the asset is stored uncompressed.
+    ///
+    /// This is the case for unencrypted assets packed with [`Algorithm::Store`], and
+    /// returns `None` for every other asset.
+    #[must_use]
+    pub fn as_stored(&self) -> Option<&'static [u8]> {
+        #[cfg(feature = "encryption")]
+        if self.cipher.is_some() {
+            return None;
+        }
+        (self.algorithm == Algorithm::Store).then_some(self.compressed)
+    }
+
+    /// Returns the compressed bytes embedded in the binary.
+    pub(crate) const fn compressed(&self) -> &'static [u8] {
+        self.compressed
+    }
+
+    /// Returns the compressed data, decrypting it first if the asset is encrypted.
+    ///
+    /// # Errors
+    /// Returns [`Error::MissingKey`] or [`Error::WrongKey`] if the asset could not be
+    /// decrypted.
+    pub(crate) fn try_decrypted(&self) -> Result<Cow<'static, [u8]>, Error> {
+        #[cfg(feature = "encryption")]
+        if let Some(cipher) = self.cipher {
+            return crypto::decrypt(cipher, self.path, self.compressed).map(Cow::Owned);
+        }
+        Ok(Cow::Borrowed(self.compressed))
+    }
+
+    /// Returns the compressed data like [`try_decrypted`](Self::try_decrypted), but
+    /// panics if it could not be decrypted.
+    #[track_caller]
+    pub(crate) fn decrypted(&self) -> Cow<'static, [u8]> {
+        self.try_decrypted()
+            .unwrap_or_else(|err| panic!("include_packed: {err}"))
+    }
+
+    /// Decompresses the asset into a newly allocated `Vec<u8>`.
+    ///
+    /// This is equivalent to calling [`include_packed!`](crate::include_packed) for the
+    /// same path.
+    ///
+    /// # Panics
+    ///
+    /// Panics with a message naming the asset if the embedded data is not valid
+    /// zstd-compressed data, which indicates a bug in `include_packed` itself, if the
+    /// asset is encrypted and could not be decrypted, or if it does not match its
+    /// checksum.
+    #[track_caller]
+    #[must_use]
+    pub fn decompress(&self) -> Vec<u8> {
+        if let Some(data) = self.read_passthrough() {
+            return data;
+        }
+        let data = codec::decode_all(
+            self.algorithm,
+            self.decoding_dictionary().get(),
+            &self.decrypted(),
+        )
+        .unwrap_or_else(|err| self.corrupt(&err));
+        self.verified(data)
+    }
+
+    /// Decompresses a UTF-8 text asset into a newly allocated `String`.
+    ///
+    /// This is what [`include_packed_str!`](crate::include_packed_str) expands to, after
+    /// checking at compile time that the asset is valid UTF-8.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`decompress`](Self::decompress), and if the
+    /// decompressed data is not valid UTF-8.
+    #[track_caller]
+    #[must_use]
+    pub fn decompress_string(&self) -> String {
+        String::from_utf8(self.decompress()).unwrap_or_else(|err| self.corrupt(&err))
+    }
+
+    /// Returns the asset's data, borrowed from the binary if it is stored uncompressed and
+    /// decompressed into a newly allocated buffer otherwise.
+    ///
+    /// This gives zero-copy access where possible with a single type for every asset,
+    /// and is what [`include_packed_cow!`](crate::include_packed_cow) expands to.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`decompress`](Self::decompress).
+    #[track_caller]
+    #[must_use]
+    pub fn decompress_cow(&self) -> Cow<'static, [u8]> {
+        self.try_decompress_cow()
+            .unwrap_or_else(|err| panic!("include_packed: {err}"))
+    }
+
+    /// Returns the asset's data like [`decompress_cow`](Self::decompress_cow), but returns
+    /// an error instead of panicking.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`try_decompress`](Self::try_decompress).
+    pub fn try_decompress_cow(&self) -> Result<Cow<'static, [u8]>, Error> {
+        match self.as_stored() {
+            // Edited files take precedence over the embedded data.
+            Some(data) if self.passthrough.is_none() => {
+                self.verify_checksum(data)?;
+                Ok(Cow::Borrowed(data))
+            }
+            _ => self.try_decompress().map(Cow::Owned),
+        }
+    }
+
+    /// Decompresses the asset like [`decompress`](Self::decompress), but returns an error
+    /// instead of panicking if the embedded data is corrupt.
+    ///
+    /// This is what [`include_packed_try!`](crate::include_packed_try) expands to.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Corrupt`] if the embedded data could not be decompressed,
+    /// [`Error::MissingKey`] or [`Error::WrongKey`] if the asset is encrypted and could
+    /// not be decrypted, and [`Error::ChecksumMismatch`] if it does not match its
+    /// checksum.
+    pub fn try_decompress(&self) -> Result<Vec<u8>, Error> {
+        if let Some(data) = self.read_passthrough() {
+            return Ok(data);
+        }
+        let data = codec::decode_all(
+            self.algorithm,
+            self.try_decoding_dictionary()?.get(),
+            &self.try_decrypted()?,
+        )
+        .map_err(|err| self.corrupt_error(err))?;
+        self.verify_checksum(&data)?;
+        Ok(data)
+    }
+
+    /// Decompresses a UTF-8 text asset like [`decompress_string`](Self::decompress_string),
+    /// but returns an error instead of panicking.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`try_decompress`](Self::try_decompress), and
+    /// [`Error::InvalidUtf8`] if the asset is not valid UTF-8.
+    pub fn try_decompress_string(&self) -> Result<String, Error> {
+        String::from_utf8(self.try_decompress()?).map_err(|err| Error::InvalidUtf8 {
+            path: self.path,
+            source: err.utf8_error(),
+        })
+    }
+
+    /// Decompresses only the bytes in `range` of the asset.
+    ///
+    /// For assets packed with `Config::chunk_size`, only the chunks overlapping the range
+    /// are decompressed, and assets packed with [`Algorithm::Store`] are sliced without
+    /// decompressing anything. Other assets are decompressed in full first. Checksums
+    /// are not verified for partial reads. Assets read through development passthrough
+    /// are sliced from the file on disk, so the range must lie within that file.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or decreasing, and under the same
+    /// conditions as [`decompress`](Self::decompress).
+    #[track_caller]
+    #[must_use]
+    pub fn decompress_range(&self, range: Range<usize>) -> Vec<u8> {
+        self.decode_range(range, &self.decrypted())
+            .unwrap_or_else(|err| panic!("include_packed: {err}"))
+    }
+
+    /// Decompresses only the bytes in `range` of the asset like
+    /// [`decompress_range`](Self::decompress_range), but returns an error instead of
+    /// panicking if the embedded data is corrupt.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`try_decompress`](Self::try_decompress), except for
+    /// checksum mismatches.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or decreasing.
+    #[track_caller]
+    pub fn try_decompress_range(&self, range: Range<usize>) -> Result<Vec<u8>, Error> {
+        self.decode_range(range, &self.try_decrypted()?)
+    }
+
+    /// Decodes `range` of the asset from its decrypted `compressed` data.
+    #[track_caller]
+    fn decode_range(&self, range: Range<usize>, compressed: &[u8]) -> Result<Vec<u8>, Error> {
+        // An edited file is read on its own, and its ranges are bounded by its length.
+        if let Some(data) = self.read_passthrough() {
+            check_range(&range, data.len());
+            return Ok(data[range].to_vec());
+        }
+        let Some(chunks) = self.chunks else {
+            if self.algorithm == Algorithm::Store {
+                check_range(&range, compressed.len());
+                return Ok(compressed[range].to_vec());
+            }
+            let dictionary = self.try_decoding_dictionary()?;
+            let data = codec::decode_all(self.algorithm, dictionary.get(), compressed)
+                .map_err(|err| self.corrupt_error(err))?;
+            check_range(&range, data.len());
+            return Ok(data[range].to_vec());
+        };
+
+        check_range(&range, chunks.len);
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+        // Consecutive chunks are consecutive zstd frames, which decode in one go.
+        let first = range.start / chunks.size;
+        let last = (range.end - 1) / chunks.size;
+        let start = if first == 0 {
+            0
+        } else {
+            chunks.ends[first - 1]
+        };
+        let data = codec::decode_all(
+            self.algorithm,
+            self.try_decoding_dictionary()?.get(),
+            &compressed[start..chunks.ends[last]],
+        )
+        .map_err(|err| self.corrupt_error(err))?;
+        let offset = first * chunks.size;
+        Ok(data[range.start - offset..range.end - offset].to_vec())
+    }
+
+    /// Returns a reader that decompresses the asset incrementally as it is read.
+    ///
+    /// This is what [`include_packed_reader!`](crate::include_packed_reader) expands to.
+    /// Encrypted assets are decrypted and decompressed up front, as the whole data must be
+    /// authenticated before any of it is released.
+    ///
+    /// Assets with a checksum are verified once the reader reaches the end, which then
+    /// fails with an error of kind [`InvalidData`](std::io::ErrorKind::InvalidData) on a
+    /// mismatch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the asset is encrypted and could not be decrypted.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[track_caller]
+    pub fn reader(&self) -> AssetReader {
+        AssetReader::new(self)
+    }
+
+    /// Decompresses the asset, periodically checking `token` for cancellation.
+    ///
+    /// The data is decompressed in chunks of roughly 1 MiB, and the token is checked
+    /// before each chunk. This lets a long-running decompression on a background thread
+    /// stop promptly once the user aborts the surrounding operation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Cancelled`] if the token was cancelled before decompression finished.
+    /// Any partially decompressed data is discarded.
+    ///
+    /// # Panics
+    ///
+    /// Panics with a message naming the asset if the embedded data is not valid
+    /// zstd-compressed data, which indicates a bug in `include_packed` itself, if the
+    /// asset is encrypted and could not be decrypted, or if it does not match its
+    /// checksum.
+    #[cfg(feature = "std")]
+    #[track_caller]
+    pub fn decompress_cancellable(&self, token: &CancellationToken) -> Result<Vec<u8>, Cancelled> {
+        if let Some(data) = self.read_passthrough() {
+            return Ok(data);
+        }
+        codec::decode_chunked(
+            self.algorithm,
+            self.decoding_dictionary().get(),
+            &self.decrypted(),
+            CANCELLATION_CHUNK_SIZE,
+            || !token.is_cancelled(),
+        )
+        .unwrap_or_else(|err| self.corrupt(&err))
+        .map(|data| self.verified(data))
+        .ok_or(Cancelled)
+    }
+
+    /// Decompresses the asset into a new anonymous memory map with default
+    /// [`MmapOptions`](super::MmapOptions).
+    ///
+    /// # Errors
+    /// Returns an error if the memory map could not be created.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`decompress`](Self::decompress).
+    #[cfg(feature = "mmap")]
+    #[track_caller]
+    pub fn decompress_to_mmap(&self) -> std::io::Result<super::AssetMmap> {
+        super::MmapOptions::new().decompress(self)
+    }
+
+    /// Returns the error reported when the asset's data failed to decode.
+    pub(crate) fn corrupt_error(
+        &self,
+        source: impl core::error::Error + Send + Sync + 'static,
+    ) -> Error {
+        Error::Corrupt {
+            path: self.path,
+            source: source.into(),
+        }
+    }
+
+    /// Reads the file set by [`with_passthrough`](Self::with_passthrough), returning
+    /// `None` if there is none or it could not be read.
+    pub(crate) fn read_passthrough(&self) -> Option<Vec<u8>> {
+        #[cfg(feature = "std")]
+        if let Some(path) = self.passthrough {
+            return std::fs::read(path).ok();
+        }
+        None
+    }
+
+    /// Checks decompressed `data` against the asset's checksum and signature, if it has
+    /// them.
+    ///
+    /// # Errors
+    /// Returns [`Error::ChecksumMismatch`] if the data does not match, and
+    /// [`Error::SignatureMismatch`] if the signature is invalid.
+    pub(crate) fn verify_checksum(&self, data: &[u8]) -> Result<(), Error> {
+        #[cfg(feature = "checksums")]
+        if self.checksum.is_some() {
+            return self.verify_hash(&blake3::hash(data));
+        }
+        let _ = data;
+        Ok(())
+    }
+
+    /// Checks the BLAKE3 `hash` of the decompressed data against the asset's checksum
+    /// and signature, if it has them.
+    ///
+    /// # Errors
+    /// Returns [`Error::ChecksumMismatch`] if the hash does not match, and
+    /// [`Error::SignatureMismatch`] if the signature is invalid.
+    #[cfg(feature = "checksums")]
+    pub(crate) fn verify_hash(&self, hash: &blake3::Hash) -> Result<(), Error> {
+        let Some(checksum) = self.checksum else {
+            return Ok(());
+        };
+        if hash != checksum {
+            return Err(Error::ChecksumMismatch { path: self.path });
+        }
+        #[cfg(feature = "signatures")]
+        if let Some(signature) = self.signature {
+            signature.verify(self.path, checksum)?;
+        }
+        Ok(())
+    }
+
+    /// Returns decompressed `data` after checking it against the asset's checksum.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data does not match the checksum.
+    #[track_caller]
+    fn verified(&self, data: Vec<u8>) -> Vec<u8> {
+        if let Err(err) = self.verify_checksum(&data) {
+            panic!("include_packed: {err}");
+        }
+        data
+    }
+
+    /// Panics with a message naming this asset after its data failed to decode.
+    #[cold]
+    #[track_caller]
+    pub(crate) fn corrupt(&self, err: &dyn fmt::Display) -> ! {
+        let path = if self.path.is_empty() {
+            "<unnamed>"
+        } else {
+            self.path
+        };
+        panic!(
+            "include_packed: failed to decompress asset '{path}': {err}. The embedded data is corrupt, which indicates a bug in `include_packed` or a damaged binary."
+        )
+    }
+}
@@ -0,0 +1,141 @@
+//! Serving packed directories with `tower`-based frameworks such as axum.
+use core::{
+    convert::Infallible,
+    future::{Ready, ready},
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use http::{HeaderValue, Method, Request, Response, StatusCode, header};
+use http_body_util::Full;
+
+use super::{
+    PackedDir,
+    serve::{Reply, percent_decode, serve_dir},
+};
+
+/// A `tower` [`Service`](tower_service::Service) serving the assets of a [`PackedDir`].
+///
+/// Requests are resolved against the directory by their URI path, so the service can
+/// be mounted at any prefix, e.g. with axum's `Router::nest_service`:
+///
+/// ```no_run
+/// use include_packed::{PackedServeDir, include_packed_dir};
+/// # // The directory only exists in crates whose build script packed it.
+/// # macro_rules! include_packed_dir {
+/// #     ($path:literal) => {
+/// #         include_packed::PackedDir::new(&[], |_| None)
+/// #     };
+/// # }
+///
+/// # // `axum` is not a dependency of this crate.
+/// # #[cfg(any())]
+/// # fn main() {
+/// let app = axum::Router::new()
+///     .nest_service("/static", PackedServeDir::new(include_packed_dir!("static")));
+/// # }
+/// # #[cfg(not(any()))]
+/// # fn main() {
+/// #     let _ = PackedServeDir::new(include_packed_dir!("static"));
+/// # }
+/// ```
+///
+/// Paths ending in `/` serve the `index.html` below them. Responses carry a
+/// `Content-Type` guessed from the file extension and an `ETag` derived from the
+/// asset's data, and conditional requests whose `If-None-Match` matches it are answered
+/// with `304 Not Modified` without decompressing anything. Unknown paths are answered
+/// with `404 Not Found`, methods other than `GET` and `HEAD` with
+/// `405 Method Not Allowed`, and assets that fail to decompress with
+/// `500 Internal Server Error`. Assets are decompressed on every request, on the thread
+/// handling it; cache responses in front of the service if that is too slow.
+#[derive(Debug, Clone, Copy)]
+pub struct PackedServeDir {
+    dir: PackedDir,
+}
+
+impl PackedServeDir {
+    /// Creates a service serving the assets of `dir`.
+    #[must_use]
+    pub const fn new(dir: PackedDir) -> Self {
+        Self { dir }
+    }
+
+    /// Returns the response to `request`.
+    ///
+    /// This is what the service responds with, for callers that drive it by hand.
+    #[must_use]
+    pub fn respond<B>(&self, request: &Request<B>) -> Response<Full<Bytes>> {
+        let head = match *request.method() {
+            Method::GET => false,
+            Method::HEAD => true,
+            _ => {
+                let mut response = status(StatusCode::METHOD_NOT_ALLOWED);
+                response
+                    .headers_mut()
+                    .insert(header::ALLOW, HeaderValue::from_static("GET, HEAD"));
+                return response;
+            }
+        };
+        let Some(path) = percent_decode(request.uri().path()) else {
+            return status(StatusCode::NOT_FOUND);
+        };
+        let if_none_match = request
+            .headers()
+            .get_all(header::IF_NONE_MATCH)
+            .iter()
+            .filter_map(|value| value.to_str().ok());
+
+        match serve_dir(&self.dir, &path, if_none_match) {
+            Reply::NotFound => status(StatusCode::NOT_FOUND),
+            Reply::Failed => status(StatusCode::INTERNAL_SERVER_ERROR),
+            Reply::NotModified { etag } => {
+                let mut response = status(StatusCode::NOT_MODIFIED);
+                response
+                    .headers_mut()
+                    .insert(header::ETAG, header_value(etag));
+                response
+            }
+            Reply::Found {
+                content_type,
+                etag,
+                data,
+            } => {
+                let mut response = Response::new(Full::default());
+                let headers = response.headers_mut();
+                headers.insert(header::CONTENT_TYPE, header_value(content_type));
+                headers.insert(header::CONTENT_LENGTH, HeaderValue::from(data.len()));
+                headers.insert(header::ETAG, header_value(etag));
+                if !head {
+                    *response.body_mut() = Full::new(Bytes::from(data));
+                }
+                response
+            }
+        }
+    }
+}
+
+impl<B> tower_service::Service<Request<B>> for PackedServeDir {
+    type Response = Response<Full<Bytes>>;
+    type Error = Infallible;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        ready(Ok(self.respond(&request)))
+    }
+}
+
+/// Returns an empty response with the given status.
+fn status(status: StatusCode) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::default());
+    *response.status_mut() = status;
+    response
+}
+
+/// Converts a content type or `ETag` produced by [`serve_dir`] into a header value.
+fn header_value(value: String) -> HeaderValue {
+    HeaderValue::try_from(value).expect("content types and ETags are valid header values")
+}
@@ -0,0 +1,145 @@
+//! Loading packed assets with Bevy's asset server.
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use bevy_asset::io::{
+    AssetReader, AssetReaderError, AssetSourceBuilder, PathStream, Reader, VecReader,
+};
+
+use super::PackedDir;
+
+/// A Bevy [`AssetReader`] that loads assets from a [`PackedDir`].
+///
+/// Registering it as the default asset source lets `asset_server.load` keep working
+/// with the same paths, while the assets are decompressed from the executable instead
+/// of read from disk. Sources must be registered before `AssetPlugin`, which is part
+/// of `DefaultPlugins`:
+///
+/// ```no_run
+/// use include_packed::{PackedAssetReader, include_packed_dir};
+/// # // The directory only exists in crates whose build script packed it.
+/// # macro_rules! include_packed_dir {
+/// #     ($path:literal) => {
+/// #         include_packed::PackedDir::new(&[], |_| None)
+/// #     };
+/// # }
+///
+/// # // `bevy` is not a dependency of this crate, only `bevy_asset` is.
+/// # #[cfg(any())]
+/// # fn main() {
+/// use bevy::{asset::io::AssetSourceId, prelude::*};
+///
+/// App::new()
+///     .register_asset_source(
+///         AssetSourceId::Default,
+///         PackedAssetReader::source(include_packed_dir!("assets")),
+///     )
+///     .add_plugins(DefaultPlugins);
+/// # }
+/// # #[cfg(not(any()))]
+/// # fn main() {
+/// #     let _ = PackedAssetReader::source(include_packed_dir!("assets"));
+/// # }
+/// ```
+///
+/// Bevy's `.meta` files are looked up next to their assets, and are left out of
+/// directory listings. Assets are decompressed when they are read, on the task that
+/// loads them.
+#[derive(Debug, Clone, Copy)]
+pub struct PackedAssetReader {
+    dir: PackedDir,
+}
+
+impl PackedAssetReader {
+    /// Creates a reader loading the assets of `dir`.
+    #[must_use]
+    pub const fn new(dir: PackedDir) -> Self {
+        Self { dir }
+    }
+
+    /// Returns an asset source reading the assets of `dir`, for
+    /// `App::register_asset_source`.
+    #[must_use]
+    pub fn source(dir: PackedDir) -> AssetSourceBuilder {
+        AssetSourceBuilder::new(move || Box::new(Self::new(dir)))
+    }
+
+    /// Decompresses the asset at `path`.
+    fn decompress(&self, path: &Path) -> Result<VecReader, AssetReaderError> {
+        let asset = key(path)
+            .and_then(|key| self.dir.get(&key))
+            .ok_or_else(|| AssetReaderError::NotFound(path.to_path_buf()))?;
+        let data = asset.try_decompress().map_err(|err| {
+            AssetReaderError::Io(Arc::new(io::Error::new(io::ErrorKind::InvalidData, err)))
+        })?;
+        Ok(VecReader::new(data))
+    }
+
+    /// Returns the paths of the assets below the directory `path`, with `/` appended
+    /// unless it is the root.
+    fn entries_below(&self, path: &Path) -> Option<impl Iterator<Item = &'static str>> {
+        let mut prefix = key(path)?;
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+        Some(
+            self.dir
+                .paths()
+                .iter()
+                .filter_map(move |path| path.strip_prefix(prefix.as_str())),
+        )
+    }
+}
+
+impl AssetReader for PackedAssetReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        self.decompress(path)
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        let mut meta_path = path.as_os_str().to_owned();
+        meta_path.push(".meta");
+        self.decompress(Path::new(&meta_path))
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        let mut children = self
+            .entries_below(path)
+            .into_iter()
+            .flatten()
+            .map(|rest| rest.split_once('/').map_or(rest, |(child, _)| child))
+            .filter(|child| !child.ends_with(".meta"))
+            .collect::<Vec<_>>();
+        if children.is_empty() {
+            return Err(AssetReaderError::NotFound(path.to_path_buf()));
+        }
+        children.dedup();
+        let children = children
+            .into_iter()
+            .map(|child| path.join(child))
+            .collect::<Vec<PathBuf>>();
+        Ok(Box::new(futures_lite::stream::iter(children)))
+    }
+
+    async fn is_directory<'a>(&'a self, path: &'a Path) -> Result<bool, AssetReaderError> {
+        Ok(self
+            .entries_below(path)
+            .is_some_and(|mut entries| entries.next().is_some()))
+    }
+}
+
+/// Returns the `/`-separated path relative to the packed directory that Bevy's asset
+/// `path` refers to.
+fn key(path: &Path) -> Option<String> {
+    let components = path
+        .components()
+        .map(|component| component.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()?;
+    Some(components.join("/"))
+}
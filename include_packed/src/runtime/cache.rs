@@ -0,0 +1,110 @@
+//! The process-wide cache of decompressed assets behind
+//! [`include_packed_lazy!`](crate::include_packed_lazy) and [`prewarm`].
+use std::{
+    collections::BTreeMap,
+    num::NonZeroUsize,
+    sync::{
+        Mutex, OnceLock, PoisonError,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+use super::{AlignedBytes, Error, PackedAsset, assets};
+
+/// The cached decompressed data of an asset.
+type Slot = OnceLock<AlignedBytes>;
+
+/// The decompressed data of every cached asset, by the address and size of its
+/// compressed data and the alignment of the decompressed data. Entries are never
+/// removed, so they are leaked to hand out `'static` references.
+static CACHE: Mutex<BTreeMap<(usize, usize, usize), &'static Slot>> = Mutex::new(BTreeMap::new());
+
+impl PackedAsset {
+    /// Returns the asset's decompressed data, decompressing it only the first time it is
+    /// requested in the process.
+    ///
+    /// Every handle to the same embedded data shares the cache, which [`prewarm`] fills
+    /// ahead of time. The data starts at a multiple of the asset's [`align`](Self::align)
+    /// and stays in memory until the process exits.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`decompress`](Self::decompress).
+    #[track_caller]
+    #[must_use]
+    pub fn cached(&self) -> &'static [u8] {
+        self.try_cached()
+            .unwrap_or_else(|err| panic!("include_packed: {err}"))
+    }
+
+    /// Returns the asset's cached decompressed data like [`cached`](Self::cached), but
+    /// returns an error instead of panicking.
+    ///
+    /// Failures are not cached, so a later call decompresses the asset again.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`try_decompress`](Self::try_decompress).
+    pub fn try_cached(&self) -> Result<&'static [u8], Error> {
+        self.try_cached_aligned(self.align())
+    }
+
+    /// Returns the asset's cached decompressed data, aligned to `align` bytes.
+    pub(crate) fn try_cached_aligned(&self, align: usize) -> Result<&'static [u8], Error> {
+        let slot = self.cache_slot(align);
+        if let Some(data) = slot.get() {
+            return Ok(data);
+        }
+        let data = self.with_align(align).try_decompress_aligned()?;
+        // Another thread may have cached the asset in the meantime.
+        Ok(slot.get_or_init(|| data))
+    }
+
+    /// Returns the entry of the asset's data aligned to `align` bytes in the cache.
+    fn cache_slot(&self, align: usize) -> &'static Slot {
+        let compressed = self.compressed();
+        CACHE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry((compressed.as_ptr() as usize, compressed.len(), align))
+            .or_insert_with(|| Box::leak(Box::default()))
+    }
+}
+
+/// Decompresses every asset registered with
+/// [`include_packed_registry!`](crate::include_packed_registry) into the cache of
+/// [`PackedAsset::cached`] and [`include_packed_lazy!`](crate::include_packed_lazy),
+/// using all available cores.
+///
+/// Calling this at startup, e.g. on a background thread, moves the decompression cost
+/// of the assets out of their first use, avoiding latency spikes in interactive
+/// applications. Assets that fail to decompress, such as encrypted ones whose key isn't
+/// set yet, are skipped and decompressed on first use instead.
+///
+/// The binary must invoke [`include_packed_registry!`](crate::include_packed_registry)
+/// once, or it fails to link.
+pub fn prewarm() {
+    let assets = assets().map(|(_, asset)| asset).collect::<Vec<_>>();
+    let next = AtomicUsize::new(0);
+    let warm = || {
+        while let Some(asset) = assets.get(next.fetch_add(1, Ordering::Relaxed)) {
+            // Failures are reported when the asset is used.
+            let _ = asset.try_cached();
+        }
+    };
+
+    let threads = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(assets.len());
+    if threads <= 1 {
+        // Also covers targets without threads, such as Wasm.
+        warm();
+        return;
+    }
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(warm);
+        }
+    });
+}
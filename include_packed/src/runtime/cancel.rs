@@ -0,0 +1,73 @@
+//! Cooperative cancellation for long-running decompressions.
+use std::{
+    fmt,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+/// A token used to cooperatively cancel a decompression in progress.
+///
+/// Clones of a token share the same state, so one clone can be handed to the thread
+/// performing the work while another is kept to signal cancellation.
+///
+/// # Example
+/// ```no_run
+/// use include_packed::{CancellationToken, include_packed_asset};
+/// # // The asset only exists in crates whose build script packed it.
+/// # macro_rules! include_packed_asset {
+/// #     ($path:literal) => {
+/// #         include_packed::PackedAsset::from_compressed(&[])
+/// #     };
+/// # }
+///
+/// let token = CancellationToken::new();
+/// let worker_token = token.clone();
+/// let worker = std::thread::spawn(move || {
+///     include_packed_asset!("assets/huge.bin").decompress_cancellable(&worker_token)
+/// });
+///
+/// // The user aborted the operation, which may have finished already.
+/// token.cancel();
+/// match worker.join().unwrap() {
+///     Ok(data) => println!("decompressed {} bytes", data.len()),
+///     Err(_) => println!("cancelled"),
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Creates a new token that has not been cancelled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Signals cancellation to every holder of this token.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if [`cancel`](Self::cancel) has been called on this token or any
+    /// of its clones.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// The error returned when a decompression is aborted through a [`CancellationToken`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("decompression was cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
@@ -0,0 +1,399 @@
+//! Decoding primitives shared by the runtime APIs.
+//!
+//! These functions report malformed input as errors instead of panicking. The public
+//! runtime APIs treat such errors as bugs, while the fuzzing harness drives these
+//! functions directly with arbitrary bytes, so it exercises exactly the code that ends
+//! up in user binaries.
+//!
+//! Without the `std` feature, only [`decode_all`] is available, and zstd data is decoded
+//! with the pure-Rust `ruzstd` crate.
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::{self, Read};
+
+/// Panic message used when embedded data fails to decode.
+pub const BUG_DECOMPRESS: &str = "BUG: include_packed: failed to decompress compile-time data. This indicates a bug in the crate.";
+
+/// The error returned when data fails to decode without `std`.
+///
+/// This holds the message of the error reported by `ruzstd`, whose error types don't
+/// implement [`Error`](core::error::Error) without `std`.
+#[cfg(not(feature = "std"))]
+#[derive(Debug)]
+pub struct DecodeError(alloc::string::String);
+
+#[cfg(not(feature = "std"))]
+impl DecodeError {
+    fn new(err: impl core::fmt::Display) -> Self {
+        Self(alloc::string::ToString::to_string(&err))
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl core::error::Error for DecodeError {}
+
+/// The largest zstd window the decoders accept, matching the largest one the encoder
+/// can produce for this pointer width. zstd's streaming decoder otherwise rejects
+/// windows above 128 MiB, which `Config::zstd_window_log` can exceed.
+#[cfg(feature = "std")]
+const MAX_WINDOW_LOG: u32 = if cfg!(target_pointer_width = "64") {
+    31
+} else {
+    30
+};
+
+/// A compression algorithm that assets can be packed with.
+///
+/// The algorithm is chosen with `Config::algorithm` and recorded for every asset, so
+/// the macros automatically select the matching decoder. Algorithms other than
+/// [`Zstd`](Self::Zstd) and [`Store`](Self::Store) are behind Cargo features of the
+/// same name, which must be enabled for both the build-dependency and the regular
+/// dependency.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Algorithm {
+    /// Zstandard, which offers a good balance of ratio and speed.
+    #[default]
+    Zstd,
+    /// LZ4 frames, which decompress considerably faster at a lower ratio.
+    #[cfg(feature = "lz4")]
+    Lz4,
+    /// Brotli, which compresses text especially well.
+    #[cfg(feature = "brotli")]
+    Brotli,
+    /// No compression. The data is stored as is and merely copied out.
+    Store,
+}
+
+impl Algorithm {
+    /// Returns the algorithm's name as recorded in build artifacts.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Zstd => "zstd",
+            #[cfg(feature = "lz4")]
+            Self::Lz4 => "lz4",
+            #[cfg(feature = "brotli")]
+            Self::Brotli => "brotli",
+            Self::Store => "store",
+        }
+    }
+
+    /// Returns a stable numeric identifier for the algorithm, used across FFI boundaries.
+    pub(crate) const fn id(self) -> u32 {
+        match self {
+            Self::Zstd => 0,
+            #[cfg(feature = "lz4")]
+            Self::Lz4 => 1,
+            #[cfg(feature = "brotli")]
+            Self::Brotli => 2,
+            Self::Store => 3,
+        }
+    }
+
+    /// Returns the algorithm with the given identifier, if it is supported by this build.
+    pub(crate) const fn from_id(id: u32) -> Option<Self> {
+        match id {
+            0 => Some(Self::Zstd),
+            #[cfg(feature = "lz4")]
+            1 => Some(Self::Lz4),
+            #[cfg(feature = "brotli")]
+            2 => Some(Self::Brotli),
+            3 => Some(Self::Store),
+            _ => None,
+        }
+    }
+}
+
+/// A zstd dictionary that data was compressed with.
+///
+/// Dictionaries trained by `Config::train_dictionary` are in zstd's dictionary format,
+/// while the bases of delta assets are plain contents that patches refer back to. These
+/// are always loaded as such, even if they start with the magic number of the format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dictionary<'a> {
+    /// A dictionary in zstd's format, or empty if none was used.
+    Trained(&'a [u8]),
+    /// Raw content, such as the base of a delta asset.
+    Raw(&'a [u8]),
+}
+
+impl Dictionary<'_> {
+    /// The absence of a dictionary.
+    pub const NONE: Self = Self::Trained(&[]);
+}
+
+/// Decodes `data` into a newly allocated buffer.
+///
+/// `dictionary` is the zstd dictionary the data was compressed with. It is ignored by
+/// other algorithms.
+///
+/// # Errors
+/// Returns an error if `data` is not valid compressed data for `algorithm`.
+#[cfg(feature = "std")]
+pub fn decode_all(
+    algorithm: Algorithm,
+    dictionary: Dictionary<'_>,
+    data: &[u8],
+) -> io::Result<Vec<u8>> {
+    let mut output = Vec::new();
+    decode_extend(algorithm, dictionary, data, &mut output)?;
+    Ok(output)
+}
+
+/// Decodes `data` like [`decode_all`], but appends to `output`, reusing its allocation.
+///
+/// # Errors
+/// Returns an error if `data` is not valid compressed data for `algorithm`.
+#[cfg(feature = "std")]
+pub fn decode_extend(
+    algorithm: Algorithm,
+    dictionary: Dictionary<'_>,
+    data: &[u8],
+    output: &mut Vec<u8>,
+) -> io::Result<()> {
+    match algorithm {
+        Algorithm::Store => output.extend_from_slice(data),
+        _ => {
+            decoder(algorithm, dictionary, data)?.read_to_end(output)?;
+        }
+    }
+    Ok(())
+}
+
+/// Decodes `data` into a newly allocated buffer.
+///
+/// `dictionary` is the zstd dictionary the data was compressed with.
+///
+/// # Errors
+/// Returns an error if `data` is not valid compressed data for `algorithm`, or if it
+/// was compressed against raw content, which `ruzstd` cannot load.
+#[cfg(not(feature = "std"))]
+pub fn decode_all(
+    algorithm: Algorithm,
+    dictionary: Dictionary<'_>,
+    data: &[u8],
+) -> Result<Vec<u8>, DecodeError> {
+    let mut output = Vec::new();
+    decode_extend(algorithm, dictionary, data, &mut output)?;
+    Ok(output)
+}
+
+/// Decodes `data` like [`decode_all`], but appends to `output`, reusing its allocation.
+///
+/// # Errors
+/// Returns an error if `data` is not valid compressed data for `algorithm`.
+#[cfg(not(feature = "std"))]
+pub fn decode_extend(
+    algorithm: Algorithm,
+    dictionary: Dictionary<'_>,
+    data: &[u8],
+    output: &mut Vec<u8>,
+) -> Result<(), DecodeError> {
+    use ruzstd::{
+        decoding::{Dictionary as RuzstdDictionary, FrameDecoder, StreamingDecoder},
+        io::Read as _,
+    };
+
+    match algorithm {
+        Algorithm::Store => output.extend_from_slice(data),
+        Algorithm::Zstd => {
+            let mut decoder = FrameDecoder::new();
+            match dictionary {
+                Dictionary::Trained([]) => {}
+                Dictionary::Trained(dictionary) => {
+                    let dictionary =
+                        RuzstdDictionary::decode_dict(dictionary).map_err(DecodeError::new)?;
+                    decoder.add_dict(dictionary).map_err(DecodeError::new)?;
+                }
+                Dictionary::Raw(_) => {
+                    return Err(DecodeError::new(
+                        "ruzstd cannot load raw content dictionaries",
+                    ));
+                }
+            }
+            // Unlike the reference implementation, `ruzstd` decodes a single frame at a
+            // time, while chunked assets consist of several.
+            let mut remaining = data;
+            while !remaining.is_empty() {
+                StreamingDecoder::new_with_decoder(&mut remaining, &mut decoder)
+                    .map_err(DecodeError::new)?
+                    .read_to_end(output)
+                    .map_err(DecodeError::new)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns a streaming decoder over `data`, using `dictionary` as [`decode_all`] does.
+///
+/// # Errors
+/// Returns an error if the decoder could not be created.
+#[cfg(feature = "std")]
+pub fn decoder<'a>(
+    algorithm: Algorithm,
+    dictionary: Dictionary<'a>,
+    data: &'a [u8],
+) -> io::Result<Box<dyn Read + Send + 'a>> {
+    Ok(match (algorithm, dictionary) {
+        (Algorithm::Zstd, Dictionary::Trained([])) => {
+            let mut decoder = zstd::stream::read::Decoder::with_buffer(data)?;
+            decoder.window_log_max(MAX_WINDOW_LOG)?;
+            Box::new(decoder)
+        }
+        (Algorithm::Zstd, Dictionary::Trained(dictionary)) => {
+            let mut decoder = zstd::stream::read::Decoder::with_dictionary(data, dictionary)?;
+            decoder.window_log_max(MAX_WINDOW_LOG)?;
+            Box::new(decoder)
+        }
+        (Algorithm::Zstd, Dictionary::Raw(prefix)) => Box::new(PrefixedFrames {
+            prefix,
+            remaining: data,
+            frame: None,
+        }),
+        #[cfg(feature = "lz4")]
+        (Algorithm::Lz4, _) => Box::new(lz4_flex::frame::FrameDecoder::new(data)),
+        #[cfg(feature = "brotli")]
+        (Algorithm::Brotli, _) => Box::new(brotli::Decompressor::new(data, 4096)),
+        (Algorithm::Store, _) => Box::new(data),
+    })
+}
+
+/// Decodes zstd frames that were each compressed against the raw content `prefix`.
+///
+/// zstd forgets a referenced prefix at the end of the frame it was used for, so every
+/// frame of a chunked asset is decoded with a decoder of its own.
+#[cfg(feature = "std")]
+struct PrefixedFrames<'a> {
+    prefix: &'a [u8],
+    /// The frames that have not been started yet.
+    remaining: &'a [u8],
+    /// The decoder of the current frame.
+    frame: Option<zstd::stream::read::Decoder<'a, &'a [u8]>>,
+}
+
+#[cfg(feature = "std")]
+impl Read for PrefixedFrames<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if let Some(frame) = &mut self.frame {
+                match frame.read(buf)? {
+                    0 if !buf.is_empty() => self.frame = None,
+                    read => return Ok(read),
+                }
+            }
+            if self.remaining.is_empty() {
+                return Ok(0);
+            }
+            let len = zstd::zstd_safe::find_frame_compressed_size(self.remaining)
+                .map_err(|code| io::Error::other(zstd::zstd_safe::get_error_name(code)))?;
+            let (frame, remaining) = self.remaining.split_at(len);
+            self.remaining = remaining;
+            let mut decoder =
+                zstd::stream::read::Decoder::with_ref_prefix(frame, self.prefix)?.single_frame();
+            decoder.window_log_max(MAX_WINDOW_LOG)?;
+            self.frame = Some(decoder);
+        }
+    }
+}
+
+/// Decodes `data` in chunks of at most `chunk_size` bytes, calling `keep_going` before
+/// each chunk. `dictionary` is used as [`decode_all`] does.
+///
+/// Returns `Ok(None)` as soon as `keep_going` returns `false`.
+///
+/// # Errors
+/// Returns an error if `data` is not valid compressed data for `algorithm`.
+#[cfg(feature = "std")]
+pub fn decode_chunked(
+    algorithm: Algorithm,
+    dictionary: Dictionary<'_>,
+    data: &[u8],
+    chunk_size: usize,
+    mut keep_going: impl FnMut() -> bool,
+) -> io::Result<Option<Vec<u8>>> {
+    let mut decoder = decoder(algorithm, dictionary, data)?;
+    let mut output = Vec::new();
+    loop {
+        if !keep_going() {
+            return Ok(None);
+        }
+        let read = (&mut decoder)
+            .take(chunk_size as u64)
+            .read_to_end(&mut output)?;
+        if read == 0 {
+            return Ok(Some(output));
+        }
+    }
+}
+
+/// Returns the decoded size of `data`, using `dictionary` as [`decode_all`] does.
+///
+/// For zstd, the size is read from the frame header when present. Data packed by older
+/// versions may lack it, and other algorithms don't record it, in which case the data is
+/// decoded once to measure it.
+///
+/// # Errors
+/// Returns an error if `data` is not valid compressed data for `algorithm`, or if its
+/// decoded size does not fit in memory.
+#[cfg(any(feature = "mmap", feature = "fuzzing"))]
+pub fn decoded_len(
+    algorithm: Algorithm,
+    dictionary: Dictionary<'_>,
+    data: &[u8],
+) -> io::Result<usize> {
+    let len = match algorithm {
+        Algorithm::Store => return Ok(data.len()),
+        Algorithm::Zstd => zstd::zstd_safe::get_frame_content_size(data).ok().flatten(),
+        #[allow(unreachable_patterns)]
+        _ => None,
+    };
+    let len = match len {
+        Some(len) => len,
+        None => io::copy(&mut decoder(algorithm, dictionary, data)?, &mut io::sink())?,
+    };
+    usize::try_from(len).map_err(|_| io::Error::other("decoded size does not fit in memory"))
+}
+
+/// Decodes `data` into `output`, returning the number of bytes written. `dictionary` is
+/// used as [`decode_all`] does.
+///
+/// # Errors
+/// Returns an error if `data` is not valid compressed data for `algorithm` or does not
+/// fit into `output`.
+#[cfg(feature = "std")]
+pub fn decode_into(
+    algorithm: Algorithm,
+    dictionary: Dictionary<'_>,
+    data: &[u8],
+    output: &mut [u8],
+) -> io::Result<usize> {
+    if let (Algorithm::Zstd, Dictionary::Trained(dictionary)) = (algorithm, dictionary) {
+        let mut decompressor = zstd::bulk::Decompressor::with_dictionary(dictionary)?;
+        decompressor.set_parameter(zstd::zstd_safe::DParameter::WindowLogMax(MAX_WINDOW_LOG))?;
+        return decompressor.decompress_to_buffer(data, output);
+    }
+
+    let mut decoder = decoder(algorithm, dictionary, data)?;
+    let mut written = 0;
+    while written < output.len() {
+        match decoder.read(&mut output[written..])? {
+            0 => return Ok(written),
+            read => written += read,
+        }
+    }
+    if decoder.read(&mut [0])? != 0 {
+        return Err(io::Error::other(
+            "decoded data does not fit into the output",
+        ));
+    }
+    Ok(written)
+}
@@ -0,0 +1,75 @@
+//! The [`CompressedAsset`] returned by
+//! [`include_packed_compressed!`](crate::include_packed_compressed).
+use super::{Algorithm, AssetInfo};
+
+/// The compressed data of a packed asset exactly as it is embedded in the binary, along
+/// with the asset's metadata.
+///
+/// Returned by [`include_packed_compressed!`](crate::include_packed_compressed). Web
+/// servers can send [`data`](Self::data) as is, with the `Content-Encoding` header set
+/// to [`content_encoding`](Self::content_encoding), and leave decompression to the
+/// client. [`info`](Self::info) provides the original size, modification time, and MIME
+/// type for the remaining headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompressedAsset {
+    data: &'static [u8],
+    info: AssetInfo,
+}
+
+impl CompressedAsset {
+    /// Creates a handle to compressed data.
+    ///
+    /// This is an implementation detail of the macros and is not intended to be called
+    /// directly by user code.
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(data: &'static [u8], info: AssetInfo) -> Self {
+        Self { data, info }
+    }
+
+    /// Returns the compressed data, e.g. a zstd frame for assets packed with
+    /// [`Algorithm::Zstd`], or the contents themselves for [`Algorithm::Store`].
+    #[must_use]
+    pub const fn data(&self) -> &'static [u8] {
+        self.data
+    }
+
+    /// Returns the asset's metadata.
+    #[must_use]
+    pub const fn info(&self) -> &AssetInfo {
+        &self.info
+    }
+
+    /// Returns the asset's original, decompressed size in bytes.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.info.len()
+    }
+
+    /// Returns `true` if the asset is empty.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.info.is_empty()
+    }
+
+    /// Returns the algorithm the data was compressed with.
+    #[must_use]
+    pub const fn algorithm(&self) -> Algorithm {
+        self.info.algorithm()
+    }
+
+    /// Returns the HTTP content coding of the data: `"zstd"` for zstd and `"br"` for
+    /// Brotli.
+    ///
+    /// Returns `None` for assets stored uncompressed, whose data can be sent without the
+    /// header, and for LZ4, which has no content coding and must be decompressed first.
+    #[must_use]
+    pub const fn content_encoding(&self) -> Option<&'static str> {
+        match self.algorithm() {
+            Algorithm::Zstd => Some("zstd"),
+            #[cfg(feature = "brotli")]
+            Algorithm::Brotli => Some("br"),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Encryption of assets at rest.
+use std::sync::RwLock;
+
+use chacha20poly1305::{
+    ChaCha20Poly1305, Key, Nonce,
+    aead::{Aead, KeyInit},
+};
+
+use super::Error;
+
+/// The length of the nonce stored in front of each encrypted asset.
+const NONCE_LEN: usize = 12;
+
+/// The BLAKE3 context the cipher's key is derived from the provisioned key with.
+const CIPHER_KEY_CONTEXT: &str = "include_packed asset encryption key";
+
+/// The BLAKE3 context the key for deriving nonces is derived from the provisioned key
+/// with.
+#[cfg(feature = "build")]
+const NONCE_KEY_CONTEXT: &str = "include_packed asset nonce key";
+
+/// The key set with [`set_key`] or [`unlock`].
+static KEY: RwLock<Option<[u8; 32]>> = RwLock::new(None);
+
+/// A cipher that assets can be encrypted with.
+///
+/// The cipher is chosen with `Config::encrypt` and recorded for every asset, so the
+/// macros automatically decrypt them with the key set by [`unlock`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Cipher {
+    /// ChaCha20-Poly1305, which authenticates the data, so a wrong key is detected
+    /// instead of producing garbage.
+    ChaCha20Poly1305,
+}
+
+impl Cipher {
+    /// Returns the name under which the build script records the cipher.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::ChaCha20Poly1305 => "chacha20poly1305",
+        }
+    }
+
+    /// Returns the stable identifier used for the cipher across FFI boundaries.
+    pub(crate) const fn id(self) -> u32 {
+        match self {
+            Self::ChaCha20Poly1305 => 1,
+        }
+    }
+
+    /// Returns the cipher with the given identifier, if it is known.
+    pub(crate) const fn from_id(id: u32) -> Option<Self> {
+        match id {
+            1 => Some(Self::ChaCha20Poly1305),
+            _ => None,
+        }
+    }
+}
+
+/// Sets the 256-bit key that encrypted assets are decrypted with.
+///
+/// This must match the key the build script read from `INCLUDE_PACKED_ENCRYPTION_KEY`,
+/// and must be called before any encrypted asset is decompressed, e.g. with a key
+/// fetched from a license server at startup. Setting it again replaces the key.
+pub fn set_key(key: [u8; 32]) {
+    *KEY.write().unwrap_or_else(|err| err.into_inner()) = Some(key);
+}
+
+/// Sets the key that encrypted assets are decrypted with, like [`set_key`], from key
+/// material provisioned at runtime, e.g. read from a secrets store.
+///
+/// Call this before any encrypted asset is decompressed. Until then, decompressing one
+/// fails with [`Error::MissingKey`], and afterwards with [`Error::WrongKey`] if `key`
+/// doesn't match the key the build script read from `INCLUDE_PACKED_ENCRYPTION_KEY` or
+/// `INCLUDE_PACKED_KEY_FILE`. Calling it again replaces the key.
+///
+/// # Errors
+/// Returns [`Error::InvalidKeyLength`] if `key` is not 32 bytes long, leaving the
+/// current key in place.
+pub fn unlock(key: &[u8]) -> Result<(), Error> {
+    let key = <[u8; 32]>::try_from(key).map_err(|_| Error::InvalidKeyLength { len: key.len() })?;
+    set_key(key);
+    Ok(())
+}
+
+/// Decrypts the data of the asset at `path` with the key set by [`set_key`].
+///
+/// # Errors
+/// Returns [`Error::MissingKey`] if no key was set, and [`Error::WrongKey`] if the data
+/// could not be decrypted with it.
+pub(crate) fn decrypt(cipher: Cipher, path: &'static str, data: &[u8]) -> Result<Vec<u8>, Error> {
+    let key = KEY
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .ok_or(Error::MissingKey { path })?;
+    open(cipher, &key, data).ok_or(Error::WrongKey { path })
+}
+
+/// Decrypts `data` with `key`, returning `None` if it was encrypted with another key
+/// or was tampered with.
+pub(crate) fn open(cipher: Cipher, key: &[u8; 32], data: &[u8]) -> Option<Vec<u8>> {
+    match cipher {
+        Cipher::ChaCha20Poly1305 => {
+            let (nonce, ciphertext) = data.split_at_checked(NONCE_LEN)?;
+            let key = blake3::derive_key(CIPHER_KEY_CONTEXT, key);
+            ChaCha20Poly1305::new(Key::from_slice(&key))
+                .decrypt(Nonce::from_slice(nonce), ciphertext)
+                .ok()
+        }
+    }
+}
+
+/// Encrypts `data` with `key`, prefixing the result with the nonce.
+///
+/// The nonce is derived from the data, so builds stay reproducible and a nonce is only
+/// ever reused for identical data. Identical data thus encrypts to identical
+/// ciphertext, which reveals which assets are equal. The nonce and the cipher use
+/// separate subkeys of `key`.
+#[cfg(feature = "build")]
+pub(crate) fn seal(cipher: Cipher, key: &[u8; 32], data: &[u8]) -> Vec<u8> {
+    match cipher {
+        Cipher::ChaCha20Poly1305 => {
+            let nonce_key = blake3::derive_key(NONCE_KEY_CONTEXT, key);
+            let hash = blake3::keyed_hash(&nonce_key, data);
+            let nonce = Nonce::from_slice(&hash.as_bytes()[..NONCE_LEN]);
+            let key = blake3::derive_key(CIPHER_KEY_CONTEXT, key);
+            let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&key))
+                .encrypt(nonce, data)
+                .expect("encrypting into a Vec cannot fail");
+            [nonce.as_slice(), &ciphertext].concat()
+        }
+    }
+}
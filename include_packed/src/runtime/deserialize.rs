@@ -0,0 +1,85 @@
+//! Deserializing packed assets with serde.
+use alloc::boxed::Box;
+
+use serde::de::DeserializeOwned;
+
+use super::{Error, PackedAsset};
+
+/// A serialization format that assets can be deserialized from.
+///
+/// Each format is enabled by the Cargo feature of the same name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Format {
+    /// JSON, decoded with `serde_json`.
+    #[cfg(feature = "json")]
+    Json,
+    /// The `postcard` wire format.
+    #[cfg(feature = "postcard")]
+    Postcard,
+    /// The `bincode` format with its standard configuration.
+    #[cfg(feature = "bincode")]
+    Bincode,
+}
+
+impl Format {
+    /// Returns the format's name, as accepted by
+    /// [`include_packed_as!`](crate::include_packed_as).
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "json")]
+            Self::Json => "json",
+            #[cfg(feature = "postcard")]
+            Self::Postcard => "postcard",
+            #[cfg(feature = "bincode")]
+            Self::Bincode => "bincode",
+        }
+    }
+}
+
+impl PackedAsset {
+    /// Decompresses the asset and deserializes it from `format`.
+    ///
+    /// This is what [`include_packed_as!`](crate::include_packed_as) expands to.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`decompress`](Self::decompress), and if the
+    /// decompressed data is not a valid `T` in `format`.
+    #[track_caller]
+    #[must_use]
+    pub fn deserialize<T: DeserializeOwned>(&self, format: Format) -> T {
+        self.try_deserialize(format)
+            .unwrap_or_else(|err| panic!("include_packed: {err}"))
+    }
+
+    /// Decompresses the asset and deserializes it from `format` like
+    /// [`deserialize`](Self::deserialize), but returns an error instead of panicking.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Deserialize`] if the decompressed data is not a valid `T` in
+    /// `format`, and the errors of [`try_decompress`](Self::try_decompress) if it could
+    /// not be decompressed.
+    pub fn try_deserialize<T: DeserializeOwned>(&self, format: Format) -> Result<T, Error> {
+        let data = self.try_decompress()?;
+        let error = |source: Box<dyn core::error::Error + Send + Sync>| Error::Deserialize {
+            path: self.path(),
+            format,
+            source,
+        };
+        match format {
+            #[cfg(feature = "json")]
+            Format::Json => serde_json::from_slice(&data).map_err(|err| error(err.into())),
+            #[cfg(feature = "postcard")]
+            Format::Postcard => postcard::from_bytes(&data).map_err(|err| error(err.into())),
+            #[cfg(feature = "bincode")]
+            Format::Bincode => {
+                bincode::serde::decode_from_slice(&data, bincode::config::standard())
+                    .map(|(value, _)| value)
+                    .map_err(|err| error(err.into()))
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! The [`PackedDir`] lookup structure returned by
+//! [`include_packed_dir!`](crate::include_packed_dir).
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::PackedAsset;
+
+/// A directory of assets that were packed at compile time.
+///
+/// The [`include_packed_dir!`](crate::include_packed_dir) macro expands to this
+/// lightweight, `Copy` structure, which maps each file's path relative to the
+/// directory, using `/` as separator, to its [`PackedAsset`] handle. Lookups are
+/// compiled into a `match`, and nothing is decompressed until an asset is requested.
+/// The structure can be stored in a `static`.
+#[derive(Clone, Copy)]
+pub struct PackedDir {
+    paths: &'static [&'static str],
+    lookup: fn(&str) -> Option<PackedAsset>,
+}
+
+impl PackedDir {
+    /// Creates a directory from its sorted relative paths and a lookup function.
+    ///
+    /// This is what [`include_packed_dir!`](crate::include_packed_dir) expands to.
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(
+        paths: &'static [&'static str],
+        lookup: fn(&str) -> Option<PackedAsset>,
+    ) -> Self {
+        Self { paths, lookup }
+    }
+
+    /// Returns the asset at `path`, relative to the directory.
+    #[must_use]
+    pub fn get(&self, path: &str) -> Option<PackedAsset> {
+        (self.lookup)(path)
+    }
+
+    /// Decompresses the asset at `path`, relative to the directory.
+    ///
+    /// # Panics
+    ///
+    /// Panics with a message naming the asset if the embedded data is not valid
+    /// zstd-compressed data, which indicates a bug in `include_packed` itself.
+    #[track_caller]
+    #[must_use]
+    pub fn decompress(&self, path: &str) -> Option<Vec<u8>> {
+        self.get(path).map(|asset| asset.decompress())
+    }
+
+    /// Returns `true` if the directory contains an asset at `path`.
+    #[must_use]
+    pub fn contains(&self, path: &str) -> bool {
+        self.paths.binary_search(&path).is_ok()
+    }
+
+    /// Returns the relative paths of all assets in the directory, in sorted order.
+    #[must_use]
+    pub const fn paths(&self) -> &'static [&'static str] {
+        self.paths
+    }
+
+    /// Returns the number of assets in the directory.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Returns `true` if the directory contains no assets.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Returns an iterator over the relative paths and handles of all assets, in sorted
+    /// order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, PackedAsset)> + '_ {
+        self.paths
+            .iter()
+            .filter_map(|&path| Some((path, self.get(path)?)))
+    }
+}
+
+impl fmt::Debug for PackedDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PackedDir")
+            .field("paths", &self.paths)
+            .finish_non_exhaustive()
+    }
+}
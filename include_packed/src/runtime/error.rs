@@ -0,0 +1,211 @@
+//! The error type of the non-panicking runtime APIs.
+use alloc::{boxed::Box, string::String};
+use core::{fmt, str::Utf8Error};
+
+/// An error returned when an asset could not be decompressed.
+///
+/// Data packed by this crate always decodes, so the panicking APIs such as
+/// [`PackedAsset::decompress`](super::PackedAsset::decompress) treat failures as bugs.
+/// Long-running services that would rather degrade gracefully on a damaged binary or
+/// hand-built data can use the `try_` APIs and
+/// [`include_packed_try!`](crate::include_packed_try), which return this error instead.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The embedded data is not valid compressed data.
+    Corrupt {
+        /// The logical path of the asset, or an empty string if it has none.
+        path: &'static str,
+        /// The error reported by the decoder.
+        source: Box<dyn core::error::Error + Send + Sync>,
+    },
+    /// A text asset did not decompress to valid UTF-8.
+    InvalidUtf8 {
+        /// The logical path of the asset, or an empty string if it has none.
+        path: &'static str,
+        /// Where the first invalid byte was found.
+        source: Utf8Error,
+    },
+    /// A decompressed asset does not match the checksum recorded by `Config::verify`.
+    ///
+    /// The embedded data was corrupted, e.g. by a faulty linker, or tampered with.
+    ChecksumMismatch {
+        /// The logical path of the asset, or an empty string if it has none.
+        path: &'static str,
+    },
+    /// A decompressed asset does not carry a valid signature by the key set with
+    /// `set_trusted_key`.
+    ///
+    /// The asset was signed with another key, or the embedded data, its checksum, or
+    /// its signature was tampered with.
+    SignatureMismatch {
+        /// The logical path of the asset, or an empty string if it has none.
+        path: &'static str,
+    },
+    /// A signed asset was decompressed before a trusted key was set with
+    /// `set_trusted_key`.
+    UntrustedSignature {
+        /// The logical path of the asset, or an empty string if it has none.
+        path: &'static str,
+    },
+    /// An encrypted asset was decompressed before a key was set with `unlock` or
+    /// `set_key`.
+    MissingKey {
+        /// The logical path of the asset, or an empty string if it has none.
+        path: &'static str,
+    },
+    /// An encrypted asset could not be decrypted with the key set with `unlock` or
+    /// `set_key`.
+    ///
+    /// The key is wrong, or the embedded data was tampered with.
+    WrongKey {
+        /// The logical path of the asset, or an empty string if it has none.
+        path: &'static str,
+    },
+    /// A key passed to `unlock` does not have the 32 bytes of a 256-bit key.
+    ///
+    /// This error concerns no asset, so its path is empty.
+    InvalidKeyLength {
+        /// The length of the key that was passed.
+        len: usize,
+    },
+    /// A decompressed asset could not be deserialized.
+    #[cfg(any(feature = "json", feature = "postcard", feature = "bincode"))]
+    Deserialize {
+        /// The logical path of the asset, or an empty string if it has none.
+        path: &'static str,
+        /// The format the asset was deserialized from.
+        format: super::Format,
+        /// The error reported by the deserializer.
+        source: Box<dyn core::error::Error + Send + Sync>,
+    },
+    /// An asset is not a valid rkyv archive of the requested type.
+    #[cfg(feature = "rkyv")]
+    InvalidArchive {
+        /// The logical path of the asset, or an empty string if it has none.
+        path: &'static str,
+        /// The error reported by the validator.
+        source: Box<dyn core::error::Error + Send + Sync>,
+    },
+    /// A decompressed asset does not fit into the buffer it was decompressed into.
+    BufferTooSmall {
+        /// The logical path of the asset, or an empty string if it has none.
+        path: &'static str,
+        /// The size of the buffer.
+        capacity: usize,
+    },
+    /// An asset served over HTTP could not be downloaded.
+    Fetch {
+        /// The logical path of the asset, or an empty string if it has none.
+        path: &'static str,
+        /// A description of the failure reported by the browser.
+        message: String,
+    },
+}
+
+impl Error {
+    /// Returns the logical path of the asset that failed to decompress, or an empty
+    /// string if it has none.
+    #[must_use]
+    pub const fn path(&self) -> &'static str {
+        match self {
+            Self::Corrupt { path, .. }
+            | Self::InvalidUtf8 { path, .. }
+            | Self::ChecksumMismatch { path }
+            | Self::SignatureMismatch { path }
+            | Self::UntrustedSignature { path }
+            | Self::MissingKey { path }
+            | Self::WrongKey { path }
+            | Self::BufferTooSmall { path, .. }
+            | Self::Fetch { path, .. } => path,
+            Self::InvalidKeyLength { .. } => "",
+            #[cfg(any(feature = "json", feature = "postcard", feature = "bincode"))]
+            Self::Deserialize { path, .. } => path,
+            #[cfg(feature = "rkyv")]
+            Self::InvalidArchive { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = match self.path() {
+            "" => "<unnamed>",
+            path => path,
+        };
+        match self {
+            Self::Corrupt { source, .. } => {
+                write!(f, "failed to decompress asset '{path}': {source}")
+            }
+            Self::InvalidUtf8 { source, .. } => {
+                write!(f, "asset '{path}' is not valid UTF-8: {source}")
+            }
+            Self::ChecksumMismatch { .. } => {
+                write!(
+                    f,
+                    "asset '{path}' does not match the checksum recorded at build time"
+                )
+            }
+            Self::SignatureMismatch { .. } => {
+                write!(f, "asset '{path}' does not match its signature")
+            }
+            Self::UntrustedSignature { .. } => {
+                write!(f, "asset '{path}' is signed, but no trusted key was set")
+            }
+            Self::MissingKey { .. } => {
+                write!(f, "asset '{path}' is encrypted, but no key was set")
+            }
+            Self::WrongKey { .. } => {
+                write!(
+                    f,
+                    "failed to decrypt asset '{path}' with the key that was set"
+                )
+            }
+            Self::InvalidKeyLength { len } => {
+                write!(f, "expected a 32-byte key, but got {len} bytes")
+            }
+            Self::BufferTooSmall { capacity, .. } => {
+                write!(
+                    f,
+                    "asset '{path}' does not fit into a buffer of {capacity} bytes"
+                )
+            }
+            Self::Fetch { message, .. } => {
+                write!(f, "failed to fetch asset '{path}': {message}")
+            }
+            #[cfg(any(feature = "json", feature = "postcard", feature = "bincode"))]
+            Self::Deserialize { format, source, .. } => {
+                write!(
+                    f,
+                    "failed to deserialize asset '{path}' from {}: {source}",
+                    format.name()
+                )
+            }
+            #[cfg(feature = "rkyv")]
+            Self::InvalidArchive { source, .. } => {
+                write!(f, "asset '{path}' is not a valid archive: {source}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Corrupt { source, .. } => Some(&**source),
+            #[cfg(any(feature = "json", feature = "postcard", feature = "bincode"))]
+            Self::Deserialize { source, .. } => Some(&**source),
+            #[cfg(feature = "rkyv")]
+            Self::InvalidArchive { source, .. } => Some(&**source),
+            Self::InvalidUtf8 { source, .. } => Some(source),
+            Self::ChecksumMismatch { .. }
+            | Self::SignatureMismatch { .. }
+            | Self::UntrustedSignature { .. }
+            | Self::MissingKey { .. }
+            | Self::WrongKey { .. }
+            | Self::InvalidKeyLength { .. }
+            | Self::BufferTooSmall { .. }
+            | Self::Fetch { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! Downloading assets that the build script left out of the `.wasm` binary.
+use alloc::{format, string::String, vec::Vec};
+
+use wasm_bindgen::{JsCast, JsValue, prelude::wasm_bindgen};
+use wasm_bindgen_futures::JsFuture;
+
+use super::{Error, PackedAsset, codec};
+
+#[wasm_bindgen]
+extern "C" {
+    /// The global `fetch` function, available both in windows and in workers.
+    #[wasm_bindgen(js_name = fetch)]
+    fn fetch_with_str(input: &str) -> js_sys::Promise;
+}
+
+impl PackedAsset {
+    /// Downloads the asset's compressed data from `url` and decompresses it.
+    ///
+    /// This is what [`include_packed_fetch!`](crate::include_packed_fetch) expands to
+    /// for assets packed with `Config::wasm_fetch`, whose handles carry no data. The
+    /// downloaded data is decompressed with the handle's algorithm and dictionary.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Fetch`] if the request failed or the server did not respond with
+    /// a success status, and the errors of [`PackedAsset::try_decompress`] if the
+    /// response could not be decoded.
+    pub async fn fetch(&self, url: &str) -> Result<Vec<u8>, Error> {
+        let fetch_error = |message: String| Error::Fetch {
+            path: self.path(),
+            message,
+        };
+
+        let response: web_sys::Response = JsFuture::from(fetch_with_str(url))
+            .await
+            .map_err(|err| fetch_error(describe(&err)))?
+            .unchecked_into();
+        if !response.ok() {
+            return Err(fetch_error(format!(
+                "'{url}' responded with status {}",
+                response.status()
+            )));
+        }
+        let buffer = response
+            .array_buffer()
+            .map_err(|err| fetch_error(describe(&err)))?;
+        let buffer = JsFuture::from(buffer)
+            .await
+            .map_err(|err| fetch_error(describe(&err)))?;
+        let compressed = js_sys::Uint8Array::new(&buffer).to_vec();
+        #[cfg(feature = "encryption")]
+        let compressed = match self.cipher() {
+            Some(cipher) => super::crypto::decrypt(cipher, self.path(), &compressed)?,
+            None => compressed,
+        };
+
+        let dictionary = self.try_decoding_dictionary()?;
+        let data = codec::decode_all(self.algorithm(), dictionary.get(), &compressed).map_err(
+            |source| Error::Corrupt {
+                path: self.path(),
+                source: source.into(),
+            },
+        )?;
+        self.verify_checksum(&data)?;
+        Ok(data)
+    }
+}
+
+/// Returns a readable description of a JavaScript exception.
+fn describe(err: &JsValue) -> String {
+    match err.dyn_ref::<js_sys::Error>() {
+        Some(err) => err.message().into(),
+        None => format!("{err:?}"),
+    }
+}
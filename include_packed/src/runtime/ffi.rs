@@ -0,0 +1,128 @@
+//! An FFI-safe form of [`PackedAsset`] for handing assets across dynamic library
+//! boundaries, e.g. from a `cdylib` plugin loaded with `libloading` to its host.
+use super::{Algorithm, PackedAsset};
+
+/// An FFI-safe description of a [`PackedAsset`].
+///
+/// Plugins built as `cdylib`s carry their own packed assets, but Rust types have no
+/// stable layout across separately compiled libraries. A plugin can instead export a
+/// function returning this `#[repr(C)]` struct, and the host turns it back into a
+/// [`PackedAsset`] with [`PackedAsset::from_raw`]:
+///
+/// ```no_run
+/// use include_packed::{PackedAsset, RawPackedAsset, include_packed_asset};
+/// # // The asset only exists in crates whose build script packed it.
+/// # macro_rules! include_packed_asset {
+/// #     ($path:literal) => {
+/// #         include_packed::PackedAsset::from_compressed(&[])
+/// #     };
+/// # }
+///
+/// // In the plugin (crate-type = ["cdylib"]), which packs its own assets in build.rs.
+/// #[unsafe(no_mangle)]
+/// pub extern "C" fn plugin_logo() -> RawPackedAsset {
+///     include_packed_asset!("assets/logo.png").into_raw()
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// // In the host.
+/// # // `libloading` is not a dependency of this crate.
+/// # #[cfg(any())]
+/// # {
+/// let library = unsafe { libloading::Library::new(plugin_path)? };
+/// let logo: libloading::Symbol<extern "C" fn() -> RawPackedAsset> =
+///     unsafe { library.get(b"plugin_logo")? };
+/// # }
+/// # let logo = plugin_logo;
+/// let logo = unsafe { PackedAsset::from_raw(logo()) };
+/// let bytes = logo.decompress();
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Build provenance and checksums are not carried across the boundary.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct RawPackedAsset {
+    /// Pointer to the compressed data.
+    pub compressed: *const u8,
+    /// Length of the compressed data in bytes.
+    pub compressed_len: usize,
+    /// Pointer to the UTF-8 logical path.
+    pub path: *const u8,
+    /// Length of the logical path in bytes.
+    pub path_len: usize,
+    /// Identifier of the [`Algorithm`] the data was compressed with.
+    pub algorithm: u32,
+    /// Pointer to the zstd dictionary the data was compressed with.
+    pub dictionary: *const u8,
+    /// Length of the dictionary in bytes, or zero if none was used.
+    pub dictionary_len: usize,
+    /// Identifier of the cipher the data was encrypted with, or zero if it is not
+    /// encrypted.
+    pub cipher: u32,
+}
+
+impl PackedAsset {
+    /// Converts the handle into its FFI-safe form.
+    #[must_use]
+    pub const fn into_raw(self) -> RawPackedAsset {
+        RawPackedAsset {
+            compressed: self.compressed().as_ptr(),
+            compressed_len: self.compressed().len(),
+            path: self.path().as_ptr(),
+            path_len: self.path().len(),
+            algorithm: self.algorithm().id(),
+            dictionary: self.dictionary().as_ptr(),
+            dictionary_len: self.dictionary().len(),
+            #[cfg(feature = "encryption")]
+            cipher: match self.cipher() {
+                Some(cipher) => cipher.id(),
+                None => 0,
+            },
+            #[cfg(not(feature = "encryption"))]
+            cipher: 0,
+        }
+    }
+
+    /// Reconstructs a handle from its FFI-safe form.
+    ///
+    /// # Safety
+    ///
+    /// `raw` must have been produced by [`PackedAsset::into_raw`], and the library that
+    /// produced it must stay loaded for as long as the returned handle, or anything
+    /// borrowed from it, is in use. Keeping the `libloading::Library` alive for the rest
+    /// of the program, or leaking it, satisfies this.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the asset was compressed with an algorithm, or encrypted with a cipher,
+    /// whose Cargo feature is not enabled in this build.
+    #[must_use]
+    pub const unsafe fn from_raw(raw: RawPackedAsset) -> Self {
+        // SAFETY: The caller guarantees that the pointers came from `into_raw` on
+        // `'static` data that outlives the returned handle.
+        let (compressed, path, dictionary) = unsafe {
+            (
+                core::slice::from_raw_parts(raw.compressed, raw.compressed_len),
+                core::str::from_utf8_unchecked(core::slice::from_raw_parts(raw.path, raw.path_len)),
+                core::slice::from_raw_parts(raw.dictionary, raw.dictionary_len),
+            )
+        };
+        let Some(algorithm) = Algorithm::from_id(raw.algorithm) else {
+            panic!("include_packed: asset uses a compression algorithm that is not enabled");
+        };
+        let asset = Self::from_compressed(compressed)
+            .with_path(path)
+            .with_algorithm(algorithm)
+            .with_dictionary(dictionary);
+        if raw.cipher == 0 {
+            return asset;
+        }
+        #[cfg(feature = "encryption")]
+        if let Some(cipher) = super::crypto::Cipher::from_id(raw.cipher) {
+            return asset.with_cipher(cipher);
+        }
+        panic!("include_packed: asset uses a cipher that is not enabled");
+    }
+}
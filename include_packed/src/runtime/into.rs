@@ -0,0 +1,162 @@
+//! Decompressing packed assets into caller-provided buffers and writers.
+use alloc::vec::Vec;
+
+use super::{Error, PackedAsset, codec};
+
+/// A buffer that assets can be decompressed into, reusing its memory across calls.
+///
+/// [`PackedAsset::decompress_into`] and
+/// [`include_packed_into!`](crate::include_packed_into) accept any of these. A `Vec<u8>`
+/// is overwritten with the decompressed data, reusing its allocation, while slices and
+/// arrays receive the data at their start and must be large enough to hold it.
+pub trait DecompressInto {
+    /// Decompresses `asset` into `self`, returning the size of the decompressed data.
+    ///
+    /// The contents of `self` are unspecified if this returns an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`PackedAsset::try_decompress`], and
+    /// [`Error::BufferTooSmall`] if the data does not fit into `self`.
+    fn decompress_from(&mut self, asset: &PackedAsset) -> Result<usize, Error>;
+}
+
+impl DecompressInto for Vec<u8> {
+    fn decompress_from(&mut self, asset: &PackedAsset) -> Result<usize, Error> {
+        self.clear();
+        if let Some(data) = asset.read_passthrough() {
+            self.extend_from_slice(&data);
+        } else {
+            codec::decode_extend(
+                asset.algorithm(),
+                asset.try_decoding_dictionary()?.get(),
+                &asset.try_decrypted()?,
+                self,
+            )
+            .map_err(|err| asset.corrupt_error(err))?;
+        }
+        asset.verify_checksum(self)?;
+        Ok(self.len())
+    }
+}
+
+impl DecompressInto for [u8] {
+    fn decompress_from(&mut self, asset: &PackedAsset) -> Result<usize, Error> {
+        let too_small = Error::BufferTooSmall {
+            path: asset.path(),
+            capacity: self.len(),
+        };
+        let len = if let Some(data) = asset.read_passthrough() {
+            self.get_mut(..data.len())
+                .ok_or(too_small)?
+                .copy_from_slice(&data);
+            data.len()
+        } else {
+            decode_into_slice(asset, self)?
+        };
+        asset.verify_checksum(&self[..len])?;
+        Ok(len)
+    }
+}
+
+impl<const N: usize> DecompressInto for [u8; N] {
+    fn decompress_from(&mut self, asset: &PackedAsset) -> Result<usize, Error> {
+        self.as_mut_slice().decompress_from(asset)
+    }
+}
+
+/// Decodes `asset` into `output` without allocating for the decompressed data.
+#[cfg(feature = "std")]
+fn decode_into_slice(asset: &PackedAsset, output: &mut [u8]) -> Result<usize, Error> {
+    use std::io;
+
+    let (algorithm, dictionary) = (asset.algorithm(), asset.try_decoding_dictionary()?);
+    let data = asset.try_decrypted()?;
+    codec::decode_into(algorithm, dictionary.get(), &data, output).map_err(|err| {
+        // The decoders don't tell data that doesn't fit apart from corrupt data, so
+        // failures are measured again.
+        let len = codec::decoder(algorithm, dictionary.get(), &data)
+            .and_then(|mut decoder| io::copy(&mut decoder, &mut io::sink()));
+        match len {
+            Ok(len) if len > output.len() as u64 => Error::BufferTooSmall {
+                path: asset.path(),
+                capacity: output.len(),
+            },
+            _ => asset.corrupt_error(err),
+        }
+    })
+}
+
+/// Decodes `asset` into `output`. Without `std`, the data is decoded into a temporary
+/// buffer first.
+#[cfg(not(feature = "std"))]
+fn decode_into_slice(asset: &PackedAsset, output: &mut [u8]) -> Result<usize, Error> {
+    let data = codec::decode_all(
+        asset.algorithm(),
+        asset.try_decoding_dictionary()?.get(),
+        &asset.try_decrypted()?,
+    )
+    .map_err(|err| asset.corrupt_error(err))?;
+    let too_small = Error::BufferTooSmall {
+        path: asset.path(),
+        capacity: output.len(),
+    };
+    output
+        .get_mut(..data.len())
+        .ok_or(too_small)?
+        .copy_from_slice(&data);
+    Ok(data.len())
+}
+
+impl PackedAsset {
+    /// Decompresses the asset into `target`, such as a reused `Vec<u8>` or a slice,
+    /// instead of allocating a new buffer, and returns the size of the decompressed data.
+    ///
+    /// This is what [`include_packed_into!`](crate::include_packed_into) expands to.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`decompress`](Self::decompress), and if the
+    /// data does not fit into `target`.
+    #[track_caller]
+    pub fn decompress_into<T: DecompressInto + ?Sized>(&self, target: &mut T) -> usize {
+        self.try_decompress_into(target)
+            .unwrap_or_else(|err| panic!("include_packed: {err}"))
+    }
+
+    /// Decompresses the asset into `target` like
+    /// [`decompress_into`](Self::decompress_into), but returns an error instead of
+    /// panicking.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`try_decompress`](Self::try_decompress), and
+    /// [`Error::BufferTooSmall`] if the data does not fit into `target`.
+    pub fn try_decompress_into<T: DecompressInto + ?Sized>(
+        &self,
+        target: &mut T,
+    ) -> Result<usize, Error> {
+        target.decompress_from(self)
+    }
+
+    /// Decompresses the asset into `writer` as it is decoded, returning the number of
+    /// bytes written.
+    ///
+    /// The data is streamed through the asset's [`reader`](Self::reader), so a checksum
+    /// mismatch is only reported after the data was written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails, and one of kind
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData) if the asset does not match its
+    /// checksum.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`reader`](Self::reader).
+    #[cfg(feature = "std")]
+    #[track_caller]
+    pub fn decompress_to_writer(&self, mut writer: impl std::io::Write) -> std::io::Result<u64> {
+        std::io::copy(&mut self.reader(), &mut writer)
+    }
+}
@@ -0,0 +1,95 @@
+//! Metadata about the assets packed by a build script.
+use super::Algorithm;
+
+/// Describes an asset packed by the build script.
+///
+/// [`include_packed_info!`](crate::include_packed_info) expands to this for a single
+/// asset, e.g. to fill in HTTP headers without recomputing anything at runtime, and
+/// [`include_packed_manifest!`](crate::include_packed_manifest) to a slice of these for
+/// every asset, which is handy for runtime asset pickers or for logging what a binary
+/// embeds without maintaining a list by hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AssetInfo {
+    path: &'static str,
+    len: usize,
+    compressed_len: usize,
+    algorithm: Algorithm,
+    modified: Option<u64>,
+    mime_type: Option<&'static str>,
+}
+
+impl AssetInfo {
+    /// Creates an asset description.
+    ///
+    /// This is an implementation detail of the macros and is not intended to be called
+    /// directly by user code.
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(
+        path: &'static str,
+        len: usize,
+        compressed_len: usize,
+        algorithm: Algorithm,
+        modified: Option<u64>,
+        mime_type: Option<&'static str>,
+    ) -> Self {
+        Self {
+            path,
+            len,
+            compressed_len,
+            algorithm,
+            modified,
+            mime_type,
+        }
+    }
+
+    /// Returns the asset's logical path, i.e. the path the macros accept for it.
+    ///
+    /// This is the path passed to `Config::new` joined with the asset's path below it,
+    /// using `/` as separator.
+    #[must_use]
+    pub const fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// Returns the asset's original, decompressed size in bytes.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the asset is empty.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the size of the asset's compressed data in bytes.
+    #[must_use]
+    pub const fn compressed_len(&self) -> usize {
+        self.compressed_len
+    }
+
+    /// Returns the algorithm the asset was compressed with.
+    #[must_use]
+    pub const fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Returns when the asset's source file was last modified, in seconds since the
+    /// Unix epoch, if the file system recorded it.
+    ///
+    /// Like build provenance, this is capped at `SOURCE_DATE_EPOCH` for reproducible
+    /// builds.
+    #[must_use]
+    pub const fn modified(&self) -> Option<u64> {
+        self.modified
+    }
+
+    /// Returns the MIME type guessed from the asset's file extension, such as
+    /// `"text/html"`, or `None` if the extension is unknown.
+    #[must_use]
+    pub const fn mime_type(&self) -> Option<&'static str> {
+        self.mime_type
+    }
+}
@@ -0,0 +1,156 @@
+//! Decompression into anonymous memory maps.
+use std::{fmt, io, ops::Deref};
+
+use memmap2::{Mmap, MmapMut};
+
+use super::{PackedAsset, codec};
+
+/// A builder for decompressing a [`PackedAsset`] into an anonymous memory map.
+///
+/// Decompressing into a `Vec<u8>` grows the buffer as data is produced, which for very
+/// large assets can temporarily require far more memory than the final size and puts
+/// pressure on the global allocator. Mapping the exact decompressed size up front and
+/// decoding straight into it avoids both.
+///
+/// # Example
+/// ```no_run
+/// use include_packed::{MmapOptions, include_packed_asset};
+/// # // The asset only exists in crates whose build script packed it.
+/// # macro_rules! include_packed_asset {
+/// #     ($path:literal) => {
+/// #         include_packed::PackedAsset::from_compressed(&[])
+/// #     };
+/// # }
+///
+/// let map = MmapOptions::new()
+///     .huge_pages(true)
+///     .decompress(&include_packed_asset!("assets/dataset.bin"))
+///     .expect("Failed to map asset");
+/// println!("{} bytes mapped", map.len());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MmapOptions {
+    huge_pages: bool,
+    sequential: bool,
+}
+
+impl MmapOptions {
+    /// Creates a new set of options with default settings.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests transparent huge pages (`MADV_HUGEPAGE`) for the mapping.
+    ///
+    /// Backing multi-gigabyte buffers with huge pages reduces TLB pressure, which can
+    /// noticeably speed up scans over the decompressed data. The hint is applied before
+    /// any data is written, and only has an effect on Linux with transparent huge pages
+    /// enabled in `madvise` or `always` mode. Defaults to `false`.
+    #[must_use]
+    pub const fn huge_pages(mut self, huge_pages: bool) -> Self {
+        self.huge_pages = huge_pages;
+        self
+    }
+
+    /// Advises the kernel that the decompressed data will be read sequentially
+    /// (`MADV_SEQUENTIAL`).
+    ///
+    /// This only has an effect on Linux. Defaults to `false`.
+    #[must_use]
+    pub const fn sequential(mut self, sequential: bool) -> Self {
+        self.sequential = sequential;
+        self
+    }
+
+    /// Decompresses `asset` into a new anonymous memory map.
+    ///
+    /// # Errors
+    /// Returns an error if the memory map could not be created or protected.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`PackedAsset::decompress`].
+    #[track_caller]
+    pub fn decompress(&self, asset: &PackedAsset) -> io::Result<AssetMmap> {
+        if let Some(data) = asset.read_passthrough() {
+            let mut map = self.map_anon(data.len())?;
+            map[..data.len()].copy_from_slice(&data);
+            return self.finish(map, data.len());
+        }
+
+        let compressed = asset.decrypted();
+        let dictionary = asset.decoding_dictionary();
+        let len = match asset.known_len() {
+            Some(len) => len,
+            None => codec::decoded_len(asset.algorithm(), dictionary.get(), &compressed)
+                .unwrap_or_else(|err| asset.corrupt(&err)),
+        };
+        let mut map = self.map_anon(len)?;
+        let written = codec::decode_into(
+            asset.algorithm(),
+            dictionary.get(),
+            &compressed,
+            &mut map[..len],
+        )
+        .unwrap_or_else(|err| asset.corrupt(&err));
+        debug_assert_eq!(written, len);
+        if let Err(err) = asset.verify_checksum(&map[..len]) {
+            panic!("include_packed: {err}");
+        }
+        self.finish(map, len)
+    }
+
+    /// Creates a writable anonymous memory map of at least `len` bytes.
+    fn map_anon(&self, len: usize) -> io::Result<MmapMut> {
+        // Anonymous mappings of length zero are rejected by the OS.
+        let map = MmapMut::map_anon(len.max(1))?;
+        // Hints are purely advisory, so failing to apply them is not an error.
+        #[cfg(target_os = "linux")]
+        if self.huge_pages {
+            let _ = map.advise(memmap2::Advice::HugePage);
+        }
+        Ok(map)
+    }
+
+    /// Makes `map`, holding `len` bytes of decompressed data, read-only.
+    fn finish(&self, map: MmapMut, len: usize) -> io::Result<AssetMmap> {
+        let map = map.make_read_only()?;
+        #[cfg(target_os = "linux")]
+        if self.sequential {
+            let _ = map.advise(memmap2::Advice::Sequential);
+        }
+
+        Ok(AssetMmap { map, len })
+    }
+}
+
+/// A read-only anonymous memory map holding a decompressed asset.
+///
+/// Dereferences to the decompressed bytes.
+pub struct AssetMmap {
+    map: Mmap,
+    len: usize,
+}
+
+impl Deref for AssetMmap {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.map[..self.len]
+    }
+}
+
+impl AsRef<[u8]> for AssetMmap {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl fmt::Debug for AssetMmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssetMmap")
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,177 @@
+//! External patch packs that overlay embedded assets at runtime.
+use std::{collections::HashMap, fs, io, ops::Range, path::Path};
+
+use super::PackedAsset;
+
+/// Magic bytes at the start of every `.pack` file, including the format version.
+pub(crate) const MAGIC: &[u8; 8] = b"IPPACK01";
+
+/// A set of zstd-compressed assets loaded from an external `.pack` file.
+///
+/// Patch packs let hotfixes or downloadable content replace individual embedded assets
+/// without rebuilding the binary. They are written by `Config::write_pack`, and
+/// [`overlay`](Self::overlay) swaps in a pack's version of an asset whenever the pack
+/// contains its logical path:
+///
+/// ```no_run
+/// use std::sync::OnceLock;
+///
+/// use include_packed::{PackedAsset, PatchPack};
+///
+/// static PATCHES: OnceLock<PatchPack> = OnceLock::new();
+///
+/// fn load(asset: PackedAsset) -> Vec<u8> {
+///     let patches = PATCHES.get_or_init(|| PatchPack::open("hotfix.pack").unwrap_or_default());
+///     patches.overlay(asset).decompress()
+/// }
+/// ```
+///
+/// # Format
+///
+/// All integers are little-endian:
+///
+/// ```text
+/// magic:   b"IPPACK01"
+/// count:   u32
+/// entries: count × { path_len: u32, path: [u8; path_len] (UTF-8),
+///                    data_len: u64, data: [u8; data_len] (a zstd frame) }
+/// ```
+#[derive(Debug, Default)]
+pub struct PatchPack {
+    data: Vec<u8>,
+    entries: HashMap<String, Range<usize>>,
+}
+
+impl PatchPack {
+    /// Reads and parses a `.pack` file.
+    ///
+    /// # Errors
+    /// Returns an error if the file could not be read or is not a valid pack.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_bytes(fs::read(path)?)
+    }
+
+    /// Parses a pack from its raw bytes.
+    ///
+    /// Only the pack's structure is validated here. The compressed data of each entry is
+    /// checked when it is decompressed.
+    ///
+    /// # Errors
+    /// Returns an [`io::ErrorKind::InvalidData`] error if the bytes are not a valid pack.
+    pub fn from_bytes(data: Vec<u8>) -> io::Result<Self> {
+        let mut reader = Reader {
+            data: &data,
+            pos: 0,
+        };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(invalid("not an include_packed patch pack"));
+        }
+
+        let count = reader.u32()?;
+        let mut entries = HashMap::new();
+        for _ in 0..count {
+            let path_len = reader.u32()? as usize;
+            let path = std::str::from_utf8(reader.take(path_len)?)
+                .map_err(|_| invalid("asset path is not valid UTF-8"))?
+                .to_owned();
+            // Obfuscated assets have empty paths, which must not match an entry.
+            if path.is_empty() {
+                return Err(invalid("empty asset path"));
+            }
+            let data_len = usize::try_from(reader.u64()?)
+                .map_err(|_| invalid("asset data length overflows"))?;
+            let start = reader.pos;
+            reader.take(data_len)?;
+            if entries.insert(path, start..reader.pos).is_some() {
+                return Err(invalid("duplicate asset path"));
+            }
+        }
+        if reader.pos != data.len() {
+            return Err(invalid("trailing data after the last entry"));
+        }
+
+        Ok(Self { data, entries })
+    }
+
+    /// Returns the number of assets in the pack.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the pack contains no assets.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns `true` if the pack contains an asset with the given logical path.
+    #[must_use]
+    pub fn contains(&self, path: &str) -> bool {
+        self.entries.contains_key(path)
+    }
+
+    /// Returns an iterator over the logical paths of the assets in the pack.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// Returns the compressed data the pack holds for the given logical path.
+    #[must_use]
+    pub fn get(&self, path: &str) -> Option<&[u8]> {
+        self.entries
+            .get(path)
+            .map(|range| &self.data[range.clone()])
+    }
+
+    /// Returns the pack's version of `asset` if it has one, or `asset` itself otherwise.
+    ///
+    /// Assets are matched by their logical [`path`](PackedAsset::path), i.e. the path
+    /// as written at the macro call site. Assets without a path, such as those packed
+    /// with `Config::obfuscate`, are never overlaid. The pack must live for the rest of
+    /// the program, e.g. in a `static` [`OnceLock`](std::sync::OnceLock). A patched
+    /// asset carries no build provenance.
+    #[must_use]
+    pub fn overlay(&'static self, asset: PackedAsset) -> PackedAsset {
+        if asset.path().is_empty() {
+            return asset;
+        }
+        match self.get(asset.path()) {
+            Some(compressed) => PackedAsset::from_compressed(compressed).with_path(asset.path()),
+            None => asset,
+        }
+    }
+}
+
+/// A bounds-checked cursor over a pack's bytes.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        let bytes = self
+            .pos
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.pos..end))
+            .ok_or_else(|| invalid("unexpected end of pack"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid patch pack: {message}"),
+    )
+}
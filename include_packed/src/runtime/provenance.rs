@@ -0,0 +1,64 @@
+//! Build provenance recorded for packed assets.
+
+/// Where and when an asset was packed.
+///
+/// This is recorded by the build script when
+/// `Config::provenance` is enabled, and lets support teams
+/// verify in the field exactly which asset snapshot a given binary contains. It is
+/// available through [`PackedAsset::provenance`](super::PackedAsset::provenance).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Provenance {
+    crate_name: &'static str,
+    crate_version: &'static str,
+    git_commit: Option<&'static str>,
+    build_timestamp: u64,
+}
+
+impl Provenance {
+    /// Creates a provenance record.
+    ///
+    /// This is an implementation detail of the macros and is not intended to be called
+    /// directly by user code.
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(
+        crate_name: &'static str,
+        crate_version: &'static str,
+        git_commit: Option<&'static str>,
+        build_timestamp: u64,
+    ) -> Self {
+        Self {
+            crate_name,
+            crate_version,
+            git_commit,
+            build_timestamp,
+        }
+    }
+
+    /// Returns the name of the crate whose build script packed the asset.
+    #[must_use]
+    pub const fn crate_name(&self) -> &'static str {
+        self.crate_name
+    }
+
+    /// Returns the version of the crate whose build script packed the asset.
+    #[must_use]
+    pub const fn crate_version(&self) -> &'static str {
+        self.crate_version
+    }
+
+    /// Returns the git commit checked out when the asset was packed, if the crate was
+    /// built from a git repository.
+    #[must_use]
+    pub const fn git_commit(&self) -> Option<&'static str> {
+        self.git_commit
+    }
+
+    /// Returns when the asset was packed, in seconds since the Unix epoch.
+    ///
+    /// This honors `SOURCE_DATE_EPOCH` for reproducible builds.
+    #[must_use]
+    pub const fn build_timestamp(&self) -> u64 {
+        self.build_timestamp
+    }
+}
@@ -0,0 +1,81 @@
+//! Streaming decompression of packed assets.
+use std::{borrow::Cow, fmt, io};
+
+use super::{PackedAsset, asset::DecodingDictionary, codec};
+
+/// A streaming reader over the decompressed contents of a packed asset.
+///
+/// Created by [`PackedAsset::reader`](super::PackedAsset::reader) or the
+/// [`include_packed_reader!`](crate::include_packed_reader) macro. Data is decompressed
+/// incrementally as it is read, so multi-hundred-megabyte assets can be fed into parsers
+/// without ever materializing the whole decompressed file in memory.
+pub struct AssetReader {
+    decoder: Box<dyn io::Read + Send>,
+    /// Hashes the data read so far, to be compared with the asset's checksum.
+    #[cfg(feature = "checksums")]
+    checksum: Option<(blake3::Hasher, PackedAsset)>,
+}
+
+impl AssetReader {
+    /// Creates a reader over the contents of `asset`.
+    ///
+    /// Encrypted and delta assets are decompressed up front, as the decoders borrow
+    /// their input and the bases of delta assets.
+    #[track_caller]
+    pub(crate) fn new(asset: &PackedAsset) -> Self {
+        if let Some(data) = asset.read_passthrough() {
+            return Self {
+                decoder: Box::new(io::Cursor::new(data)),
+                #[cfg(feature = "checksums")]
+                checksum: None,
+            };
+        }
+
+        let (algorithm, dictionary) = (asset.algorithm(), asset.decoding_dictionary());
+        let decoder: Box<dyn io::Read + Send> = match (asset.decrypted(), &dictionary) {
+            (Cow::Borrowed(compressed), DecodingDictionary::Trained(dictionary)) => codec::decoder(
+                algorithm,
+                codec::Dictionary::Trained(dictionary),
+                compressed,
+            )
+            .expect(codec::BUG_DECOMPRESS),
+            (compressed, dictionary) => Box::new(io::Cursor::new(
+                codec::decode_all(algorithm, dictionary.get(), &compressed)
+                    .expect(codec::BUG_DECOMPRESS),
+            )),
+        };
+        Self {
+            decoder,
+            #[cfg(feature = "checksums")]
+            checksum: asset.checksum().map(|_| (blake3::Hasher::new(), *asset)),
+        }
+    }
+}
+
+impl io::Read for AssetReader {
+    /// Reads decompressed data into `buf`.
+    ///
+    /// Errors are only returned for corrupt embedded data, which indicates a bug in
+    /// `include_packed` or a damaged binary, and for data that does not match the
+    /// asset's checksum once the end is reached.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.decoder.read(buf)?;
+        #[cfg(feature = "checksums")]
+        if let Some((hasher, asset)) = &mut self.checksum {
+            if read > 0 {
+                hasher.update(&buf[..read]);
+            } else if !buf.is_empty() {
+                asset
+                    .verify_hash(&hasher.finalize())
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            }
+        }
+        Ok(read)
+    }
+}
+
+impl fmt::Debug for AssetReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssetReader").finish_non_exhaustive()
+    }
+}
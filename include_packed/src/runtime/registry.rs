@@ -0,0 +1,43 @@
+//! Looking up assets by path at runtime, through the registry of
+//! [`include_packed_registry!`](crate::include_packed_registry).
+use alloc::vec::Vec;
+
+use super::{PackedAsset, PackedDir};
+
+unsafe extern "Rust" {
+    /// Defined by [`include_packed_registry!`](crate::include_packed_registry).
+    static __include_packed_registry: PackedDir;
+}
+
+/// Returns the registry defined by
+/// [`include_packed_registry!`](crate::include_packed_registry).
+fn registry() -> &'static PackedDir {
+    // SAFETY: The macro defines the static with this type, and the name is reserved for
+    // it. Binaries that don't invoke the macro fail to link instead.
+    unsafe { &__include_packed_registry }
+}
+
+/// Returns an iterator over the logical paths and handles of every registered asset, in
+/// sorted order.
+///
+/// The binary must invoke [`include_packed_registry!`](crate::include_packed_registry)
+/// once to register the assets packed by its build script, or it fails to link.
+pub fn assets() -> impl Iterator<Item = (&'static str, PackedAsset)> {
+    registry().iter()
+}
+
+/// Decompresses the registered asset at the logical path `path`, e.g.
+/// `"assets/foo.bin"`, or returns `None` if no such asset is registered.
+///
+/// This suits plugins and scripts that name assets at runtime. The binary must invoke
+/// [`include_packed_registry!`](crate::include_packed_registry) once to register the
+/// assets packed by its build script, or it fails to link.
+///
+/// # Panics
+///
+/// Panics under the same conditions as [`PackedAsset::decompress`].
+#[track_caller]
+#[must_use]
+pub fn get(path: &str) -> Option<Vec<u8>> {
+    registry().decompress(path)
+}
@@ -0,0 +1,104 @@
+//! The framework-independent logic of serving packed assets over HTTP.
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use super::{PackedAsset, PackedDir};
+
+/// The outcome of a request for a packed asset, turned into a response by each web
+/// framework's integration.
+pub(crate) enum Reply {
+    /// No asset exists at the requested path.
+    NotFound,
+    /// The asset could not be decompressed.
+    Failed,
+    /// The client's cached copy, tagged with `etag`, is still current.
+    NotModified { etag: String },
+    /// The asset's decompressed contents.
+    Found {
+        content_type: String,
+        etag: String,
+        data: Vec<u8>,
+    },
+}
+
+/// Serves the asset at the percent-decoded `path` below `dir`.
+///
+/// Paths that are empty or end in `/` serve the `index.html` below them.
+pub(crate) fn serve_dir<'a>(
+    dir: &PackedDir,
+    path: &str,
+    if_none_match: impl IntoIterator<Item = &'a str>,
+) -> Reply {
+    let mut path = path.trim_start_matches('/').to_owned();
+    if path.is_empty() || path.ends_with('/') {
+        path.push_str("index.html");
+    }
+    match dir.get(&path) {
+        Some(asset) => serve_asset(&asset, &path, if_none_match),
+        None => Reply::NotFound,
+    }
+}
+
+/// Serves `asset`, guessing its content type from `path`.
+///
+/// Conditional requests whose `If-None-Match` header values match the asset's `ETag`
+/// are answered without decompressing anything.
+pub(crate) fn serve_asset<'a>(
+    asset: &PackedAsset,
+    path: &str,
+    if_none_match: impl IntoIterator<Item = &'a str>,
+) -> Reply {
+    // Files read from disk in development may differ from the embedded data.
+    let (etag, data) = if asset.passthrough().is_some() {
+        match asset.try_decompress() {
+            Ok(data) => (etag(&data), Some(data)),
+            Err(_) => return Reply::Failed,
+        }
+    } else {
+        (etag(asset.compressed()), None)
+    };
+    if if_none_match
+        .into_iter()
+        .flat_map(|value| value.split(','))
+        // `If-None-Match` uses the weak comparison, which ignores the `W/` prefix.
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || tag == etag)
+    {
+        return Reply::NotModified { etag };
+    }
+
+    match data.map_or_else(|| asset.try_decompress(), Ok) {
+        Ok(data) => Reply::Found {
+            content_type: mime_guess::from_path(path)
+                .first_or_octet_stream()
+                .to_string(),
+            etag,
+            data,
+        },
+        Err(_) => Reply::Failed,
+    }
+}
+
+/// Returns a strong `ETag` for `data`.
+fn etag(data: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+/// Decodes the percent-encoded URI path `path`.
+///
+/// Returns `None` if the path is not valid percent-encoded UTF-8.
+#[cfg(any(feature = "axum", feature = "tauri"))]
+pub(crate) fn percent_decode(path: &str) -> Option<String> {
+    let mut decoded = Vec::with_capacity(path.len());
+    let mut bytes = path.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte == b'%' {
+            let hex = [bytes.next()?, bytes.next()?];
+            decoded.push(u8::from_str_radix(core::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            decoded.push(byte);
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
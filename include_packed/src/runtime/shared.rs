@@ -0,0 +1,39 @@
+//! Decompressing packed assets into reference-counted `bytes::Bytes`.
+use alloc::borrow::Cow;
+
+use bytes::Bytes;
+
+use super::{Error, PackedAsset};
+
+impl PackedAsset {
+    /// Decompresses the asset into a [`Bytes`] buffer, which is cheap to clone and can be
+    /// handed to `hyper` or `axum` response bodies without copying.
+    ///
+    /// Assets stored uncompressed are returned without copying at all, like
+    /// [`decompress_cow`](Self::decompress_cow). This is what
+    /// [`include_packed_shared!`](crate::include_packed_shared) expands to.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`decompress`](Self::decompress).
+    #[track_caller]
+    #[must_use]
+    pub fn decompress_bytes(&self) -> Bytes {
+        self.try_decompress_bytes()
+            .unwrap_or_else(|err| panic!("include_packed: {err}"))
+    }
+
+    /// Decompresses the asset into a [`Bytes`] buffer like
+    /// [`decompress_bytes`](Self::decompress_bytes), but returns an error instead of
+    /// panicking.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`try_decompress`](Self::try_decompress).
+    pub fn try_decompress_bytes(&self) -> Result<Bytes, Error> {
+        Ok(match self.try_decompress_cow()? {
+            Cow::Borrowed(data) => Bytes::from_static(data),
+            Cow::Owned(data) => Bytes::from(data),
+        })
+    }
+}
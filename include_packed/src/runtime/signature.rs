@@ -0,0 +1,85 @@
+//! Ed25519 signatures of packed assets.
+use std::sync::RwLock;
+
+use ed25519_dalek::{Signature as Ed25519Signature, VerifyingKey};
+
+use super::Error;
+
+/// The public key set with [`set_trusted_key`].
+static TRUSTED_KEY: RwLock<Option<[u8; 32]>> = RwLock::new(None);
+
+/// Sets the ed25519 public key that signed assets are verified with.
+///
+/// This must be the public half of the key passed to `Config::sign`, and must be called
+/// before any signed asset is decompressed, e.g. with a key written into the
+/// application's source. Until then, decompressing one fails with
+/// [`Error::UntrustedSignature`]. Signatures are only ever checked against this key,
+/// never against the public key recorded next to them, so replacing an asset along with
+/// its signature and key is detected. Setting it again replaces the key.
+pub fn set_trusted_key(public_key: [u8; 32]) {
+    *TRUSTED_KEY.write().unwrap_or_else(|err| err.into_inner()) = Some(public_key);
+}
+
+/// The ed25519 signature of an asset, and the public key it was made with.
+///
+/// This is recorded by the build script when `Config::sign` is used, and is available
+/// through [`PackedAsset::signature`](super::PackedAsset::signature). The signature
+/// covers the BLAKE3 hash of the asset's original contents, and is verified against the
+/// key set with [`set_trusted_key`] every time the asset is decompressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Signature {
+    public_key: [u8; 32],
+    signature: [u8; 64],
+}
+
+impl Signature {
+    /// Creates a signature record.
+    ///
+    /// This is an implementation detail of the macros and is not intended to be called
+    /// directly by user code.
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(public_key: [u8; 32], signature: [u8; 64]) -> Self {
+        Self {
+            public_key,
+            signature,
+        }
+    }
+
+    /// Returns the ed25519 public key the build script recorded for the asset.
+    ///
+    /// This is informational, e.g. for telling which key a build used. It is not
+    /// trusted when verifying the signature.
+    #[must_use]
+    pub const fn public_key(&self) -> &[u8; 32] {
+        &self.public_key
+    }
+
+    /// Returns the ed25519 signature of the BLAKE3 hash of the asset's contents.
+    #[must_use]
+    pub const fn signature(&self) -> &[u8; 64] {
+        &self.signature
+    }
+
+    /// Checks that this is a valid signature of `hash` by the key set with
+    /// [`set_trusted_key`], for the asset at `path`.
+    ///
+    /// # Errors
+    /// Returns [`Error::UntrustedSignature`] if no key was set, and
+    /// [`Error::SignatureMismatch`] if the signature was not made with it.
+    pub(crate) fn verify(&self, path: &'static str, hash: &[u8; 32]) -> Result<(), Error> {
+        let trusted = TRUSTED_KEY
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .ok_or(Error::UntrustedSignature { path })?;
+        let valid = VerifyingKey::from_bytes(&trusted).is_ok_and(|key| {
+            key.verify_strict(hash, &Ed25519Signature::from_bytes(&self.signature))
+                .is_ok()
+        });
+        if valid {
+            Ok(())
+        } else {
+            Err(Error::SignatureMismatch { path })
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Serving packed frontends from a Tauri custom protocol.
+use http::{Method, Request, Response, StatusCode, header};
+
+use super::{
+    PackedDir,
+    serve::{Reply, percent_decode, serve_dir},
+};
+
+/// A Tauri custom protocol handler serving a packed frontend, such as a bundler's
+/// `dist` folder.
+///
+/// Tauri's protocol handlers take and return `http` types, so this works with
+/// `register_uri_scheme_protocol` without this crate depending on Tauri:
+///
+/// ```no_run
+/// use include_packed::{PackedProtocol, include_packed_dir};
+/// # // The directory only exists in crates whose build script packed it.
+/// # macro_rules! include_packed_dir {
+/// #     ($path:literal) => {
+/// #         include_packed::PackedDir::new(&[], |_| None)
+/// #     };
+/// # }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let frontend = PackedProtocol::new(include_packed_dir!("dist"));
+/// # // `tauri` is not a dependency of this crate.
+/// # #[cfg(any())]
+/// tauri::Builder::default()
+///     .register_uri_scheme_protocol("app", move |_ctx, request| frontend.respond(&request))
+///     .run(tauri::generate_context!())?;
+/// # let _ = frontend.respond(&http::Request::new(()));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Requests are resolved against the directory by their URI path, and paths ending in
+/// `/` serve the `index.html` below them. Unknown paths without a file extension, such
+/// as the routes of single-page applications, serve the fallback page, which defaults
+/// to the root `index.html`. Responses carry a `Content-Type` guessed from the file
+/// extension and an `ETag` derived from the asset's data, like those of
+/// `PackedServeDir`.
+#[derive(Debug, Clone, Copy)]
+pub struct PackedProtocol {
+    dir: PackedDir,
+    fallback: Option<&'static str>,
+}
+
+impl PackedProtocol {
+    /// Creates a handler serving the assets of `dir`.
+    #[must_use]
+    pub const fn new(dir: PackedDir) -> Self {
+        Self {
+            dir,
+            fallback: Some("index.html"),
+        }
+    }
+
+    /// Sets the page served for unknown paths without a file extension, relative to
+    /// the directory, or disables the fallback with `None`.
+    #[must_use]
+    pub const fn fallback(mut self, fallback: Option<&'static str>) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// Returns the response to `request`.
+    #[must_use]
+    pub fn respond<B>(&self, request: &Request<B>) -> Response<Vec<u8>> {
+        if !matches!(*request.method(), Method::GET | Method::HEAD) {
+            let mut response = status(StatusCode::METHOD_NOT_ALLOWED);
+            response
+                .headers_mut()
+                .insert(header::ALLOW, header::HeaderValue::from_static("GET, HEAD"));
+            return response;
+        }
+        let Some(path) = percent_decode(request.uri().path()) else {
+            return status(StatusCode::NOT_FOUND);
+        };
+        let if_none_match = || {
+            request
+                .headers()
+                .get_all(header::IF_NONE_MATCH)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+        };
+
+        let mut reply = serve_dir(&self.dir, &path, if_none_match());
+        if matches!(reply, Reply::NotFound)
+            && let Some(fallback) = self.fallback
+            && !path
+                .rsplit('/')
+                .next()
+                .is_some_and(|name| name.contains('.'))
+        {
+            reply = serve_dir(&self.dir, fallback, if_none_match());
+        }
+        match reply {
+            Reply::NotFound => status(StatusCode::NOT_FOUND),
+            Reply::Failed => status(StatusCode::INTERNAL_SERVER_ERROR),
+            Reply::NotModified { etag } => Response::builder()
+                .status(StatusCode::NOT_MODIFIED)
+                .header(header::ETAG, etag)
+                .body(Vec::new())
+                .expect("ETags are valid header values"),
+            Reply::Found {
+                content_type,
+                etag,
+                data,
+            } => Response::builder()
+                .header(header::CONTENT_TYPE, content_type)
+                .header(header::ETAG, etag)
+                .body(data)
+                .expect("content types and ETags are valid header values"),
+        }
+    }
+}
+
+/// Returns an empty response with the given status.
+fn status(status: StatusCode) -> Response<Vec<u8>> {
+    let mut response = Response::new(Vec::new());
+    *response.status_mut() = status;
+    response
+}
@@ -0,0 +1,53 @@
+//! Decompressing packed assets on tokio's blocking thread pool.
+use std::{future::Future, panic};
+
+use super::{Error, PackedAsset};
+
+impl PackedAsset {
+    /// Decompresses the asset on tokio's blocking thread pool, so that large assets don't
+    /// stall the executor's worker threads, e.g. while an async application starts up.
+    ///
+    /// This is what [`include_packed_async!`](crate::include_packed_async) expands to.
+    /// Decompression starts when the returned future is first polled, which must happen
+    /// within a tokio runtime.
+    ///
+    /// # Panics
+    ///
+    /// The future panics under the same conditions as [`decompress`](Self::decompress),
+    /// if it is polled outside of a tokio runtime, and if the runtime shuts down before
+    /// decompression finishes.
+    pub fn decompress_async(&self) -> impl Future<Output = Vec<u8>> + Send + 'static {
+        let asset = *self;
+        spawn(move || asset.decompress())
+    }
+
+    /// Decompresses the asset on tokio's blocking thread pool like
+    /// [`decompress_async`](Self::decompress_async), but the future returns an error
+    /// instead of panicking if the embedded data is corrupt.
+    ///
+    /// # Errors
+    ///
+    /// The future returns the errors of [`try_decompress`](Self::try_decompress).
+    ///
+    /// # Panics
+    ///
+    /// The future panics if it is polled outside of a tokio runtime, and if the runtime
+    /// shuts down before decompression finishes.
+    pub fn try_decompress_async(
+        &self,
+    ) -> impl Future<Output = Result<Vec<u8>, Error>> + Send + 'static {
+        let asset = *self;
+        spawn(move || asset.try_decompress())
+    }
+}
+
+/// Runs `decode` on tokio's blocking thread pool, forwarding its panics to the caller.
+async fn spawn<T: Send + 'static>(decode: impl FnOnce() -> T + Send + 'static) -> T {
+    match tokio::task::spawn_blocking(decode).await {
+        Ok(data) => data,
+        Err(err) => match err.try_into_panic() {
+            Ok(payload) => panic::resume_unwind(payload),
+            Err(err) => panic!("include_packed: decompression was cancelled: {err}"),
+        },
+    }
+}
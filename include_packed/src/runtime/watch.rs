@@ -0,0 +1,77 @@
+//! Watching source assets for changes during development, see [`watch`].
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher as _, event::ModifyKind};
+
+/// Watches source assets for changes until it is dropped.
+///
+/// Returned by [`watch`].
+#[derive(Debug)]
+pub struct AssetWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+/// Calls `callback` with the logical path and fresh contents of every asset below `path`
+/// that is created or changed on disk, until the returned [`AssetWatcher`] is dropped.
+///
+/// This builds on `Config::dev_passthrough`: where passthrough picks up edits the next
+/// time an asset is decompressed, a watcher lets UI themes, shaders, and the like reload
+/// the moment they are saved. A relative `path`, such as `"assets/"`, is resolved
+/// against `CARGO_MANIFEST_DIR` when it is set, as under `cargo run`, and against the
+/// current directory otherwise. Logical paths are reported like the macros name the
+/// assets, i.e. `path` joined with the file's path below it using `/`.
+///
+/// The callback runs on a background thread and may be called several times for one
+/// save, depending on how the editor writes the file. Files that can't be read, e.g.
+/// because they were removed again, are skipped.
+///
+/// # Errors
+///
+/// Returns an error if `path` does not exist or can't be watched.
+pub fn watch<F>(path: impl AsRef<Path>, mut callback: F) -> io::Result<AssetWatcher>
+where
+    F: FnMut(&str, Vec<u8>) + Send + 'static,
+{
+    let logical_root = path.as_ref().to_path_buf();
+    let root = match env::var_os("CARGO_MANIFEST_DIR") {
+        Some(dir) if logical_root.is_relative() => PathBuf::from(dir).join(&logical_root),
+        _ => logical_root.clone(),
+    };
+    // Some platforms report events under the canonical path.
+    let root = fs::canonicalize(root)?;
+
+    let handler = {
+        let root = root.clone();
+        move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            match event.kind {
+                EventKind::Create(_) => {}
+                EventKind::Modify(kind) if !matches!(kind, ModifyKind::Metadata(_)) => {}
+                _ => return,
+            }
+            for source in event.paths {
+                let Ok(relative) = source.strip_prefix(&root) else {
+                    continue;
+                };
+                let Ok(data) = fs::read(&source) else {
+                    continue;
+                };
+                let logical_path = logical_root
+                    .join(relative)
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                callback(&logical_path, data);
+            }
+        }
+    };
+    let mut watcher = notify::recommended_watcher(handler).map_err(io::Error::other)?;
+    watcher
+        .watch(&root, RecursiveMode::Recursive)
+        .map_err(io::Error::other)?;
+    Ok(AssetWatcher { _watcher: watcher })
+}
@@ -0,0 +1,60 @@
+//! Runtime lookup of assets embedded as Windows `RT_RCDATA` resources.
+use std::ptr;
+
+use windows_sys::Win32::{
+    Foundation::HMODULE,
+    System::LibraryLoader::{
+        FindResourceW, GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS,
+        GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT, GetModuleHandleExW, LoadResource,
+        LockResource, SizeofResource,
+    },
+};
+
+/// `MAKEINTRESOURCEW(RT_RCDATA)`.
+const RT_RCDATA: *const u16 = 10 as *const u16;
+
+/// Resolves the data of an asset embedded as a PE resource by the build script.
+///
+/// This function is an implementation detail of the [`include_packed!`](crate::include_packed)
+/// macro and is not intended to be called directly by user code.
+///
+/// # Panics
+///
+/// Panics if the resource cannot be found in the module containing this crate, which
+/// indicates that the `.res` file produced by the build script was not linked.
+#[doc(hidden)]
+#[track_caller]
+#[must_use]
+pub fn windows_resource(name: &str) -> &'static [u8] {
+    let wide: Vec<u16> = name.to_uppercase().encode_utf16().chain([0]).collect();
+
+    // SAFETY: All pointers passed to the Win32 API are valid for the duration of the calls.
+    // Resource memory stays mapped for as long as the module is loaded, and the module
+    // containing this function cannot be unloaded while its code is running, so the
+    // returned slice is valid for `'static` in practice.
+    unsafe {
+        let mut module: HMODULE = ptr::null_mut();
+        let found = GetModuleHandleExW(
+            GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS | GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+            windows_resource as *const () as *const u16,
+            &mut module,
+        );
+        assert!(
+            found != 0,
+            "include_packed: could not determine the current module"
+        );
+
+        let info = FindResourceW(module, wide.as_ptr(), RT_RCDATA);
+        assert!(
+            !info.is_null(),
+            "include_packed: resource '{name}' not found; was the build script's .res file linked?"
+        );
+        let len = SizeofResource(module, info) as usize;
+        let data = LockResource(LoadResource(module, info));
+        assert!(
+            !data.is_null(),
+            "include_packed: failed to load resource '{name}'"
+        );
+        std::slice::from_raw_parts(data.cast::<u8>(), len)
+    }
+}
@@ -38,3 +38,194 @@ fn run_test_project() {
         "stdout did not contain success message: {stdout}"
     );
 }
+
+#[test]
+fn run_test_project_example() {
+    let cargo = which("cargo").expect("cargo not found in PATH");
+
+    // Examples only link the asset sets scoped to them by the build script.
+    let output = Command::new(cargo)
+        .args(["run", "--example", "demo"])
+        .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/test_project"))
+        .output()
+        .expect("Failed to execute test project example");
+
+    assert!(
+        output.status.success(),
+        "Test project example failed to run:\n--- stdout\n{}\n--- stderr\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8(output.stdout).expect("non UTF-8 output from test project");
+    assert!(
+        stdout.contains("Example data matches original."),
+        "stdout did not contain success message: {stdout}"
+    );
+}
+
+#[test]
+fn run_test_plugin_host() {
+    let cargo = which("cargo").expect("cargo not found in PATH");
+    let test_project = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/test_project");
+
+    // The host loads the plugin from its own target directory.
+    let output = Command::new(&cargo)
+        .args(["build", "-p", "test_plugin"])
+        .current_dir(test_project)
+        .output()
+        .expect("Failed to build test plugin");
+    assert!(
+        output.status.success(),
+        "Test plugin failed to build:\n--- stderr\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = Command::new(&cargo)
+        .args(["run", "--bin", "plugin_host"])
+        .current_dir(test_project)
+        .output()
+        .expect("Failed to execute plugin host");
+    assert!(
+        output.status.success(),
+        "Plugin host failed to run:\n--- stdout\n{}\n--- stderr\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8(output.stdout).expect("non UTF-8 output from plugin host");
+    assert!(
+        stdout.contains("Plugin asset matches original."),
+        "stdout did not contain success message: {stdout}"
+    );
+}
+
+#[test]
+fn run_test_no_std() {
+    let cargo = which("cargo").expect("cargo not found in PATH");
+
+    // Selecting the package alone keeps Cargo from unifying in the `std` feature that
+    // the rest of the workspace enables.
+    let output = Command::new(cargo)
+        .args(["run", "-p", "test_no_std"])
+        .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/test_no_std"))
+        .output()
+        .expect("Failed to execute no_std test project");
+
+    assert!(
+        output.status.success(),
+        "no_std test project failed to run:\n--- stdout\n{}\n--- stderr\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8(output.stdout).expect("non UTF-8 output from test project");
+    assert!(
+        stdout.contains("no_std assets match original."),
+        "stdout did not contain success message: {stdout}"
+    );
+}
+
+#[test]
+fn run_test_fallback() {
+    let cargo = which("cargo").expect("cargo not found in PATH");
+
+    // The package has no build script, so the macros embed its assets themselves.
+    let output = Command::new(cargo)
+        .args(["run", "-p", "test_fallback"])
+        .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/test_fallback"))
+        .output()
+        .expect("Failed to execute fallback test project");
+
+    assert!(
+        output.status.success(),
+        "Fallback test project failed to run:\n--- stdout\n{}\n--- stderr\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8(output.stdout).expect("non UTF-8 output from test project");
+    assert!(
+        stdout.contains("Fallback assets match original."),
+        "stdout did not contain success message: {stdout}"
+    );
+}
+
+#[test]
+fn run_inspect_cli() {
+    let cargo = which("cargo").expect("cargo not found in PATH");
+
+    // Cargo's build messages name the test project's executable and `OUT_DIR`.
+    let output = Command::new(&cargo)
+        .args(["build", "--bin", "test_project", "--message-format=json"])
+        .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/test_project"))
+        .output()
+        .expect("Failed to build test project");
+    assert!(
+        output.status.success(),
+        "Test project failed to build:\n--- stderr\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let messages = String::from_utf8(output.stdout).expect("non UTF-8 output from cargo");
+    let find = |needle: &str, key: &str| {
+        messages
+            .lines()
+            .filter(|line| line.contains("test_project") && line.contains(needle))
+            .find_map(|line| json_string(line, key))
+            .unwrap_or_else(|| panic!("cargo did not report the test project's {key}"))
+    };
+    let out_dir = find("\"reason\":\"build-script-executed\"", "out_dir");
+    let executable = find("\"kind\":[\"bin\"]", "executable");
+
+    let inspect = |args: &[&str]| {
+        let output = Command::new(&cargo)
+            .args([
+                "run",
+                "-q",
+                "-p",
+                "cargo-include-packed",
+                "--",
+                "include-packed",
+            ])
+            .args(args)
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .output()
+            .expect("Failed to execute cargo-include-packed");
+        assert!(
+            output.status.success(),
+            "cargo-include-packed failed:\n--- stdout\n{}\n--- stderr\n{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).expect("non UTF-8 output from cargo-include-packed")
+    };
+
+    let listing = inspect(&[&out_dir]);
+    for source in ["blobs/file.txt", "tiles/grid.txt", "bundled/style.css"] {
+        assert!(
+            listing.contains(source),
+            "{source} missing from listing: {listing}"
+        );
+    }
+
+    let linked = inspect(&[&executable, "--out-dir", &out_dir]);
+    assert!(
+        linked.contains("include_packed_bundle_") && linked.contains("blobs/nested/inner.txt"),
+        "linked assets missing from listing: {linked}"
+    );
+}
+
+/// Returns the string value of `key` in a line of JSON written by Cargo.
+fn json_string(line: &str, key: &str) -> Option<String> {
+    let start = line.find(&format!("\"{key}\":\""))? + key.len() + 4;
+    let mut value = String::new();
+    let mut chars = line[start..].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some(value),
+            '\\' => value.push(chars.next()?),
+            c => value.push(c),
+        }
+    }
+    None
+}
@@ -0,0 +1,36 @@
+use include_packed::{
+    PackedDir, include_packed, include_packed_asset, include_packed_bytes, include_packed_dir,
+    include_packed_len, include_packed_manifest, include_packed_str,
+};
+
+static ICONS: PackedDir = include_packed_dir!("assets/icons");
+
+fn main() {
+    let greeting = include_bytes!("../assets/greeting.txt");
+    assert_eq!(include_packed!("assets/greeting.txt"), greeting);
+    assert_eq!(
+        include_packed_str!("assets/greeting.txt"),
+        "Embedded without a build script\n"
+    );
+    const LEN: usize = include_packed_len!("assets/greeting.txt");
+    assert_eq!(LEN, greeting.len());
+
+    // Assets are embedded as is, so they can be borrowed straight from the binary.
+    let bytes: &'static [u8] = include_packed_bytes!("assets/greeting.txt");
+    assert_eq!(bytes, greeting);
+    assert_eq!(
+        include_packed_asset!("assets/greeting.txt").as_stored(),
+        Some(&greeting[..])
+    );
+
+    assert_eq!(ICONS.paths(), ["dot.svg"]);
+    assert_eq!(
+        ICONS.decompress("dot.svg").as_deref(),
+        Some(&include_bytes!("../assets/icons/dot.svg")[..])
+    );
+
+    // Nothing was packed ahead of time.
+    assert!(include_packed_manifest!().is_empty());
+
+    println!("Fallback assets match original.");
+}
@@ -0,0 +1,8 @@
+fn main() {
+    // Linked through the library, so binaries depending on it need no setup of their own.
+    include_packed::Config::new("assets")
+        .symbol_namespace("test_library")
+        .library()
+        .build()
+        .expect("Failed to pack library assets");
+}
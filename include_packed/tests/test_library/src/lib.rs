@@ -0,0 +1,7 @@
+use include_packed::include_packed;
+
+/// Returns the library's packed greeting.
+#[must_use]
+pub fn greeting() -> Vec<u8> {
+    include_packed!("assets/greeting.txt")
+}
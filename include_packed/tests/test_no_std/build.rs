@@ -0,0 +1,17 @@
+fn main() {
+    include_packed::Config::new("assets")
+        .build()
+        .expect("Failed to pack assets");
+
+    // Dictionary-compressed assets decode with the pure-Rust decoder as well.
+    include_packed::Config::new("../test_project/translations")
+        .train_dictionary(1024)
+        .build()
+        .expect("Failed to pack translations");
+
+    // Chunked assets consist of several zstd frames.
+    include_packed::Config::new("../test_project/tiles")
+        .chunk_size(1024)
+        .build()
+        .expect("Failed to pack tiles");
+}
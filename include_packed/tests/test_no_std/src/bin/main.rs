@@ -0,0 +1,16 @@
+fn main() {
+    assert_eq!(
+        test_no_std::table(),
+        b"A lookup table packed for a no_std target\n"
+    );
+    assert_eq!(test_no_std::try_table().ok(), Some(test_no_std::table()));
+    assert_eq!(
+        test_no_std::translation(),
+        include_str!("../../../test_project/translations/en.json")
+    );
+    let tiles = include_bytes!("../../../test_project/tiles/grid.txt");
+    assert_eq!(test_no_std::tiles(), tiles);
+    assert_eq!(test_no_std::tile_range(1020..1030), &tiles[1020..1030]);
+
+    println!("no_std assets match original.");
+}
@@ -0,0 +1,35 @@
+//! A `no_std` library that embeds packed assets, as firmware would.
+#![no_std]
+
+extern crate alloc;
+
+use alloc::{string::String, vec::Vec};
+
+use include_packed::{
+    include_packed, include_packed_asset, include_packed_str, include_packed_try,
+};
+
+/// Decompresses the lookup table.
+pub fn table() -> Vec<u8> {
+    include_packed!("assets/table.txt")
+}
+
+/// Decompresses a dictionary-compressed translation.
+pub fn translation() -> String {
+    include_packed_str!("../test_project/translations/en.json")
+}
+
+/// Decompresses a chunked asset in full.
+pub fn tiles() -> Vec<u8> {
+    include_packed!("../test_project/tiles/grid.txt")
+}
+
+/// Decompresses a slice of a chunked asset.
+pub fn tile_range(range: core::ops::Range<usize>) -> Vec<u8> {
+    include_packed_asset!("../test_project/tiles/grid.txt").decompress_range(range)
+}
+
+/// Decompresses the lookup table without panicking.
+pub fn try_table() -> Result<Vec<u8>, include_packed::Error> {
+    include_packed_try!("assets/table.txt")
+}
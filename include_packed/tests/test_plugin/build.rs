@@ -0,0 +1,7 @@
+fn main() {
+    // Namespaced so the plugin's symbols can't clash with the host's.
+    include_packed::Config::new("assets")
+        .symbol_namespace("test_plugin")
+        .build()
+        .expect("Failed to pack plugin assets");
+}
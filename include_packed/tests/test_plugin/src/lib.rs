@@ -0,0 +1,7 @@
+use include_packed::{RawPackedAsset, include_packed_asset};
+
+/// Exposes the plugin's packed asset to the host that loaded it.
+#[unsafe(no_mangle)]
+pub extern "C" fn test_plugin_asset() -> RawPackedAsset {
+    include_packed_asset!("assets/plugin.txt").into_raw()
+}
@@ -1,6 +1,478 @@
 fn main() {
-    include_packed::Config::new("blobs")
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+
+    println!("cargo:rustc-env=TEST_BLOBS_DIR=blobs");
+
+    // Files written by the previous run for assets that no longer exist are removed.
+    let out_path = std::path::Path::new(&out_dir);
+    let orphan = out_path.join("include_packed_0000000000000000.meta");
+    std::fs::write(&orphan, "symbol=include_packed_0000000000000000\n").unwrap();
+    let mut owned =
+        std::fs::read_to_string(out_path.join("include_packed.owned")).unwrap_or_default();
+    owned.push_str("include_packed_0000000000000000.meta\n");
+    std::fs::write(out_path.join("include_packed.owned"), owned).unwrap();
+    include_packed::use_shared_assets("test_library").expect("test_library shares no assets");
+    assert!(include_packed::use_shared_assets("missing").is_err());
+
+    let summary = include_packed::Config::new("blobs")
         .level(5)
+        .debug_level(3)
+        .release_level(19)
+        .override_level("blobs/nested/**", 19)
+        .hidden_symbols(true)
+        .link_section(".assets")
+        .verify_roundtrip(true)
+        .verify(true)
+        .provenance(true)
+        .cache_dir(std::path::Path::new(&out_dir).join("compression-cache"))
+        .jobs(2)
+        .report(std::path::Path::new(&out_dir).join("report.json"))
         .build()
         .expect("Failed to pack assets");
+    assert!(!orphan.exists());
+    assert_eq!(summary.file_count(), summary.assets().len());
+    assert!(summary.file_count() > 0);
+    assert_eq!(
+        summary.input_bytes(),
+        summary
+            .assets()
+            .iter()
+            .map(|asset| asset.len())
+            .sum::<u64>()
+    );
+    assert_eq!(
+        summary.output_bytes(),
+        summary
+            .assets()
+            .iter()
+            .map(|asset| asset.compressed_len())
+            .sum::<u64>()
+    );
+
+    // Assets outside the crate root are keyed by their (remapped) absolute path.
+    include_packed::Config::new("../shared_blobs")
+        .symbol_namespace("test_project")
+        .bin("test_project")
+        .zstd_workers(2)
+        .zstd_window_log(24)
+        .zstd_long_distance_matching(true)
+        .zstd_target_block_size(4096)
+        .verify_roundtrip(true)
+        .build()
+        .expect("Failed to pack shared assets");
+
+    // Assets below a root are resolved relative to it instead of the crate root.
+    include_packed::Config::new("rooted")
+        .root("..")
+        .generate_module(true)
+        .build()
+        .expect("Failed to pack rooted assets");
+
+    // Each of these assets uses a different compression algorithm.
+    for (path, algorithm) in [
+        ("algorithms/lz4.txt", include_packed::Algorithm::Lz4),
+        ("algorithms/brotli.txt", include_packed::Algorithm::Brotli),
+        ("algorithms/store.txt", include_packed::Algorithm::Store),
+    ] {
+        include_packed::Config::new(path)
+            .algorithm(algorithm)
+            .verify_roundtrip(true)
+            .dev_passthrough(true)
+            .generate_module(true)
+            .build()
+            .expect("Failed to pack asset");
+    }
+    // Every configuration adds its assets to the same generated module.
+    let module = std::fs::read_to_string(out_path.join("packed_assets.rs")).unwrap();
+    for name in [
+        "ROOTED_MOTD_TXT",
+        "ALGORITHMS_LZ4_TXT",
+        "ALGORITHMS_BROTLI_TXT",
+        "ALGORITHMS_STORE_TXT",
+    ] {
+        assert!(module.contains(&format!("pub static {name}:")), "{module}");
+    }
+    assert!(module.contains("The packed asset `rooted/motd.txt`."));
+    assert!(module.contains("28 bytes, "), "{module}");
+    assert!(module.contains("    AlgorithmsBrotliTxt,\n"), "{module}");
+
+    // Many small, similar files share a trained dictionary, and are linked as one
+    // static archive. On Wasm, they are downloaded on demand instead of bloating the
+    // binary.
+    include_packed::Config::new("translations")
+        .alias("default_locale", "translations/en.json")
+        .train_dictionary(1024)
+        .static_archive(true)
+        .wasm_fetch(std::path::Path::new(&out_dir).join("fetched"), "/assets")
+        .verify_roundtrip(true)
+        .build()
+        .expect("Failed to pack translations");
+
+    // Variants of an asset are packed as patches against their base.
+    let summary = include_packed::Config::new("tiers")
+        .delta("tiers/*.bin", "tiers/high.bin")
+        .build()
+        .expect("Failed to pack tiers");
+    for asset in summary.assets() {
+        let patched = asset.compressed_len() < 1024;
+        assert_eq!(patched, asset.path() == "tiers/low.bin", "{}", asset.path());
+    }
+    let error = include_packed::Config::new("tiers/low.bin")
+        .delta("tiers/low.bin", "tiers/high.bin")
+        .build()
+        .expect_err("the base is not packed");
+    assert_eq!(
+        error.to_string(),
+        "A generic build error occurred: delta base 'tiers/high.bin' is not among the packed assets"
+    );
+
+    // Bases are used as raw content, even if they start with the magic number of a zstd
+    // dictionary, and every chunk of a patch refers back to them.
+    let summary = include_packed::Config::new("patches")
+        .delta("patches/variant.bin", "patches/base.bin")
+        .chunk_size(4096)
+        .verify_roundtrip(true)
+        .build()
+        .expect("Failed to pack patches");
+    for asset in summary.assets() {
+        let patched = asset.compressed_len() < 1024;
+        assert_eq!(
+            patched,
+            asset.path() == "patches/variant.bin",
+            "{}",
+            asset.path()
+        );
+    }
+
+    // Automatic selection stores incompressible assets and compresses the others.
+    let summary = include_packed::Config::new("auto")
+        .auto(std::time::Duration::from_secs(10))
+        .cache_dir(std::path::Path::new(&out_dir).join("auto-cache"))
+        .build()
+        .expect("Failed to pack assets automatically");
+    for asset in summary.assets() {
+        let stored = asset.algorithm() == include_packed::Algorithm::Store;
+        assert_eq!(stored, asset.path() == "auto/noise.bin", "{}", asset.path());
+    }
+
+    // Remote files are downloaded once and pinned to their hash.
+    let server = std::net::TcpListener::bind("127.0.0.1:0").expect("Failed to bind server");
+    let addr = server.local_addr().expect("Failed to read server address");
+    std::thread::spawn(move || {
+        for stream in server.incoming() {
+            let mut stream = stream.expect("Failed to accept connection");
+            let mut request = [0; 1024];
+            let _ = std::io::Read::read(&mut stream, &mut request);
+            let body = "remote model weights\n";
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = std::io::Write::write_all(&mut stream, response.as_bytes());
+        }
+    });
+    include_packed::Config::remote(
+        format!("http://{addr}/models/model.bin?version=3"),
+        "2db9200984fbd82dec8ce99a0e2d9c4fb4eb817df66a94a80aa5f1440dc348f3",
+    )
+    .build()
+    .expect("Failed to pack remote model");
+    let error =
+        include_packed::Config::remote(format!("http://{addr}/tampered.bin"), "0".repeat(64))
+            .build()
+            .expect_err("the download does not match the pinned hash");
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "'http://{addr}/tampered.bin' has the SHA-256 hash 2db9200984fbd82dec8ce99a0e2d9c4fb4eb817df66a94a80aa5f1440dc348f3, but {} was expected",
+            "0".repeat(64)
+        )
+    );
+    let downloads = out_path.join("include_packed_remote").join("0".repeat(64));
+    assert_eq!(std::fs::read_dir(downloads).unwrap().count(), 0);
+    let error = include_packed::Config::remote(format!("http://{addr}/model.bin"), "0".repeat(64))
+        .root("extra")
+        .build()
+        .expect_err("remote downloads are resolved below a root of their own");
+    assert!(
+        error.to_string().contains("`root` cannot be combined"),
+        "{error}"
+    );
+
+    // The members of archives are packed as individual assets, without extracting them.
+    include_packed::Config::from_archive("packed_archives/art.zip")
+        .add_path("packed_archives/levels.tar.gz")
+        .verify_roundtrip(true)
+        .build()
+        .expect("Failed to pack archives");
+
+    // Legacy text files are converted to UTF-8 before they are compressed.
+    include_packed::Config::new("legacy")
+        .transcode("**/*.utf16.txt", include_packed::Encoding::Utf16Le)
+        .transcode("**/*.latin1.txt", include_packed::Encoding::Latin1)
+        .build()
+        .expect("Failed to pack legacy text");
+
+    // Secrets are only stored encrypted. Release builds would read the key from a file
+    // provisioned by CI.
+    if std::env::var_os("INCLUDE_PACKED_ENCRYPTION_KEY").is_none() {
+        let key_file = out_path.join("encryption.key");
+        if !key_file.exists() {
+            std::fs::write(&key_file, [0x2a; 32]).unwrap();
+        }
+        // SAFETY: No other threads are running at this point.
+        unsafe { std::env::set_var("INCLUDE_PACKED_KEY_FILE", key_file) };
+    }
+    include_packed::Config::new("secrets")
+        .encrypt(include_packed::Cipher::ChaCha20Poly1305)
+        .verify(true)
+        .verify_roundtrip(true)
+        .build()
+        .expect("Failed to pack secrets");
+
+    // Signed assets are verified against their signature on every decompression.
+    include_packed::Config::new("signed")
+        .sign([7; 32])
+        .build()
+        .expect("Failed to pack signed assets");
+
+    // Large assets are split into chunks that can be decompressed on their own.
+    include_packed::Config::new("tiles")
+        .chunk_size(1024)
+        .verify_roundtrip(true)
+        .verify(true)
+        .build()
+        .expect("Failed to pack tiles");
+
+    // Identical files are only embedded once, even when found in different paths.
+    include_packed::Config::new("duplicates")
+        .add_path("extra")
+        .verify_roundtrip(true)
+        .build()
+        .expect("Failed to pack duplicates");
+
+    // Configuration deserialized with `include_packed_as!`. The environment overrides
+    // its level, preferring the variable of the current profile.
+    // SAFETY: No other threads are running at this point.
+    unsafe {
+        std::env::set_var("INCLUDE_PACKED_LEVEL", "3");
+        std::env::set_var("INCLUDE_PACKED_LEVEL_DEBUG", "2");
+    }
+    include_packed::Config::new("config")
+        .level(19)
+        .debug_level(4)
+        .report(std::path::Path::new(&out_dir).join("config-report.json"))
+        .build()
+        .expect("Failed to pack configuration");
+    // SAFETY: No other threads are running at this point.
+    unsafe {
+        std::env::remove_var("INCLUDE_PACKED_LEVEL");
+        std::env::remove_var("INCLUDE_PACKED_LEVEL_DEBUG");
+    }
+
+    // Assets are rewritten before compression, here to normalize line endings.
+    include_packed::Config::new("transformed")
+        .transform(|path, contents| {
+            assert_eq!(path, std::path::Path::new("transformed/notes.txt"));
+            String::from_utf8(contents)
+                .expect("Transformed assets are text")
+                .replace("\r\n", "\n")
+                .into_bytes()
+        })
+        .build()
+        .expect("Failed to pack transformed assets");
+
+    // Images are re-encoded or downscaled while packing.
+    include_packed::Config::new("images")
+        .image_transform("images/sprite.png", include_packed::ImageTransform::Qoi)
+        .image_transform(
+            "images/large.png",
+            include_packed::ImageTransform::MaxDimension(2),
+        )
+        .build()
+        .expect("Failed to pack images");
+
+    // Already compressed formats are stored as is, as are any extensions configured.
+    include_packed::Config::new("stored/sprite.png")
+        .build()
+        .expect("Failed to pack stored image");
+    include_packed::Config::new("stored/level.dat")
+        .store_extensions([".DAT"])
+        .build()
+        .expect("Failed to pack stored level");
+
+    // Obfuscated assets get opaque symbols, found through their metadata.
+    include_packed::Config::new("obfuscated")
+        .obfuscate(true)
+        .verify_roundtrip(true)
+        .build()
+        .expect("Failed to pack obfuscated assets");
+    let meta = std::fs::read_dir(out_path)
+        .expect("Failed to list OUT_DIR")
+        .filter_map(|entry| std::fs::read_to_string(entry.ok()?.path()).ok())
+        .find(|meta| meta.contains("source=obfuscated/plan.txt\n"))
+        .expect("Failed to find the metadata of the obfuscated asset");
+    assert!(meta.contains("symbol=_") && meta.contains("obfuscated=true\n"));
+
+    // Only the files the application uses are packed, and stored as is in debug builds.
+    include_packed::Config::new("filtered")
+        .debug_algorithm(include_packed::Algorithm::Store)
+        .include("filtered/**/*.{txt,json,psd}")
+        .exclude("**/*.psd")
+        .symbol_prefix("test_project_filtered_")
+        .build()
+        .expect("Failed to pack filtered assets");
+    // Their symbols are prefixed, which the macros pick up from the recorded metadata.
+    let meta = std::fs::read_dir(&out_dir)
+        .expect("Failed to list OUT_DIR")
+        .filter_map(|entry| std::fs::read_to_string(entry.ok()?.path()).ok())
+        .find(|meta| meta.contains("source=filtered/notes.txt\n"))
+        .expect("Failed to find the metadata of a filtered asset");
+    assert!(
+        meta.contains("symbol=include_packed_test_project_filtered_"),
+        "{meta}"
+    );
+    assert!(meta.contains("namespace=test_project_filtered\n"), "{meta}");
+
+    // Symbolic links are followed once, skipping cycles and dangling links.
+    include_packed::Config::new("linked")
+        .build()
+        .expect("Failed to pack linked assets");
+    include_packed::Config::new("linked")
+        .follow_symlinks(false)
+        .write_pack(std::path::Path::new(&out_dir).join("unlinked.pack"))
+        .expect("Failed to write pack without links");
+
+    // Cycles of links are only followed once. The cycle lives in OUT_DIR, since Cargo
+    // warns about cycles in the package itself.
+    let cyclic = std::path::Path::new(&out_dir).join("cyclic");
+    std::fs::create_dir_all(&cyclic).expect("Failed to create cyclic directory");
+    std::fs::write(cyclic.join("asset.txt"), "Cyclic asset\n").expect("Failed to write asset");
+    #[cfg(unix)]
+    if std::fs::symlink_metadata(cyclic.join("loop")).is_err() {
+        std::os::unix::fs::symlink(".", cyclic.join("loop")).expect("Failed to create link");
+    }
+    include_packed::Config::new(&cyclic)
+        .write_pack(std::path::Path::new(&out_dir).join("cyclic.pack"))
+        .expect("Failed to write pack with a cycle");
+
+    // Many small assets are linked as a single bundle symbol.
+    include_packed::Config::new("bundled")
+        .bundle(true)
+        .verify_roundtrip(true)
+        .build()
+        .expect("Failed to pack bundled assets");
+
+    // Stored tables are aligned so they can be read as `u32`s in place, some of them
+    // further than the others.
+    include_packed::Config::new("aligned")
+        .algorithm(include_packed::Algorithm::Store)
+        .align(16)
+        .file("aligned/table.bin", |file| file.align(64))
+        .bundle(true)
+        .verify_roundtrip(true)
+        .build()
+        .expect("Failed to pack aligned assets");
+
+    // A compressed copy of the aligned archive, validated after decompression.
+    include_packed::Config::new("archives")
+        .build()
+        .expect("Failed to pack archives");
+
+    // A patch pack with the same assets, overlaid over the embedded ones at runtime.
+    include_packed::Config::new("blobs")
+        .level(19)
+        .write_pack(std::path::Path::new(&out_dir).join("hotfix.pack"))
+        .expect("Failed to write patch pack");
+
+    // Invalid level override patterns are reported before anything is written.
+    assert!(
+        include_packed::Config::new("blobs")
+            .override_level("blobs/[", 1)
+            .write_pack(std::path::Path::new(&out_dir).join("invalid.pack"))
+            .is_err()
+    );
+    // Obfuscated assets have no paths to overlay them by.
+    assert!(
+        include_packed::Config::new("obfuscated")
+            .obfuscate(true)
+            .write_pack(std::path::Path::new(&out_dir).join("obfuscated.pack"))
+            .is_err()
+    );
+
+    // Per-file options are validated like those of the whole configuration.
+    let error = include_packed::Config::new("blobs")
+        .file("blobs/*.txt", |file| file.align(3))
+        .build()
+        .expect_err("3 is not a power of two");
+    assert_eq!(
+        error.to_string(),
+        "A generic build error occurred: alignment 3 is not a power of two"
+    );
+
+    // Text that is not valid in the configured encoding fails the build.
+    let error = include_packed::Config::new("legacy/fr.latin1.txt")
+        .transcode("**", include_packed::Encoding::Utf16Be)
+        .build()
+        .expect_err("an odd number of bytes is not valid UTF-16");
+    assert_eq!(
+        error.to_string(),
+        "Asset 'legacy/fr.latin1.txt' is not valid Utf16Be text"
+    );
+
+    // Unfetched Git LFS pointers fail the build instead of being packed.
+    let error = include_packed::Config::new("lfs")
+        .build()
+        .expect_err("lfs/weights.bin is an LFS pointer");
+    assert_eq!(
+        error.to_string(),
+        "Asset 'lfs/weights.bin' is an unfetched Git LFS pointer; run `git lfs pull` or enable `Config::lfs_pull`"
+    );
+    let error = include_packed::Config::new("lfs")
+        .lfs_pull(true)
+        .build()
+        .expect_err("git lfs pull can't fetch the pointer's contents");
+    assert!(error.to_string().contains("unfetched Git LFS pointer"));
+
+    // Size budgets fail the build and name the offending assets.
+    let error = include_packed::Config::new("blobs")
+        .max_asset_size(16)
+        .build()
+        .expect_err("blobs/file.txt exceeds the size limit");
+    assert_eq!(
+        error.to_string(),
+        "Assets exceed the maximum size of 16 bytes: 'blobs/file.txt' (21 bytes)"
+    );
+    let owned_before_failure = std::fs::read_to_string(out_path.join("include_packed.owned"))
+        .unwrap()
+        .lines()
+        .count();
+    let error = include_packed::Config::new("demo_data")
+        .examples()
+        .max_total_compressed(4)
+        .build()
+        .expect_err("demo data exceeds the budget");
+    assert!(
+        error
+            .to_string()
+            .ends_with("exceeding the budget of 4 bytes; largest: 'demo_data/demo.txt' (19 bytes)")
+    );
+    // The artifacts of failed builds are removed by the next run as well.
+    let owned = std::fs::read_to_string(out_path.join("include_packed.owned")).unwrap();
+    assert!(owned.lines().count() > owned_before_failure, "{owned}");
+
+    let error = include_packed::Config::new("demo_data")
+        .alias("demo", "demo_data/missing.txt")
+        .build()
+        .expect_err("the alias refers to a missing asset");
+    assert_eq!(
+        error.to_string(),
+        "A generic build error occurred: alias 'demo' refers to 'demo_data/missing.txt', which is not packed by this configuration"
+    );
+
+    // Demo data is only linked into examples.
+    include_packed::Config::new("demo_data")
+        .examples()
+        .build()
+        .expect("Failed to pack demo data");
 }
@@ -0,0 +1,8 @@
+use include_packed::include_packed;
+
+fn main() {
+    // This asset is only linked into examples.
+    let demo: Vec<u8> = include_packed!("demo_data/demo.txt");
+    assert_eq!(demo, b"Demo data\n");
+    println!("Example data matches original.");
+}
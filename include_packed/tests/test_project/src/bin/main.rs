@@ -1,6 +1,75 @@
-use include_packed::include_packed;
+use std::{
+    borrow::Cow,
+    future::Future,
+    pin::pin,
+    sync::OnceLock,
+    task::{Context, Poll, Waker},
+};
+
+use include_packed::{
+    Algorithm, CancellationToken, CompressedAsset, Format, PackedAsset, PackedAssetReader,
+    PackedAssets, PackedFiles, PackedProtocol, PackedServeDir, PatchPack, include_packed,
+    include_packed_aligned, include_packed_all, include_packed_archived, include_packed_as,
+    include_packed_asset, include_packed_async, include_packed_bytes, include_packed_compressed,
+    include_packed_cow, include_packed_dir, include_packed_fetch, include_packed_glob,
+    include_packed_info, include_packed_into, include_packed_lazy, include_packed_len,
+    include_packed_manifest, include_packed_opt, include_packed_reader, include_packed_registry,
+    include_packed_shared, include_packed_str, include_packed_try,
+};
+
+// Lets `include_packed::get` look up assets by runtime path.
+include_packed_registry!();
+
+/// A zstd frame that could be served with `Content-Encoding: zstd`.
+static COMPRESSED_FILE: CompressedAsset = include_packed_compressed!("blobs/file.txt");
+
+/// Assets declared by the build script with `Config::generate_module`.
+mod packed_assets {
+    include!(concat!(env!("OUT_DIR"), "/packed_assets.rs"));
+}
+
+/// A world archived with rkyv, accessed in place.
+#[derive(rkyv::Archive, rkyv::Serialize)]
+struct World {
+    name: String,
+    heights: Vec<u32>,
+}
+
+/// Configuration deserialized from a packed JSON file.
+#[derive(Debug, PartialEq, serde::Deserialize)]
+struct Settings {
+    title: String,
+    volume: f32,
+}
+
+/// A level deserialized from a packed postcard file.
+#[derive(Debug, PartialEq, serde::Deserialize)]
+struct Level {
+    id: u32,
+    name: String,
+}
+
+/// The bundled web assets, looked up like with `rust-embed`.
+#[derive(PackedAssets)]
+#[packed(folder = "bundled/")]
+struct WebAssets;
+
+/// The public half of the key the build script signs assets with.
+const SIGNING_PUBLIC_KEY: [u8; 32] = [
+    0xea, 0x4a, 0x6c, 0x63, 0xe2, 0x9c, 0x52, 0x0a, 0xbe, 0xf5, 0x50, 0x7b, 0x13, 0x2e, 0xc5, 0xf9,
+    0x95, 0x47, 0x76, 0xae, 0xbe, 0xbe, 0x7b, 0x92, 0x42, 0x1e, 0xea, 0x69, 0x14, 0x46, 0xd2, 0x2c,
+];
 
 fn main() {
+    // Signed assets are only decompressed once the application trusts their key.
+    assert!(matches!(
+        include_packed_asset!("signed/model.bin").try_decompress(),
+        Err(include_packed::Error::UntrustedSignature {
+            path: "signed/model.bin"
+        })
+    ));
+    include_packed::set_trusted_key(SIGNING_PUBLIC_KEY);
+
     let original_content = "Contents of file.txt\n";
 
     // Macro returns a Vec<u8> with the decompressed data.
@@ -12,5 +81,963 @@ fn main() {
     assert_eq!(s, original_content);
     assert_eq!(data_vec.as_slice(), original_content.as_bytes());
 
+    // Text assets are validated as UTF-8 when they are packed.
+    // Paths may be built from the build environment, also through `macro_rules!`.
+    macro_rules! blob {
+        ($path:expr) => {
+            include_packed!(concat!(env!("TEST_BLOBS_DIR"), "/", $path))
+        };
+    }
+    let from_env: Vec<u8> = blob!("file.txt");
+    assert_eq!(from_env, data_vec);
+
+    // Assets are deserialized in the format named by their extension, or given.
+    let settings = include_packed_as!("config/defaults.json", Settings);
+    assert_eq!(
+        settings,
+        Settings {
+            title: "Packed".to_owned(),
+            volume: 0.5
+        }
+    );
+    let level = include_packed_as!("config/level.postcard", Level, postcard);
+    assert_eq!(
+        level,
+        Level {
+            id: 7,
+            name: "intro".to_owned()
+        }
+    );
+    assert!(matches!(
+        include_packed_asset!("config/defaults.json").try_deserialize::<Level>(Format::Json),
+        Err(include_packed::Error::Deserialize {
+            path: "config/defaults.json",
+            ..
+        })
+    ));
+
+    // Transformed assets hold the rewritten contents.
+    assert_eq!(
+        include_packed!("transformed/notes.txt"),
+        b"line one\nline two\n"
+    );
+    assert_eq!(include_packed_len!("transformed/notes.txt"), 18);
+
+    // Images are packed in the format they were re-encoded to, at the reduced size.
+    assert!(include_packed!("images/sprite.png").starts_with(b"qoif"));
+
+    // Files in already compressed formats are stored instead of compressed again.
+    assert_eq!(
+        include_packed_bytes!("stored/sprite.png").as_slice(),
+        std::fs::read("stored/sprite.png").unwrap()
+    );
+    // Compressed data is included as linked, without decompressing it.
+    assert_eq!(COMPRESSED_FILE.content_encoding(), Some("zstd"));
+    assert_eq!(COMPRESSED_FILE.len(), include_packed_len!("blobs/file.txt"));
+    assert_eq!(COMPRESSED_FILE.info().path(), "blobs/file.txt");
+    assert_eq!(
+        include_packed::runtime::decompress(COMPRESSED_FILE.data()),
+        include_packed!("blobs/file.txt")
+    );
+    let sprite = include_packed_compressed!("stored/sprite.png");
+    assert_eq!(sprite.content_encoding(), None);
+    assert_eq!(sprite.data(), include_packed_bytes!("stored/sprite.png"));
+    let level = include_packed_info!("stored/level.dat");
+    assert_eq!(level.algorithm(), Algorithm::Store);
+    assert_eq!(level.compressed_len(), level.len());
+    // Obfuscated assets don't embed their paths.
+    let plan = include_packed_asset!("obfuscated/plan.txt");
+    assert_eq!(plan.path(), "");
+    assert_eq!(plan.decompress_string(), "Attack at dawn.\n");
+    assert_eq!(include_packed_info!("obfuscated/plan.txt").path(), "");
+    let large = include_packed!("images/large.png");
+    assert!(large.starts_with(b"\x89PNG"));
+    assert_eq!(large[16..24], [0, 0, 0, 2, 0, 0, 0, 2]);
+
+    // Several assets can be included at once, in the given order.
+    let [first, second]: [Vec<u8>; 2] =
+        include_packed_all!("blobs/file.txt", concat!("blobs", "/nested/inner.txt"),);
+    assert_eq!(first, data_vec);
+    assert_eq!(second, b"Nested asset\n");
+
+    // Optional assets are `None` when missing or excluded from packing.
+    assert_eq!(
+        include_packed_opt!("blobs/file.txt"),
+        Some(data_vec.clone())
+    );
+    assert_eq!(include_packed_opt!("blobs/branding.png"), None);
+    assert_eq!(include_packed_opt!("filtered/art/logo.psd"), None);
+
+    let text: String = include_packed_str!("blobs/file.txt");
+    assert_eq!(text, original_content);
+
+    // Patches decompress their base first.
+    assert_eq!(
+        include_packed!("tiers/low.bin"),
+        include_bytes!("../../tiers/low.bin")
+    );
+    let low = include_packed_asset!("tiers/low.bin");
+    let base = low.delta_base().expect("tiers/low.bin is a patch");
+    assert_eq!(base.decompress(), include_bytes!("../../tiers/high.bin"));
+    assert!(
+        include_packed_asset!("tiers/high.bin")
+            .delta_base()
+            .is_none()
+    );
+
+    let variant = include_packed_asset!("patches/variant.bin");
+    let expected = include_bytes!("../../patches/variant.bin");
+    assert_eq!(variant.decompress(), expected);
+    let mut streamed = Vec::new();
+    std::io::Read::read_to_end(&mut variant.reader(), &mut streamed)
+        .expect("failed to stream patch");
+    assert_eq!(streamed, expected);
+    assert_eq!(variant.decompress_range(4000..9000), &expected[4000..9000]);
+
+    // Assets packed with automatically chosen algorithms decompress as usual.
+    assert_eq!(
+        include_packed_str!("auto/notes.txt"),
+        include_str!("../../auto/notes.txt")
+    );
+    assert_eq!(
+        include_packed!("auto/noise.bin"),
+        include_bytes!("../../auto/noise.bin")
+    );
+
+    // Remote files are addressed by the last segment of their URL's path.
+    assert_eq!(include_packed!("model.bin"), b"remote model weights\n");
+
+    // Members of archives are addressed below the archive's path.
+    assert_eq!(
+        include_packed_str!("packed_archives/art.zip/textures/hero.txt"),
+        "hero sprite\n".repeat(20)
+    );
+    assert_eq!(
+        include_packed_str!("packed_archives/art.zip/credits.txt"),
+        "Art by the art team\n"
+    );
+    assert_eq!(
+        include_packed!("packed_archives/levels.tar.gz/levels/1.txt"),
+        b"level one\n"
+    );
+    assert_eq!(
+        include_packed_opt!("packed_archives/art.zip/missing.txt"),
+        None
+    );
+
+    // Legacy encodings are converted to UTF-8 at build time.
+    assert_eq!(
+        include_packed_str!("legacy/de.utf16.txt"),
+        "Grüße aus Köln\n"
+    );
+    assert_eq!(
+        include_packed_str!("legacy/fr.latin1.txt"),
+        "Ça coûte très cher\n"
+    );
+
+    // The reader variant streams the decompressed data.
+    let mut streamed = String::new();
+    std::io::Read::read_to_string(&mut include_packed_reader!("blobs/file.txt"), &mut streamed)
+        .expect("failed to stream asset");
+    assert_eq!(streamed, original_content);
+
+    // Assets can be decompressed into reused buffers and writers.
+    let mut scratch = Vec::with_capacity(1024);
+    assert_eq!(include_packed_into!("blobs/file.txt", &mut scratch), 21);
+    assert_eq!(scratch, original_content.as_bytes());
+    assert_eq!(
+        include_packed_into!("blobs/nested/inner.txt", &mut scratch),
+        13
+    );
+    assert_eq!(scratch, b"Nested asset\n");
+    assert!(scratch.capacity() >= 1024);
+    let mut frame = [0; 32];
+    let len = include_packed_into!("blobs/file.txt", &mut frame);
+    assert_eq!(&frame[..len], original_content.as_bytes());
+    let asset = include_packed_asset!("blobs/file.txt");
+    assert!(matches!(
+        asset.try_decompress_into(&mut [0; 4][..]),
+        Err(include_packed::Error::BufferTooSmall { capacity: 4, .. })
+    ));
+    let mut written = Vec::new();
+    assert_eq!(asset.decompress_to_writer(&mut written).ok(), Some(21));
+    assert_eq!(written, original_content.as_bytes());
+
+    // Async code decompresses on tokio's blocking thread pool.
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("failed to start tokio");
+    let decompressed = runtime.block_on(include_packed_async!("blobs/file.txt"));
+    assert_eq!(decompressed, original_content.as_bytes());
+    let tried = runtime.block_on(include_packed_asset!("blobs/file.txt").try_decompress_async());
+    assert_eq!(tried.ok(), Some(decompressed));
+
+    // Whole directories expand to a lookup by relative path.
+    let dir = include_packed_dir!("blobs");
+    assert_eq!(dir.paths(), ["file.txt", "nested/inner.txt"]);
+    assert_eq!(dir.decompress("file.txt"), Some(data_vec.clone()));
+    assert_eq!(
+        dir.get("nested/inner.txt").map(|asset| asset.path()),
+        Some("blobs/nested/inner.txt")
+    );
+    assert_eq!(
+        dir.decompress("nested/inner.txt").as_deref(),
+        Some(&b"Nested asset\n"[..])
+    );
+    assert!(dir.get("missing.txt").is_none());
+
+    // Files skipped by the build script's include and exclude patterns aren't listed.
+    let filtered = include_packed_dir!("filtered");
+    assert_eq!(filtered.paths(), ["data.json", "notes.txt"]);
+    let filtered_glob: [(&str, Vec<u8>); 1] = include_packed_glob!("filtered/*.t*");
+    assert_eq!(
+        filtered_glob[0],
+        ("filtered/notes.txt", b"Kept notes\n".to_vec())
+    );
+    // Debug builds store them without compression.
+    assert_eq!(
+        include_packed_asset!("filtered/notes.txt").as_stored(),
+        Some(&b"Kept notes\n"[..])
+    );
+
+    // Linked directories are listed under each name, while cycles are cut short.
+    let linked = include_packed_dir!("linked");
+    assert_eq!(linked.paths(), ["alias/asset.txt", "data/asset.txt"]);
+    assert_eq!(
+        linked.decompress("alias/asset.txt"),
+        linked.decompress("data/asset.txt")
+    );
+    let unlinked =
+        PatchPack::open(concat!(env!("OUT_DIR"), "/unlinked.pack")).expect("failed to open pack");
+    assert_eq!(
+        unlinked.paths().collect::<Vec<_>>(),
+        ["linked/data/asset.txt"]
+    );
+    let cyclic =
+        PatchPack::open(concat!(env!("OUT_DIR"), "/cyclic.pack")).expect("failed to open pack");
+    let cyclic_paths = cyclic.paths().collect::<Vec<_>>();
+    assert_eq!(cyclic_paths.len(), 1);
+    assert!(cyclic_paths[0].ends_with("/cyclic/asset.txt"));
+
+    // Derived lookups behave like `rust-embed`'s.
+    assert_eq!(
+        WebAssets::iter().collect::<Vec<_>>(),
+        [
+            "icons/check.svg",
+            "icons/tick.svg",
+            "index.html",
+            "style.css"
+        ]
+    );
+    assert_eq!(
+        WebAssets::get("style.css").as_deref(),
+        Some(include_bytes!("../../bundled/style.css").as_slice())
+    );
+    assert!(WebAssets::get("missing.css").is_none());
+    assert_eq!(WebAssets::dir().len(), 4);
+
+    // Packed directories can be served over HTTP with conditional requests.
+    let service = PackedServeDir::new(WebAssets::dir());
+    let get = |uri: &str| http::Request::get(uri).body(()).expect("invalid request");
+    let response = service.respond(&get("/"));
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(response.headers()[http::header::CONTENT_TYPE], "text/html");
+    let etag = response.headers()[http::header::ETAG].clone();
+    let body = pin!(http_body_util::BodyExt::collect(response.into_body()));
+    match body.poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(Ok(body)) => {
+            assert_eq!(
+                body.to_bytes(),
+                &include_bytes!("../../bundled/index.html")[..]
+            );
+        }
+        _ => panic!("response body was not ready"),
+    }
+    let conditional = http::Request::get("/index.html")
+        .header(http::header::IF_NONE_MATCH, etag)
+        .body(())
+        .expect("invalid request");
+    assert_eq!(
+        service.respond(&conditional).status(),
+        http::StatusCode::NOT_MODIFIED
+    );
+    let svg = service.respond(&get("/icons%2Fcheck.svg"));
+    assert_eq!(svg.headers()[http::header::CONTENT_TYPE], "image/svg+xml");
+    let head = http::Request::head("/style.css")
+        .body(())
+        .expect("invalid request");
+    let head = service.respond(&head);
+    assert_eq!(
+        head.headers()[http::header::CONTENT_LENGTH],
+        include_bytes!("../../bundled/style.css").len().to_string()
+    );
+    assert_eq!(
+        service.respond(&get("/missing.css")).status(),
+        http::StatusCode::NOT_FOUND
+    );
+    let post = http::Request::post("/style.css")
+        .body(())
+        .expect("invalid request");
+    assert_eq!(
+        service.respond(&post).status(),
+        http::StatusCode::METHOD_NOT_ALLOWED
+    );
+
+    // Tauri apps can serve packed frontends, falling back to `index.html` for routes.
+    let protocol = PackedProtocol::new(WebAssets::dir());
+    let index = include_bytes!("../../bundled/index.html");
+    let response = protocol.respond(&get("app://localhost/settings/profile"));
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(response.headers()[http::header::CONTENT_TYPE], "text/html");
+    assert_eq!(response.body(), index);
+    let response = protocol.respond(&get("app://localhost/style.css"));
+    assert_eq!(response.headers()[http::header::CONTENT_TYPE], "text/css");
+    assert_eq!(
+        protocol
+            .respond(&get("app://localhost/missing.js"))
+            .status(),
+        http::StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        protocol
+            .fallback(None)
+            .respond(&get("app://localhost/settings"))
+            .status(),
+        http::StatusCode::NOT_FOUND
+    );
+
+    // Actix apps can mount packed directories and respond with packed assets.
+    let app = pin!(actix_web::test::init_service(
+        actix_web::App::new()
+            .service(PackedFiles::new("/static", WebAssets::dir()))
+            .route(
+                "/style.css",
+                actix_web::web::get().to(|| async { include_packed_asset!("bundled/style.css") }),
+            )
+    ));
+    let Poll::Ready(app) = app.poll(&mut Context::from_waker(Waker::noop())) else {
+        panic!("actix app was not ready");
+    };
+    let call = |uri: &str| {
+        let request = actix_web::test::TestRequest::get().uri(uri).to_request();
+        let response = pin!(actix_web::test::call_service(&app, request));
+        match response.poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(response) => response,
+            Poll::Pending => panic!("actix response was not ready"),
+        }
+    };
+    let response = call("/static/icons/check.svg");
+    assert_eq!(response.status(), actix_web::http::StatusCode::OK);
+    assert_eq!(
+        response
+            .headers()
+            .get(actix_web::http::header::CONTENT_TYPE)
+            .expect("missing content type"),
+        "image/svg+xml"
+    );
+    assert_eq!(
+        call("/static/")
+            .headers()
+            .get(actix_web::http::header::CONTENT_TYPE)
+            .expect("missing content type"),
+        "text/html"
+    );
+    assert_eq!(
+        call("/static/missing.css").status(),
+        actix_web::http::StatusCode::NOT_FOUND
+    );
+    let response = call("/style.css");
+    assert_eq!(
+        response
+            .headers()
+            .get(actix_web::http::header::CONTENT_TYPE)
+            .expect("missing content type"),
+        "text/css"
+    );
+    let body = pin!(actix_web::test::read_body(response));
+    match body.poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(body) => assert_eq!(body, &include_bytes!("../../bundled/style.css")[..]),
+        Poll::Pending => panic!("actix response body was not ready"),
+    }
+
+    // Bevy's asset server can load assets from packed directories.
+    let reader = PackedAssetReader::new(WebAssets::dir());
+    let css = futures_lite::future::block_on(async {
+        let mut css = Vec::new();
+        let mut asset = bevy_asset::io::AssetReader::read(&reader, "style.css".as_ref()).await?;
+        bevy_asset::AsyncReadExt::read_to_end(&mut asset, &mut css).await?;
+        Ok::<_, bevy_asset::io::AssetReaderError>(css)
+    })
+    .expect("failed to read style.css");
+    assert_eq!(css, include_bytes!("../../bundled/style.css"));
+    let root = futures_lite::future::block_on(async {
+        let entries = bevy_asset::io::AssetReader::read_directory(&reader, "".as_ref()).await?;
+        Ok::<_, bevy_asset::io::AssetReaderError>(
+            futures_lite::StreamExt::collect::<Vec<_>>(entries).await,
+        )
+    })
+    .expect("failed to list the root directory");
+    assert_eq!(
+        root,
+        [
+            std::path::Path::new("icons"),
+            "index.html".as_ref(),
+            "style.css".as_ref()
+        ]
+    );
+    futures_lite::future::block_on(async {
+        assert!(
+            bevy_asset::io::AssetReader::is_directory(&reader, "icons".as_ref())
+                .await
+                .expect("failed to check the icons directory")
+        );
+        assert!(
+            bevy_asset::io::AssetReader::read_meta(&reader, "style.css".as_ref())
+                .await
+                .is_err()
+        );
+    });
+
+    // Glob patterns expand to every matching file.
+    let globbed: [(&str, Vec<u8>); 2] = include_packed_glob!("blobs/**/*.txt");
+    assert_eq!(globbed[0], ("blobs/file.txt", data_vec.clone()));
+    assert_eq!(
+        globbed[1],
+        ("blobs/nested/inner.txt", b"Nested asset\n".to_vec())
+    );
+
+    // The decoder matching each asset's compression algorithm is picked automatically.
+    for (asset, algorithm, expected) in [
+        (
+            include_packed_asset!("algorithms/lz4.txt"),
+            Algorithm::Lz4,
+            "Packed with lz4\n",
+        ),
+        (
+            include_packed_asset!("algorithms/brotli.txt"),
+            Algorithm::Brotli,
+            "Packed with brotli\n",
+        ),
+        (
+            include_packed_asset!("algorithms/store.txt"),
+            Algorithm::Store,
+            "Packed with store\n",
+        ),
+    ] {
+        assert_eq!(asset.algorithm(), algorithm);
+        assert_eq!(asset.decompress(), expected.as_bytes());
+        assert_eq!(asset.decompress_string(), expected);
+        assert_eq!(
+            &*asset.decompress_to_mmap().expect("failed to map asset"),
+            expected.as_bytes()
+        );
+        let raw = unsafe { PackedAsset::from_raw(asset.into_raw()) };
+        assert_eq!(raw.decompress(), expected.as_bytes());
+    }
+    // Stored assets are available without decompression or allocation.
+    let stored: &'static [u8] = include_packed_bytes!("algorithms/store.txt");
+    assert_eq!(stored, b"Packed with store\n");
+    let stored_asset = include_packed_asset!("algorithms/store.txt");
+    assert_eq!(stored_asset.as_stored(), Some(stored));
+    assert_eq!(include_packed_asset!("blobs/file.txt").as_stored(), None);
+
+    // `Bytes` buffers share stored data instead of copying it.
+    let shared = include_packed_shared!("blobs/file.txt");
+    assert_eq!(shared.clone(), original_content.as_bytes());
+    let stored = PackedAsset::from_compressed(stored).with_algorithm(Algorithm::Store);
+    assert_eq!(
+        Some(stored.decompress_bytes().as_ptr()),
+        stored.as_stored().map(<[u8]>::as_ptr)
+    );
+
+    // Stored assets are borrowed and compressed ones decompressed into the same type.
+    assert!(matches!(
+        stored.decompress_cow(),
+        Cow::Borrowed(b"Packed with store\n")
+    ));
+    let compressed = include_packed_cow!("blobs/file.txt");
+    assert!(matches!(&compressed, Cow::Owned(data) if data == original_content.as_bytes()));
+    assert_eq!(
+        include_packed_asset!("blobs/file.txt").algorithm(),
+        Algorithm::Zstd
+    );
+
+    // Aliases registered by the build script name assets in place of their paths.
+    assert_eq!(
+        include_packed_str!(alias = "default_locale"),
+        include_packed_str!("translations/en.json")
+    );
+
+    // Assets packed with a trained dictionary carry it along.
+    let translations = include_packed_dir!("translations");
+    assert_eq!(translations.len(), 16);
+    for (path, asset) in translations.iter() {
+        assert!(!asset.dictionary().is_empty());
+        let text = asset.decompress_string();
+        assert!(text.contains(&format!(
+            "\"locale\": \"{}\"",
+            path.trim_end_matches(".json")
+        )));
+        let mut streamed = Vec::new();
+        std::io::Read::read_to_end(&mut asset.reader(), &mut streamed).expect("failed to stream");
+        assert_eq!(streamed, text.as_bytes());
+        assert_eq!(
+            &*asset.decompress_to_mmap().expect("failed to map asset"),
+            text.as_bytes()
+        );
+    }
+    assert!(
+        include_packed_asset!("blobs/file.txt")
+            .dictionary()
+            .is_empty()
+    );
+
+    // The lazy variant decompresses once and hands out the cached bytes.
+    // Pre-warming decompresses every registered asset into the cache it shares with
+    // `PackedAsset::cached`.
+    include_packed::prewarm();
+    let cached = || -> &'static [u8] { include_packed_lazy!("blobs/file.txt") };
+    assert_eq!(cached(), data_vec.as_slice());
+    assert!(std::ptr::eq(cached(), cached()));
+    assert!(std::ptr::eq(
+        cached(),
+        include_packed_asset!("blobs/file.txt").cached()
+    ));
+
+    // The asset handle decompresses on demand and supports cancellation.
+    let asset: PackedAsset = include_packed_asset!("blobs/file.txt");
+    assert_eq!(asset.decompress(), data_vec);
+    assert_eq!(asset.path(), "blobs/file.txt");
+
+    // Build provenance is recorded for assets packed with `Config::provenance`.
+    let provenance = asset.provenance().expect("provenance was not recorded");
+    assert_eq!(provenance.crate_name(), "test_project");
+    assert_eq!(provenance.crate_version(), env!("CARGO_PKG_VERSION"));
+    assert!(provenance.build_timestamp() > 0);
+
+    let token = CancellationToken::new();
+    assert_eq!(asset.decompress_cancellable(&token), Ok(data_vec.clone()));
+    token.clone().cancel();
+    assert!(asset.decompress_cancellable(&token).is_err());
+
+    // Decompressing into an anonymous memory map yields the same bytes.
+    let map = include_packed::MmapOptions::new()
+        .huge_pages(true)
+        .sequential(true)
+        .decompress(&asset)
+        .expect("failed to map asset");
+    assert_eq!(&*map, data_vec.as_slice());
+
+    // Assets packed by a library's build script are linked into its dependents.
+    assert_eq!(test_library::greeting(), b"Hello from a library\n");
+
+    // The library's assets are shared with dependents, which include them without
+    // packing them again.
+    let greeting: Vec<u8> = include_packed!("assets/greeting.txt");
+    assert_eq!(greeting, b"Hello from a library\n");
+    assert_eq!(include_packed_dir!("assets").paths(), ["greeting.txt"]);
+
+    // Assets outside the crate root resolve the same way as those inside it.
+    let shared: Vec<u8> = include_packed!("../shared_blobs/shared.txt");
+    assert_eq!(shared, b"Shared between crates\n");
+
+    // Assets packed below `Config::root` are included by their path relative to it.
+    assert_eq!(
+        include_packed_str!("rooted/motd.txt"),
+        "Resolved relative to a root\n"
+    );
+
+    // Generated modules declare a handle for each asset.
+    assert_eq!(
+        packed_assets::ROOTED_MOTD_TXT.decompress(),
+        b"Resolved relative to a root\n"
+    );
+    assert_eq!(
+        packed_assets::ALGORITHMS_LZ4_TXT.path(),
+        "algorithms/lz4.txt"
+    );
+    assert_eq!(
+        packed_assets::ALGORITHMS_STORE_TXT.algorithm(),
+        Algorithm::Store
+    );
+    // ...and an enum of all of them, so new assets are flagged by exhaustive matches.
+    for asset in packed_assets::PackedAsset::ALL {
+        let expected = match asset {
+            packed_assets::PackedAsset::RootedMotdTxt => b"Resolved relative to a root\n".to_vec(),
+            packed_assets::PackedAsset::AlgorithmsLz4Txt
+            | packed_assets::PackedAsset::AlgorithmsBrotliTxt
+            | packed_assets::PackedAsset::AlgorithmsStoreTxt => {
+                std::fs::read(asset.path()).expect("failed to read asset")
+            }
+        };
+        assert_eq!(asset.bytes(), expected, "{}", asset.path());
+    }
+
+    // Patch packs overlay embedded assets by logical path.
+    static PATCHES: OnceLock<PatchPack> = OnceLock::new();
+    let patches = PATCHES.get_or_init(|| {
+        PatchPack::open(concat!(env!("OUT_DIR"), "/hotfix.pack")).expect("failed to open pack")
+    });
+    let mut patched_paths = patches.paths().collect::<Vec<_>>();
+    patched_paths.sort_unstable();
+    assert_eq!(patched_paths, ["blobs/file.txt", "blobs/nested/inner.txt"]);
+    let patched = patches.overlay(asset);
+    assert_ne!(patched.into_raw().compressed, asset.into_raw().compressed);
+    assert_eq!(patched.path(), asset.path());
+    assert_eq!(patched.decompress(), data_vec);
+    let unpatched = include_packed_asset!("../shared_blobs/shared.txt");
+    assert_eq!(patches.overlay(unpatched).decompress(), shared);
+    assert!(PatchPack::from_bytes(b"IPPACK01\x01\0\0\0".to_vec()).is_err());
+    // Entries without a path would match every obfuscated asset.
+    assert!(PatchPack::from_bytes(b"IPPACK01\x01\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0".to_vec()).is_err());
+    assert_eq!(patches.overlay(plan).decompress(), plan.decompress());
+
+    // The build report lists the compression results of every asset.
+    let report = include_str!(concat!(env!("OUT_DIR"), "/report.json"));
+    assert!(report.starts_with("{\"assets\":[{\"path\":\"blobs/file.txt\",\"size\":21,"));
+    assert!(report.contains("\"path\":\"blobs/nested/inner.txt\""));
+    assert!(report.contains("\"level\":19,"));
+    assert!(report.contains("\"time_ms\":"));
+    // Debug builds use the debug level instead of the default one.
+    assert!(report.contains("\"level\":3,"));
+    assert!(!report.contains("\"level\":5,"));
+    // `INCLUDE_PACKED_LEVEL_DEBUG` overrode the debug level of the configuration.
+    let report = include_str!(concat!(env!("OUT_DIR"), "/config-report.json"));
+    assert!(report.contains("\"level\":2,"));
+    assert!(!report.contains("\"level\":19,"));
+
+    // The original size is available at compile time.
+    const FILE_LEN: usize = include_packed_len!("blobs/file.txt");
+    const _: () = assert!(FILE_LEN == 21);
+    let buffer = [0u8; include_packed_len!("algorithms/lz4.txt")];
+    assert_eq!(buffer.len(), include_packed!("algorithms/lz4.txt").len());
+
+    // Metadata for serving assets is recorded at build time.
+    const INFO: include_packed::AssetInfo = include_packed_info!("blobs/file.txt");
+    assert_eq!(INFO.path(), "blobs/file.txt");
+    assert_eq!(INFO.len(), FILE_LEN);
+    assert!(INFO.compressed_len() > 0);
+    assert_eq!(INFO.mime_type(), Some("text/plain"));
+    assert!(INFO.modified().is_some_and(|secs| secs > 0));
+    assert_eq!(
+        include_packed_info!("translations/en.json").mime_type(),
+        Some("application/json")
+    );
+
+    // The non-panicking APIs report corrupt data as errors.
+    assert_eq!(
+        include_packed_try!("blobs/file.txt").ok(),
+        Some(data_vec.clone())
+    );
+    let corrupt = PackedAsset::from_compressed(b"not a zstd frame").with_path("corrupt.bin");
+    let err = corrupt
+        .try_decompress()
+        .expect_err("corrupt data decompressed");
+    assert!(matches!(err, include_packed::Error::Corrupt { .. }));
+    assert_eq!(err.path(), "corrupt.bin");
+    assert!(corrupt.try_decompress_string().is_err());
+    let binary = PackedAsset::from_compressed(b"\xff\xfe").with_algorithm(Algorithm::Store);
+    assert!(matches!(
+        binary.try_decompress_string(),
+        Err(include_packed::Error::InvalidUtf8 { .. })
+    ));
+    assert!(include_packed::runtime::try_decompress(b"not a zstd frame").is_err());
+
+    // Debug builds read passthrough assets from disk, falling back to the embedded data.
+    let lz4 = include_packed_asset!("algorithms/lz4.txt");
+    assert_eq!(lz4.passthrough().is_some(), cfg!(debug_assertions));
+    let edited = std::env::temp_dir().join("include_packed_passthrough.txt");
+    std::fs::write(&edited, "edited on disk\n").expect("failed to write edited asset");
+    let edited: &'static str = edited
+        .to_str()
+        .expect("non-UTF-8 temp dir")
+        .to_owned()
+        .leak();
+    assert_eq!(
+        lz4.with_passthrough(edited).decompress(),
+        b"edited on disk\n"
+    );
+    assert_eq!(
+        lz4.with_passthrough(edited).decompress_range(0..6),
+        b"edited"
+    );
+    // Ranges are checked against the edited file, not the embedded data.
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let out_of_bounds =
+        std::panic::catch_unwind(|| lz4.with_passthrough(edited).decompress_range(10..16));
+    std::panic::set_hook(hook);
+    assert!(out_of_bounds.is_err());
+    let _ = std::fs::remove_file(edited);
+    assert_eq!(
+        lz4.with_passthrough("/nonexistent/lz4.txt").decompress(),
+        include_packed!("algorithms/lz4.txt")
+    );
+
+    // Watched assets are reported with their fresh contents as soon as they change.
+    let watched = std::env::temp_dir().join(format!("include_packed_watch_{}", std::process::id()));
+    std::fs::create_dir_all(&watched).expect("failed to create watched directory");
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let watcher = include_packed::watch(&watched, move |path, data| {
+        let _ = sender.send((path.to_owned(), data));
+    })
+    .expect("failed to watch assets");
+    std::fs::write(watched.join("theme.css"), "body { color: red; }\n")
+        .expect("failed to write watched asset");
+    let (path, data) = std::iter::from_fn(|| {
+        receiver
+            .recv_timeout(std::time::Duration::from_secs(10))
+            .ok()
+    })
+    .find(|(_, data)| !data.is_empty())
+    .expect("no change was reported");
+    assert!(path.ends_with("/theme.css"));
+    assert_eq!(data, b"body { color: red; }\n");
+    drop(watcher);
+    let _ = std::fs::remove_dir_all(&watched);
+    assert!(include_packed::watch("/nonexistent/assets", |_, _| {}).is_err());
+
+    // Ranges of chunked assets only decompress the chunks they overlap.
+    let tiles = include_packed_asset!("tiles/grid.txt");
+    let grid = include_bytes!("../../tiles/grid.txt");
+    assert_eq!(tiles.decompress(), grid);
+    assert_eq!(tiles.decompress_range(1020..1030), &grid[1020..1030]);
+    assert_eq!(tiles.decompress_range(0..10), b"tile 0000\n");
+    assert_eq!(tiles.decompress_range(9990..10000), b"tile 0999\n");
+    assert!(tiles.decompress_range(5000..5000).is_empty());
+    assert_eq!(
+        tiles.try_decompress_range(2048..4096).ok().as_deref(),
+        Some(&grid[2048..4096])
+    );
+    assert_eq!(
+        tiles.decompress_to_mmap().expect("failed to map tiles")[..],
+        grid[..]
+    );
+    let mut streamed = Vec::new();
+    std::io::Read::read_to_end(&mut tiles.reader(), &mut streamed).expect("failed to read tiles");
+    assert_eq!(streamed, grid);
+    assert_eq!(
+        include_packed_asset!("algorithms/store.txt").decompress_range(0..4),
+        &include_packed_bytes!("algorithms/store.txt")[..4]
+    );
+
+    // Assets packed with checksums are verified on every decompression.
+    assert!(include_packed_asset!("blobs/file.txt").checksum().is_some());
+    let tampered = PackedAsset::from_compressed(b"tampered")
+        .with_path("tampered.txt")
+        .with_algorithm(Algorithm::Store)
+        .with_checksum(&[0; 32]);
+    assert!(matches!(
+        tampered.try_decompress(),
+        Err(include_packed::Error::ChecksumMismatch {
+            path: "tampered.txt"
+        })
+    ));
+    let mut sink = Vec::new();
+    let err = std::io::Read::read_to_end(&mut tampered.reader(), &mut sink)
+        .expect_err("tampered data was read without error");
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    // Signed assets are verified against the key the application trusts, and can't be
+    // swapped.
+    let model = include_packed_asset!("signed/model.bin");
+    let signature = model.signature().expect("model is not signed");
+    assert_eq!(*signature.public_key(), SIGNING_PUBLIC_KEY);
+    assert!(model.checksum().is_some());
+    assert_eq!(model.decompress().len(), 4096);
+    let forged: &'static _ = Box::leak(Box::new(include_packed::runtime::Signature::new(
+        *signature.public_key(),
+        [0; 64],
+    )));
+    assert!(matches!(
+        model.with_signature(forged).try_decompress(),
+        Err(include_packed::Error::SignatureMismatch {
+            path: "signed/model.bin"
+        })
+    ));
+    let err = std::io::Read::read_to_end(&mut model.with_signature(forged).reader(), &mut sink)
+        .expect_err("data with a forged signature was read without error");
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    // Signatures by any other key are rejected, whatever key is recorded next to them.
+    include_packed::set_trusted_key([0x11; 32]);
+    assert!(matches!(
+        model.try_decompress(),
+        Err(include_packed::Error::SignatureMismatch {
+            path: "signed/model.bin"
+        })
+    ));
+    include_packed::set_trusted_key(SIGNING_PUBLIC_KEY);
+
+    // Off Wasm, fetched assets are embedded and their futures complete immediately.
+    let fetch = pin!(include_packed_fetch!("translations/en.json"));
+    match fetch.poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(Ok(data)) => {
+            assert_eq!(data, include_packed_str!("translations/en.json").as_bytes());
+        }
+        Poll::Ready(Err(err)) => panic!("failed to fetch asset: {err}"),
+        Poll::Pending => panic!("embedded asset was not ready"),
+    }
+
+    // Encrypted assets need the key that the build script encrypted them with.
+    let weights = include_packed_asset!("secrets/weights.txt");
+    assert!(matches!(
+        weights.try_decompress(),
+        Err(include_packed::Error::MissingKey { .. })
+    ));
+    assert!(matches!(
+        include_packed::unlock(&[0x2a; 16]),
+        Err(include_packed::Error::InvalidKeyLength { len: 16 })
+    ));
+    include_packed::unlock(&[0; 32]).expect("a 32-byte key is valid");
+    assert!(matches!(
+        include_packed_try!("secrets/weights.txt"),
+        Err(include_packed::Error::WrongKey { .. })
+    ));
+    include_packed::set_key([0x2a; 32]);
+    assert_eq!(
+        include_packed_str!("secrets/weights.txt"),
+        "Proprietary model weights: 0.25 0.5 0.125\n"
+    );
+    assert!(weights.as_stored().is_none());
+    let mut reader = include_packed_reader!("secrets/weights.txt");
+    let mut streamed = String::new();
+    std::io::Read::read_to_string(&mut reader, &mut streamed).expect("failed to read secret");
+    assert_eq!(streamed, weights.decompress_string());
+
+    // Bundled assets are slices of one symbol, laid out in packing order.
+    let check = include_packed_asset!("bundled/icons/check.svg");
+    let index = include_packed_asset!("bundled/index.html");
+    let style = include_packed_asset!("bundled/style.css");
+    assert_eq!(
+        check.decompress(),
+        include_bytes!("../../bundled/icons/check.svg")
+    );
+    assert_eq!(
+        index.decompress(),
+        include_bytes!("../../bundled/index.html")
+    );
+    assert_eq!(
+        include_packed_str!("bundled/style.css"),
+        include_str!("../../bundled/style.css")
+    );
+    // The identical icon shares the data of the first one, taking no space of its own.
+    let tick = include_packed_asset!("bundled/icons/tick.svg");
+    assert_eq!(tick.decompress(), check.decompress());
+    let tick = tick.into_raw();
+    let (check, index, style) = (check.into_raw(), index.into_raw(), style.into_raw());
+    assert_eq!(tick.compressed, check.compressed);
+    assert_eq!(
+        check.compressed.wrapping_add(check.compressed_len),
+        index.compressed
+    );
+    assert_eq!(
+        index.compressed.wrapping_add(index.compressed_len),
+        style.compressed
+    );
+
+    // Identical files are linked once, under a single symbol.
+    let en = include_packed_asset!("duplicates/en/ok.txt");
+    let fr = include_packed_asset!("duplicates/fr/ok.txt");
+    assert_eq!(en.decompress_string(), "OK\n");
+    assert_eq!(fr.decompress_string(), "OK\n");
+    let extra = include_packed_asset!("extra/ok.txt");
+    assert_eq!(extra.decompress_string(), "OK\n");
+    assert_eq!(en.into_raw().compressed, fr.into_raw().compressed);
+    assert_eq!(en.into_raw().compressed, extra.into_raw().compressed);
+
+    // Aligned stored tables can be read in place, even when bundled after an asset
+    // whose size isn't a multiple of the alignment.
+    let odd = include_packed_asset!("aligned/odd.bin");
+    let table = include_packed_asset!("aligned/table.bin");
+    assert_eq!(odd.as_stored(), Some(&b"abc"[..]));
+    assert_eq!((odd.align(), table.align()), (16, 64));
+    let stored = table.as_stored().expect("table is not stored");
+    assert!(stored.as_ptr().addr().is_multiple_of(64));
+    let squares = stored
+        .chunks_exact(4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().expect("chunk of 4 bytes")))
+        .collect::<Vec<_>>();
+    assert_eq!(squares, (0..16).map(|i| i * i).collect::<Vec<u32>>());
+    // Decompressed data keeps the alignment, which the macro can raise.
+    let aligned = include_packed_aligned!("aligned/table.bin");
+    assert_eq!((aligned.align(), &aligned[..]), (64, stored));
+    assert!(aligned.as_ptr().addr().is_multiple_of(64));
+    let aligned = include_packed_aligned!("blobs/file.txt", 4096);
+    assert_eq!(aligned.align(), 4096);
+    assert!(aligned.as_ptr().addr().is_multiple_of(4096));
+    assert!(aligned.clone().as_ptr().addr().is_multiple_of(4096));
+    assert_eq!(&aligned[..], data_vec.as_slice());
+
+    // Stored archives are validated in place, compressed ones after decompression.
+    let archived = || include_packed_archived!("aligned/world.rkyv", ArchivedWorld);
+    assert_eq!(archived().name, "Packed Plains");
+    assert_eq!(archived().heights[63], 189);
+    assert!(std::ptr::eq(archived(), archived()));
+    let stored = include_packed_asset!("aligned/world.rkyv")
+        .as_stored()
+        .expect("archive is not stored");
+    let world = World {
+        name: "Packed Plains".to_owned(),
+        heights: (0..64).map(|i| i * 3).collect(),
+    };
+    let serialized = rkyv::to_bytes::<rkyv::rancor::Error>(&world).expect("failed to archive");
+    assert_eq!(stored, serialized.as_slice());
+    assert!(
+        stored
+            .as_ptr_range()
+            .contains(&std::ptr::from_ref(archived()).cast())
+    );
+    let decompressed = include_packed_archived!("archives/world.rkyv", ArchivedWorld);
+    assert_eq!(decompressed.name, archived().name);
+    assert_eq!(decompressed.heights, archived().heights);
+    assert!(matches!(
+        include_packed_asset!("blobs/file.txt").try_archived::<ArchivedWorld>(),
+        Err(include_packed::Error::InvalidArchive {
+            path: "blobs/file.txt",
+            ..
+        })
+    ));
+
+    // The manifest lists every asset packed by the build script, in packing order.
+    let manifest = include_packed_manifest!();
+    assert_eq!(manifest.len(), 61);
+    assert_eq!(manifest[0].path(), "blobs/file.txt");
+    assert_eq!(manifest[0].len(), data_vec.len());
+    assert_eq!(manifest[0].algorithm(), Algorithm::Zstd);
+    let store = manifest
+        .iter()
+        .find(|info| info.path() == "algorithms/store.txt")
+        .expect("store.txt is missing from the manifest");
+    assert_eq!(store.algorithm(), Algorithm::Store);
+    assert_eq!(store.compressed_len(), store.len());
+    assert_eq!(manifest[0], INFO);
+    assert!(
+        manifest
+            .iter()
+            .any(|info| info.path() == "../shared_blobs/shared.txt")
+    );
+    assert!(
+        manifest
+            .iter()
+            .any(|info| info.path() == "demo_data/demo.txt")
+    );
+
+    // Assets can be looked up by a path only known at runtime. Assets scoped to other
+    // targets aren't registered.
+    let name = String::from("file.txt");
+    assert_eq!(
+        include_packed::get(&format!("blobs/{name}")).as_deref(),
+        Some(original_content.as_bytes())
+    );
+    assert!(include_packed::get("blobs/missing.txt").is_none());
+    assert!(include_packed::get("demo_data/demo.txt").is_none());
+    assert!(include_packed::get("../shared_blobs/shared.txt").is_none());
+    let registered = include_packed::assets()
+        .map(|(path, _)| path)
+        .collect::<Vec<_>>();
+    assert!(registered.is_sorted());
+    assert!(registered.contains(&"bundled/icons/tick.svg"));
+    assert!(registered.contains(&"translations/en.json"));
+
     println!("Decompressed data matches original.");
 }
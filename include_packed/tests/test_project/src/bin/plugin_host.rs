@@ -0,0 +1,23 @@
+use include_packed::{PackedAsset, RawPackedAsset};
+
+fn main() {
+    // The plugin is built into the same target directory as this binary.
+    let exe = std::env::current_exe().expect("failed to locate the host binary");
+    let plugin = exe.with_file_name(format!(
+        "{}test_plugin{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    ));
+
+    let library = unsafe { libloading::Library::new(&plugin) }
+        .unwrap_or_else(|err| panic!("failed to load {}: {err}", plugin.display()));
+    let get_asset: libloading::Symbol<extern "C" fn() -> RawPackedAsset> =
+        unsafe { library.get(b"test_plugin_asset") }.expect("plugin does not export its asset");
+
+    // SAFETY: `library` stays loaded until the end of `main`.
+    let asset = unsafe { PackedAsset::from_raw(get_asset()) };
+    assert_eq!(asset.path(), "assets/plugin.txt");
+    assert_eq!(asset.decompress(), b"Packed inside a plugin\n");
+
+    println!("Plugin asset matches original.");
+}
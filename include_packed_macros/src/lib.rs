@@ -1,15 +1,28 @@
 //! Procedural macro implementation for the `include_packed` crate. Do not use directly.
-use std::{env, fs, path::PathBuf};
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
 
 use proc_macro::TokenStream;
-use proc_macro2::TokenStream as TokenStream2;
-use quote::quote;
-use syn::{LitStr, parse_macro_input};
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use syn::{
+    DeriveInput, Expr, ExprLit, ExprMacro, Ident, Lit, LitInt, LitStr, Token,
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    spanned::Spanned,
+};
 
 /// Includes a large, compressed binary file without high compile-time costs.
 ///
 /// This macro takes a single string literal which must be a path to an asset
-/// relative to the crate root (`CARGO_MANIFEST_DIR`).
+/// relative to the crate root (`CARGO_MANIFEST_DIR`). The path may also be built with
+/// `concat!` and `env!`, e.g. `concat!(env!("ASSET_DIR"), "/logo.png")`, which is
+/// evaluated like the built-in macros would. Assets the build script registered with
+/// `Config::alias` can be named with `alias = "logo"` instead of their path.
 ///
 /// It expands to an expression of type `Vec<u8>`, containing the
 /// decompressed contents of the file.
@@ -24,9 +37,9 @@ use syn::{LitStr, parse_macro_input};
 ///
 /// - **Native (e.g., Linux, Windows, macOS):** The macro links to an object file
 ///   created by the build script, keeping `rustc`'s memory usage and compile times low.
-/// - **Wasm (`wasm32`):** The macro reads the asset file at compile time, compresses it,
-///   and embeds the bytes directly into the `.wasm` binary. This avoids the native
-///   linking process but may result in higher compiler memory usage for the Wasm target.
+/// - **Wasm (`wasm32`):** The build script compresses the asset into `OUT_DIR` and the
+///   macro embeds it with `include_bytes!`, so all build options apply and compression
+///   never runs inside the compiler.
 ///
 /// # Panics
 ///
@@ -36,90 +49,1926 @@ use syn::{LitStr, parse_macro_input};
 /// - Any of the intermediate files created by the build script are missing or corrupt.
 #[proc_macro]
 pub fn include_packed(input: TokenStream) -> TokenStream {
-    let lit_str = parse_macro_input!(input as LitStr);
+    let path = parse_macro_input!(input as AssetPath);
+    expand(&path, |_, _, asset| quote! { #asset.decompress() })
+}
+
+/// Includes a packed file, returning an error instead of panicking if it is corrupt.
+///
+/// This accepts the same path argument as [`include_packed!`], but expands to an
+/// expression of type `Result<Vec<u8>, include_packed::Error>`. Data packed by the build
+/// script always decompresses, but services that must degrade gracefully, e.g. on a
+/// damaged binary, can handle the error instead of aborting. Encrypted assets also
+/// report a missing or wrong key this way.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`].
+#[proc_macro]
+pub fn include_packed_try(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as AssetPath);
+    expand(&path, |_, _, asset| quote! { #asset.try_decompress() })
+}
+
+/// Includes a packed file deserialized into a value of the given type.
+///
+/// This takes the same path argument as [`include_packed!`], followed by the type to
+/// deserialize into and optionally the format, one of `json`, `postcard`, or
+/// `bincode`, e.g. `include_packed_as!("config/defaults.json", Config)`. Without a
+/// format, it is taken from the file's extension. The macro expands to an expression of
+/// the given type, which must implement `serde::de::DeserializeOwned`, deserialized
+/// with `include_packed::runtime::PackedAsset::deserialize` when it is evaluated. The
+/// format's Cargo feature must be enabled.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`], and
+/// if the format is unknown. The expression panics if the asset doesn't deserialize.
+#[proc_macro]
+pub fn include_packed_as(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as DeserializeArgs);
+    let path = &args.path;
+    let format = args.format.as_ref().map_or_else(
+        || {
+            Path::new(&path.lit.value())
+                .extension()
+                .map(|extension| extension.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        },
+        Ident::to_string,
+    );
+    let variant = match format.as_str() {
+        "json" => "Json",
+        "postcard" => "Postcard",
+        "bincode" => "Bincode",
+        _ => {
+            let msg = "include_packed: unknown format; pass one of `json`, `postcard`, or `bincode` after the type";
+            let span = args
+                .format
+                .as_ref()
+                .map_or_else(|| path.lit.span(), Ident::span);
+            return syn::Error::new(span, msg).to_compile_error().into();
+        }
+    };
+    let variant = Ident::new(variant, Span::call_site());
+    let ty = &args.ty;
+    expand(path, |crate_name, _, asset| {
+        quote! { #asset.deserialize::<#ty>(#crate_name::runtime::Format::#variant) }
+    })
+}
+
+/// The arguments of [`include_packed_as!`]: a path, a type, and an optional format.
+struct DeserializeArgs {
+    path: AssetPath,
+    ty: syn::Type,
+    format: Option<Ident>,
+}
+
+impl Parse for DeserializeArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let ty = input.parse()?;
+        let mut format = None;
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            format = Some(input.parse()?);
+            input.parse::<Option<Token![,]>>()?;
+        }
+        Ok(Self { path, ty, format })
+    }
+}
+
+/// Decompresses a packed file into an existing buffer instead of allocating a new one.
+///
+/// This takes a path argument like [`include_packed!`]'s and a mutable reference to
+/// the buffer, such as `include_packed_into!("frames/0.png", &mut scratch)`, and expands
+/// to an expression of type `usize` holding the size of the decompressed data. A
+/// `Vec<u8>` is overwritten with the data, reusing its allocation, and slices and arrays
+/// receive it at their start. Any `include_packed::runtime::DecompressInto` buffer works.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`]. The
+/// expression panics if the data does not fit into the buffer.
+#[proc_macro]
+pub fn include_packed_into(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as IntoArgs);
+    let target = &args.target;
+    expand(&args.path, |_, _, asset| {
+        quote! { #asset.decompress_into(#target) }
+    })
+}
+
+/// Includes a packed file as an `include_packed::AlignedBytes`, whose data starts at a
+/// guaranteed alignment.
+///
+/// This accepts the same path argument as [`include_packed!`], optionally followed by a
+/// minimum alignment such as `include_packed_aligned!("tables/lut.bin", 16)`. The data
+/// is aligned to the larger of that and the build script's `Config::align`, so it can be
+/// reinterpreted as slices of wider types, e.g. with `bytemuck::cast_slice`, without
+/// copying it again.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`], and if
+/// the alignment is not a power of two.
+#[proc_macro]
+pub fn include_packed_aligned(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as AlignedArgs);
+    let min_align = match &args.align {
+        Some(lit) => match lit.base10_parse::<usize>() {
+            Ok(align) if align.is_power_of_two() => align,
+            _ => {
+                return syn::Error::new(
+                    lit.span(),
+                    "include_packed: alignment must be a power of two",
+                )
+                .to_compile_error()
+                .into();
+            }
+        },
+        None => 1,
+    };
+    expand(&args.path, |_, packed, asset| {
+        let align = packed
+            .meta
+            .get("align")
+            .and_then(|align| align.parse::<usize>().ok())
+            .unwrap_or(1)
+            .max(min_align);
+        quote! { #asset.with_align(#align).decompress_aligned() }
+    })
+}
+
+/// The arguments of [`include_packed_aligned!`]: a path and an optional alignment.
+struct AlignedArgs {
+    path: AssetPath,
+    align: Option<LitInt>,
+}
+
+impl Parse for AlignedArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        let mut align = None;
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            align = Some(input.parse()?);
+            input.parse::<Option<Token![,]>>()?;
+        }
+        Ok(Self { path, align })
+    }
+}
+
+/// The arguments of [`include_packed_into!`]: a path and the buffer to decompress into.
+struct IntoArgs {
+    path: AssetPath,
+    target: Expr,
+}
+
+impl Parse for IntoArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let target = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(Self { path, target })
+    }
+}
+
+/// Includes several packed files as an array of `Vec<u8>`.
+///
+/// This takes a comma-separated list of path arguments like [`include_packed!`]'s,
+/// such as `include_packed_all!("frames/0.png", "frames/1.png")`, and expands to an
+/// expression of type `[Vec<u8>; N]` holding the decompressed contents of each file,
+/// in the order given.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`] for
+/// any of the files.
+#[proc_macro]
+pub fn include_packed_all(input: TokenStream) -> TokenStream {
+    let paths = parse_macro_input!(input with Punctuated::<AssetPath, Token![,]>::parse_terminated);
+    let elements = paths
+        .iter()
+        .map(|path| TokenStream2::from(expand(path, |_, _, asset| quote! { #asset.decompress() })))
+        .collect::<Vec<_>>();
+    quote! { [#(#elements),*] }.into()
+}
+
+/// Includes a packed file if it exists, as an `Option<Vec<u8>>`.
+///
+/// This accepts the same path argument as [`include_packed!`], but expands to `None`
+/// instead of failing to compile if the file doesn't exist, or was left out by the build
+/// script's include or exclude patterns. This suits builds where some assets are
+/// intentionally missing, such as white-label variants.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`] for a
+/// file that exists and wasn't excluded.
+#[proc_macro]
+pub fn include_packed_opt(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as AssetPath);
+    let path_str = path.lit.value();
+    let root = AssetRoot::find(&path_str);
+    let missing = match source_file(&root.manifest_dir.join(&path_str)) {
+        // Members of archives only exist if the build script packed them.
+        Some((path, metadata)) if metadata.is_file() => {
+            is_skipped(&root, &path_str, !path.exists())
+        }
+        _ => true,
+    };
+    if missing {
+        let crate_name = crate_name();
+        let expansion = quote! {
+            ::core::option::Option::<#crate_name::runtime::PackedAsset>::None
+                .map(|asset| asset.decompress())
+        };
+        return path.track(with_build_script_check(
+            &crate_name,
+            path.lit.span(),
+            expansion,
+        ));
+    }
+    expand(&path, |_, _, asset| {
+        quote! { ::core::option::Option::Some(#asset.decompress()) }
+    })
+}
+
+/// Includes a packed file, decompressing it at most once.
+///
+/// This accepts the same path argument as [`include_packed!`], but expands to an
+/// expression of type `&'static [u8]`. The asset is decompressed on first use into a
+/// process-wide cache, which `include_packed::prewarm` can fill ahead of time, and
+/// every later evaluation returns the cached bytes, so hot paths don't pay repeated
+/// decompression and allocation costs. This requires the standard library.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`].
+#[proc_macro]
+pub fn include_packed_lazy(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as AssetPath);
+    expand(&path, |_, _, asset| {
+        quote! {
+            {
+                // Saves locking the process-wide cache on every evaluation.
+                static CACHE: ::std::sync::OnceLock<&'static [u8]> =
+                    ::std::sync::OnceLock::new();
+                *CACHE.get_or_init(|| #asset.cached())
+            }
+        }
+    })
+}
+
+/// Includes a packed rkyv archive as a reference to its archived type.
+///
+/// This takes the same path argument as [`include_packed!`], followed by the archived
+/// type, e.g. `include_packed_archived!("assets/world.rkyv", ArchivedWorld)`, and
+/// expands to an expression of type `&'static ArchivedWorld`. The archive is validated
+/// the first time the expression is evaluated, with
+/// `include_packed::runtime::PackedAsset::archived`, and every later evaluation returns
+/// the same reference. Archives packed with `Algorithm::Store` and a sufficient
+/// `Config::align` are accessed in place, without decompressing or copying them. This
+/// requires the `rkyv` feature of `include_packed`.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`]. The
+/// expression panics if the asset is not a valid archive of the type.
+#[proc_macro]
+pub fn include_packed_archived(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as ArchivedArgs);
+    let ty = &args.ty;
+    expand(&args.path, |_, _, asset| {
+        quote! {
+            {
+                static ARCHIVED: ::std::sync::OnceLock<&'static #ty> =
+                    ::std::sync::OnceLock::new();
+                *ARCHIVED.get_or_init(|| #asset.archived::<#ty>())
+            }
+        }
+    })
+}
+
+/// The arguments of [`include_packed_archived!`]: a path and the archived type.
+struct ArchivedArgs {
+    path: AssetPath,
+    ty: syn::Type,
+}
+
+impl Parse for ArchivedArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let ty = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(Self { path, ty })
+    }
+}
+
+/// Includes a packed file as a `bytes::Bytes` buffer.
+///
+/// This accepts the same path argument as [`include_packed!`], but expands to an
+/// expression of type `bytes::Bytes`, which is cheap to clone and can be handed to
+/// `hyper` or `axum` response bodies without copying. Assets stored uncompressed are
+/// not copied at all. This requires the `bytes` feature of `include_packed`.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`].
+#[proc_macro]
+pub fn include_packed_shared(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as AssetPath);
+    expand(&path, |_, _, asset| quote! { #asset.decompress_bytes() })
+}
+
+/// Includes a packed file as a `Cow<'static, [u8]>`.
+///
+/// This accepts the same path argument as [`include_packed!`]. Assets stored
+/// uncompressed with `Algorithm::Store` are borrowed from the binary without copying,
+/// like [`include_packed_bytes!`] does, while compressed assets are decompressed into an
+/// owned buffer, so callers get zero-copy access where possible with a single type.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`].
+#[proc_macro]
+pub fn include_packed_cow(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as AssetPath);
+    expand(&path, |_, _, asset| quote! { #asset.decompress_cow() })
+}
+
+/// Includes a packed file, decompressing it without blocking an async executor.
+///
+/// This accepts the same path argument as [`include_packed!`], but expands to a future
+/// resolving to the `Vec<u8>`, which decompresses the asset on tokio's blocking thread
+/// pool. This requires the `tokio` feature of `include_packed`, and the future must be
+/// awaited within a tokio runtime.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`].
+#[proc_macro]
+pub fn include_packed_async(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as AssetPath);
+    expand(&path, |_, _, asset| quote! { #asset.decompress_async() })
+}
+
+/// Includes a packed UTF-8 text file as a `String`.
+///
+/// This accepts the same path argument as [`include_packed!`], but expands to an
+/// expression of type `String`. The asset is validated as UTF-8 when it is packed, so
+/// no validation is needed at runtime.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`], and
+/// if the asset is not valid UTF-8.
+#[proc_macro]
+pub fn include_packed_str(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as AssetPath);
+    let lit_str = &path.lit;
+    expand(&path, |_, packed, asset| {
+        if packed.meta.get("utf8").map(String::as_str) != Some("true") {
+            let msg = format!(
+                "include_packed: asset '{}' is not valid UTF-8",
+                lit_str.value()
+            );
+            return syn::Error::new(lit_str.span(), msg).to_compile_error();
+        }
+        quote! { #asset.decompress_string() }
+    })
+}
+
+/// Includes a packed file that may be served over HTTP, as a future.
+///
+/// This accepts the same path argument as [`include_packed!`], but expands to an
+/// expression implementing `Future<Output = Result<Vec<u8>, include_packed::Error>>`.
+/// On Wasm, assets packed with `Config::wasm_fetch` are downloaded from their
+/// content-hashed URL and decompressed, which requires the `fetch` feature. Everywhere
+/// else, the future decompresses the embedded data, so the same code runs on every
+/// target.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`].
+#[proc_macro]
+pub fn include_packed_fetch(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as AssetPath);
+    let lit_str = &path.lit;
+    let crate_name = crate_name();
+    let expansion = pack(lit_str, &crate_name).map_or_else(
+        |err| err,
+        |mut packed| match packed.meta.get("url").cloned() {
+            Some(url) => {
+                packed.data = quote! { &[] };
+                let asset = packed.to_asset(&crate_name, lit_str);
+                quote! { #asset.fetch(#url) }
+            }
+            None => {
+                let asset = packed.to_asset(&crate_name, lit_str);
+                quote! { ::core::future::ready(#asset.try_decompress()) }
+            }
+        },
+    );
+    path.track(with_build_script_check(
+        &crate_name,
+        lit_str.span(),
+        expansion,
+    ))
+}
+
+/// Evaluates to the original, decompressed size of a packed file.
+///
+/// This accepts the same path argument as [`include_packed!`], but expands to a
+/// `usize` literal, which can be used in `const` contexts such as array lengths or
+/// compile-time size checks. Nothing is linked or decompressed.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`].
+#[proc_macro]
+pub fn include_packed_len(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as AssetPath);
+    let lit_str = &path.lit;
+    expand(&path, |_, packed, _| {
+        match packed
+            .meta
+            .get("len")
+            .and_then(|len| len.parse::<usize>().ok())
+        {
+            Some(len) => quote! { #len },
+            None => {
+                let msg = format!(
+                    "include_packed: the size of asset '{}' was not recorded; rebuild with a matching version of `include_packed`",
+                    lit_str.value()
+                );
+                syn::Error::new(lit_str.span(), msg).to_compile_error()
+            }
+        }
+    })
+}
+
+/// Includes a packed file as a streaming reader.
+///
+/// This accepts the same path argument as [`include_packed!`], but expands to an
+/// expression of type `include_packed::runtime::AssetReader`, which implements
+/// `std::io::Read`. The asset is decompressed incrementally as it is read, so large
+/// assets can be piped into parsers without materializing them in memory.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`].
+#[proc_macro]
+pub fn include_packed_reader(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as AssetPath);
+    expand(&path, |_, _, asset| quote! { #asset.reader() })
+}
+
+/// Includes every file below a directory as an `include_packed::PackedDir`.
+///
+/// The argument is a path to a directory relative to the crate root
+/// (`CARGO_MANIFEST_DIR`), which the build script must have packed. The macro expands
+/// to a `Copy` lookup structure that maps each file's path relative to the directory,
+/// using `/` as separator, to its `PackedAsset` handle. Nothing is decompressed until
+/// an asset is requested.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`] for
+/// any of the files, and if the directory cannot be read.
+#[proc_macro]
+pub fn include_packed_dir(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as AssetPath);
+    let lit_str = &path.lit;
+    let crate_name = crate_name();
+    path.track(expand_dir(lit_str, &crate_name).unwrap_or_else(|err| err))
+}
+
+/// Expansion logic for [`include_packed_dir!`].
+fn expand_dir(lit_str: &LitStr, crate_name: &Ident) -> Result<TokenStream2, TokenStream2> {
+    let dir_str = lit_str.value();
+    let root = AssetRoot::find(&dir_str);
+    let mut files = Vec::new();
+    let dir = root.manifest_dir.join(&dir_str);
+    collect_files(&dir, "", false, &mut Vec::new(), &mut files).map_err(|err| {
+        let msg = format!("include_packed: could not read directory '{dir_str}': {err}");
+        syn::Error::new(lit_str.span(), msg).to_compile_error()
+    })?;
+    check_fingerprints(&root, &dir, lit_str)?;
+    let dir_str = dir_str.trim_end_matches('/');
+    let mut files = files
+        .into_iter()
+        .filter(|(file, linked)| !is_skipped(&root, &format!("{dir_str}/{file}"), *linked))
+        .map(|(file, _)| file)
+        .collect::<Vec<_>>();
+    files.sort();
+
+    let mut assets = Vec::with_capacity(files.len());
+    for file in &files {
+        let logical = LitStr::new(&format!("{dir_str}/{file}"), lit_str.span());
+        let packed = pack(&logical, crate_name)?;
+        assets.push(packed.to_asset(crate_name, &logical));
+    }
+
+    let dir = quote! {
+        #crate_name::runtime::PackedDir::new(&[#(#files),*], |path| match path {
+            #(#files => ::core::option::Option::Some(#assets),)*
+            _ => ::core::option::Option::None,
+        })
+    };
+    Ok(with_build_script_check(crate_name, lit_str.span(), dir))
+}
+
+/// Derives lookup functions over every file below a folder, like `rust-embed`.
+///
+/// The folder is given relative to the crate root (`CARGO_MANIFEST_DIR`) with a
+/// `#[packed(folder = "...")]` attribute, and must have been packed by the build script:
+///
+/// ```no_run
+/// # // The folder only exists in crates whose build script packed it, and this crate
+/// # // cannot depend on `include_packed`.
+/// # #[cfg(any())]
+/// # fn main() {
+/// #[derive(include_packed::PackedAssets)]
+/// #[packed(folder = "static/")]
+/// struct Assets;
+///
+/// let css = Assets::get("css/app.css").expect("app.css is missing");
+/// for path in Assets::iter() {
+///     println!("{path}");
+/// }
+/// # }
+/// # #[cfg(not(any()))]
+/// # fn main() {}
+/// ```
+///
+/// The derive generates an inherent `get` function returning the decompressed contents
+/// of the file at a path relative to the folder, an `iter` function over the relative
+/// paths of all files in sorted order, and a `dir` function returning the underlying
+/// `include_packed::PackedDir`, as [`include_packed_dir!`] would. Nothing is
+/// decompressed until a file is requested.
+///
+/// # Panics
+///
+/// This derive fails to compile under the same conditions as [`include_packed_dir!`],
+/// and if the `folder` attribute is missing.
+#[proc_macro_derive(PackedAssets, attributes(packed))]
+pub fn derive_packed_assets(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_packed_assets(&input).map_or_else(TokenStream::from, TokenStream::from)
+}
+
+/// Expansion logic for [`derive_packed_assets`].
+fn expand_packed_assets(input: &DeriveInput) -> Result<TokenStream2, TokenStream2> {
+    let mut folder = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("packed"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("folder") {
+                folder = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("include_packed: expected `folder = \"...\"`"))
+            }
+        })
+        .map_err(|err| err.to_compile_error())?;
+    }
+    let Some(folder) = folder else {
+        let msg = "include_packed: missing `#[packed(folder = \"...\")]` attribute";
+        return Err(syn::Error::new_spanned(&input.ident, msg).to_compile_error());
+    };
+
+    let crate_name = crate_name();
+    let dir = expand_dir(&folder, &crate_name)?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Returns the packed folder the files are looked up in.
+            #[must_use]
+            pub const fn dir() -> #crate_name::runtime::PackedDir {
+                #dir
+            }
+
+            /// Decompresses the file at `path`, relative to the packed folder.
+            #[must_use]
+            pub fn get(path: &str) -> ::core::option::Option<::std::vec::Vec<u8>> {
+                Self::dir().decompress(path)
+            }
+
+            /// Returns an iterator over the relative paths of all files in the packed
+            /// folder, in sorted order.
+            pub fn iter() -> impl ::core::iter::Iterator<Item = &'static str> {
+                Self::dir().paths().iter().copied()
+            }
+        }
+    })
+}
+
+/// Recursively collects the paths of all files below `dir`, relative to it, along with
+/// whether they were reached through a symbolic link.
+///
+/// Like the build script, this follows links to directories once, skipping cycles,
+/// and skips links whose targets don't exist. `ancestors` holds the canonical paths of
+/// the directories being walked.
+fn collect_files(
+    dir: &Path,
+    prefix: &str,
+    linked: bool,
+    ancestors: &mut Vec<PathBuf>,
+    files: &mut Vec<(String, bool)>,
+) -> std::io::Result<()> {
+    let canonical_dir = dir.canonicalize()?;
+    if ancestors.contains(&canonical_dir) {
+        return Ok(());
+    }
+    ancestors.push(canonical_dir);
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let relative = if prefix.is_empty() {
+            name
+        } else {
+            format!("{prefix}/{name}")
+        };
+        let linked = linked || entry.file_type()?.is_symlink();
+        let Ok(metadata) = fs::metadata(entry.path()) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            collect_files(&entry.path(), &relative, linked, ancestors, files)?;
+        } else {
+            files.push((relative, linked));
+        }
+    }
+    ancestors.pop();
+    Ok(())
+}
+
+/// Checks that no files were added to or removed from `dir` or the directories below it
+/// after the build script last ran.
+fn check_fingerprints(root: &AssetRoot, dir: &Path, lit_str: &LitStr) -> Result<(), TokenStream2> {
+    let recorded = root.fingerprints();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        if directory_changed(&recorded, &dir) {
+            return Err(stale_directory(&dir, lit_str));
+        }
+        let entries = fs::read_dir(&dir).into_iter().flatten().flatten();
+        pending.extend(
+            entries
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir()),
+        );
+    }
+    Ok(())
+}
+
+/// Returns whether the entries of `dir` differ from when the build script recorded its
+/// fingerprint among `recorded`. Directories the build script didn't walk are reported
+/// per asset by `pack` instead.
+fn directory_changed(recorded: &HashMap<PathBuf, u64>, dir: &Path) -> bool {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let Some(recorded) = dir.canonicalize().ok().and_then(|dir| recorded.get(&dir)) else {
+        return false;
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+    // The build script hashes the sorted names of the entries the same way.
+    let mut names = entries
+        .map(|entry| entry.map(|entry| entry.file_name()))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap_or_default();
+    names.sort();
+    let mut hasher = DefaultHasher::new();
+    names.hash(&mut hasher);
+    hasher.finish() != *recorded
+}
+
+/// Reports that files were added to or removed from `dir` after the build script ran.
+fn stale_directory(dir: &Path, lit_str: &LitStr) -> TokenStream2 {
+    let msg = format!(
+        "include_packed: files were added to or removed from '{}' after the build script last ran, so its assets are stale\nhelp: touch the build script or run `cargo clean -p {}` to repack them",
+        dir.display(),
+        env::var("CARGO_PKG_NAME").unwrap_or_default()
+    );
+    syn::Error::new(lit_str.span(), msg).to_compile_error()
+}
+
+/// Includes every file matching a glob pattern as an array of `(path, Vec<u8>)` pairs.
+///
+/// The argument is a glob pattern relative to the crate root (`CARGO_MANIFEST_DIR`),
+/// such as `"assets/shaders/*.wgsl"`, matched at compile time. The macro expands to an
+/// expression of type `[(&'static str, Vec<u8>); N]` holding the path and decompressed
+/// contents of each matching file, sorted by path. Paths are relative to the crate
+/// root, use `/` as separator, and match what [`include_packed!`] would accept.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`] for
+/// any of the matching files, and if the pattern is invalid.
+#[proc_macro]
+pub fn include_packed_glob(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as AssetPath);
+    let lit_str = &path.lit;
+    path.track(expand_glob(lit_str).unwrap_or_else(TokenStream::from))
+}
+
+/// Expansion logic for [`include_packed_glob!`].
+fn expand_glob(lit_str: &LitStr) -> Result<TokenStream, TokenStream2> {
+    let crate_name = crate_name();
+
+    let error = |msg: String| syn::Error::new(lit_str.span(), msg).to_compile_error();
+    let mut files = Vec::new();
+    // Patterns matching nothing in this crate are matched against shared assets.
+    for root in AssetRoot::all() {
+        let pattern = format!(
+            "{}/{}",
+            glob::Pattern::escape(&root.manifest_dir.to_string_lossy()),
+            lit_str.value()
+        );
+        let paths = glob::glob(&pattern)
+            .map_err(|err| error(format!("include_packed: invalid glob pattern: {err}")))?;
+        for path in paths {
+            let path = path.map_err(|err| error(format!("include_packed: {err}")))?;
+            if !path.is_file() {
+                continue;
+            }
+            let relative = path
+                .strip_prefix(&root.manifest_dir)
+                .unwrap_or(&path)
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if !is_skipped(&root, &relative, false) {
+                files.push(relative);
+            }
+        }
+        if !files.is_empty() {
+            break;
+        }
+    }
+    files.sort();
+
+    let mut entries = Vec::with_capacity(files.len());
+    for file in &files {
+        let logical = LitStr::new(file, lit_str.span());
+        let asset = pack(&logical, &crate_name)?.to_asset(&crate_name, &logical);
+        entries.push(quote! { (#logical, #asset.decompress()) });
+    }
+
+    let entries = quote! { [#(#entries),*] };
+    Ok(with_build_script_check(&crate_name, lit_str.span(), entries).into())
+}
+
+/// Includes a file stored without compression as a `&'static [u8]`.
+///
+/// This accepts the same path argument as [`include_packed!`], but the asset must have
+/// been packed with `Algorithm::Store`, which suits data that doesn't compress, such
+/// as already-encoded media. The macro expands to a direct reference to the linked
+/// data, giving the zero-copy access of `include_bytes!` while keeping the fast
+/// object-file path.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`], and
+/// if the asset was packed with compression or encryption, or signed, since the data
+/// is handed out without being verified.
+#[proc_macro]
+pub fn include_packed_bytes(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as AssetPath);
+    let lit_str = &path.lit;
+    expand(&path, |_, packed, _| {
+        if packed.meta.get("algorithm").map(String::as_str) != Some("store") {
+            let msg = format!(
+                "include_packed: asset '{}' is compressed; pack it with `Algorithm::Store` to include it without decompression",
+                lit_str.value()
+            );
+            return syn::Error::new(lit_str.span(), msg).to_compile_error();
+        }
+        if packed.meta.contains_key("cipher") {
+            let msg = format!(
+                "include_packed: asset '{}' is encrypted and can't be included without decryption",
+                lit_str.value()
+            );
+            return syn::Error::new(lit_str.span(), msg).to_compile_error();
+        }
+        if packed.meta.contains_key("signature") {
+            let msg = format!(
+                "include_packed: asset '{}' is signed and can't be included without verifying its signature",
+                lit_str.value()
+            );
+            return syn::Error::new(lit_str.span(), msg).to_compile_error();
+        }
+        packed.data.clone()
+    })
+}
+
+/// Includes the compressed data of a packed file without decompressing it.
+///
+/// This accepts the same path argument as [`include_packed!`], but expands to an
+/// expression of type `include_packed::CompressedAsset`, holding a reference to the data
+/// exactly as it was linked, e.g. a zstd frame, along with the asset's original size and
+/// other metadata. Web servers can send the data with a `Content-Encoding` header and
+/// skip decompressing it at runtime.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`], and
+/// if the asset is encrypted, compressed with a dictionary, or a patch against another
+/// asset, since its data could then not be decompressed on its own, or signed, since
+/// its signature could not be verified.
+#[proc_macro]
+pub fn include_packed_compressed(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as AssetPath);
+    let lit_str = &path.lit;
+    expand(&path, |crate_name, packed, _| {
+        let reason = if packed.meta.contains_key("cipher") {
+            Some("is encrypted")
+        } else if packed.dictionary.is_some() {
+            Some("is compressed with a dictionary")
+        } else if packed.base.is_some() {
+            Some("is a patch against another asset")
+        } else if packed.meta.contains_key("signature") {
+            Some("is signed")
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            let msg = format!(
+                "include_packed: asset '{}' {reason} and can't be included without decompression",
+                lit_str.value()
+            );
+            return syn::Error::new(lit_str.span(), msg).to_compile_error();
+        }
+        let info = match asset_info(crate_name, &lit_str.value(), &packed.meta, lit_str.span()) {
+            Some(Ok(info)) => info,
+            Some(Err(err)) => return err,
+            None => {
+                let msg = format!(
+                    "include_packed: the metadata of asset '{}' was not recorded; rebuild with a matching version of `include_packed`",
+                    lit_str.value()
+                );
+                return syn::Error::new(lit_str.span(), msg).to_compile_error();
+            }
+        };
+        let data = &packed.data;
+        quote! { #crate_name::runtime::CompressedAsset::new(#data, #info) }
+    })
+}
+
+/// Includes a packed file as an `include_packed::PackedAsset` handle.
+///
+/// This accepts the same path argument as [`include_packed!`], but instead of
+/// decompressing immediately it expands to a cheap, `Copy` handle. The handle
+/// exposes additional ways of retrieving the data, such as cancellable
+/// decompression.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`].
+#[proc_macro]
+pub fn include_packed_asset(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as AssetPath);
+    expand(&path, |_, _, asset| asset)
+}
+
+/// Evaluates to the metadata recorded for a packed file at build time.
+///
+/// This accepts the same path argument as [`include_packed!`], but expands to a
+/// constant expression of type `include_packed::runtime::AssetInfo`, holding the asset's
+/// original and compressed sizes, compression algorithm, source modification time, and
+/// a MIME type guessed from its extension. Nothing is linked or decompressed, so this
+/// can fill in HTTP headers like `Content-Length`, `Last-Modified`, and `Content-Type`
+/// without recomputing them at runtime.
+///
+/// # Panics
+///
+/// This macro fails to compile under the same conditions as [`include_packed!`].
+#[proc_macro]
+pub fn include_packed_info(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as AssetPath);
+    let lit_str = &path.lit;
+    expand(&path, |crate_name, packed, _| {
+        match asset_info(crate_name, &lit_str.value(), &packed.meta, lit_str.span()) {
+            Some(Ok(info) | Err(info)) => info,
+            None => {
+                let msg = format!(
+                    "include_packed: the metadata of asset '{}' was not recorded; rebuild with a matching version of `include_packed`",
+                    lit_str.value()
+                );
+                syn::Error::new(lit_str.span(), msg).to_compile_error()
+            }
+        }
+    })
+}
 
-    // Read the environment variable set by the build script to determine the target.
-    env::var("INCLUDE_PACKED_TARGET_ARCH").map_or_else(|_| syn::Error::new(
-                lit_str.span(),
-                "include_packed: build script has not run. This is expected during analysis (e.g., by rust-analyzer).",
-            )
-            .to_compile_error()
-            .into(), |target_arch| if target_arch == "wasm32" {
-                // We are building for Wasm.
-                get_tokens_wasm(&lit_str).into()
-            } else {
-                // We are building for a native target.
-                get_tokens_native(&lit_str).into()
-            })
+/// Lists every asset packed by the build script.
+///
+/// This takes no arguments and expands to an expression of type
+/// `&'static [include_packed::runtime::AssetInfo]`, describing each asset's logical
+/// path, original and compressed sizes, and compression algorithm, in the order the
+/// build script packed them. Nothing is linked or decompressed, so this suits runtime
+/// asset pickers and logging what a binary embeds.
+///
+/// # Panics
+///
+/// This macro fails to compile if the build script has not run correctly.
+#[proc_macro]
+pub fn include_packed_manifest(input: TokenStream) -> TokenStream {
+    parse_macro_input!(input as syn::parse::Nothing);
+    expand_manifest().unwrap_or_else(TokenStream::from)
 }
 
-/// Wasm implementation: Reads, compresses, and embeds the file inside the macro itself.
-fn get_tokens_wasm(lit_str: &LitStr) -> TokenStream2 {
-    use proc_macro_crate::{FoundCrate, crate_name};
-    use proc_macro2::Span;
-    use syn::Ident;
+/// Expansion logic for [`include_packed_manifest!`].
+fn expand_manifest() -> Result<TokenStream, TokenStream2> {
+    let span = Span::call_site();
+    let crate_name = crate_name();
+    if !build_script_ran() {
+        // Nothing was packed ahead of time.
+        let manifest = quote! { &[] as &'static [#crate_name::runtime::AssetInfo] };
+        return Ok(with_build_script_check(&crate_name, span, manifest).into());
+    }
 
-    let path_str = lit_str.value();
-    let manifest_dir = env::var("CARGO_MANIFEST_DIR")
-        .expect("CARGO_MANIFEST_DIR is not set; this macro must be run by Cargo.");
-    let path = PathBuf::from(manifest_dir).join(&path_str);
+    let out_dir =
+        env::var("OUT_DIR").expect("OUT_DIR is not set; this macro must be run by Cargo.");
+    let manifest_path = Path::new(&out_dir).join("include_packed.manifest");
+    let corrupt = || {
+        let msg = format!(
+            "include_packed: corrupt manifest at '{}'",
+            manifest_path.display()
+        );
+        syn::Error::new(span, msg).to_compile_error()
+    };
+    let contents = fs::read_to_string(&manifest_path).map_err(|err| {
+        let msg = format!(
+            "include_packed: failed to read manifest at '{}': {err}",
+            manifest_path.display()
+        );
+        syn::Error::new(span, msg).to_compile_error()
+    })?;
+
+    let mut entries = Vec::new();
+    for line in contents.lines() {
+        let mut fields = line.splitn(5, '\t');
+        let (Some(len), Some(compressed_len), Some(name), Some(modified), Some(path)) = (
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+        ) else {
+            return Err(corrupt());
+        };
+        let meta = HashMap::from([
+            ("len".to_owned(), len.to_owned()),
+            ("compressed_len".to_owned(), compressed_len.to_owned()),
+            ("algorithm".to_owned(), name.to_owned()),
+            ("modified".to_owned(), modified.to_owned()),
+        ]);
+        entries.push(asset_info(&crate_name, path, &meta, span).ok_or_else(corrupt)??);
+    }
+
+    let count = entries.len();
+    Ok(quote! {
+        {
+            static MANIFEST: [#crate_name::runtime::AssetInfo; #count] = [#(#entries),*];
+            &MANIFEST as &'static [#crate_name::runtime::AssetInfo]
+        }
+    }
+    .into())
+}
+
+/// Registers the assets packed by the build script for lookup by path at runtime.
+///
+/// This takes no arguments and must be invoked once, at item level, in the binary (or
+/// other final artifact) that calls `include_packed::assets` or `include_packed::get`,
+/// which resolve assets by logical path without a compile-time literal:
+///
+/// ```no_run
+/// # // The assets only exist in crates whose build script packed them, and this crate
+/// # // cannot depend on `include_packed`.
+/// # #[cfg(any())]
+/// # mod example {
+/// include_packed::include_packed_registry!();
+///
+/// fn load(name: &str) -> Option<Vec<u8>> {
+///     include_packed::get(&format!("assets/{name}"))
+/// }
+/// # }
+/// # fn main() {}
+/// ```
+///
+/// Every registered asset is linked into the binary. Assets scoped to some targets with
+/// `Config::bin` and the like, and assets fetched with `Config::wasm_fetch`, are left
+/// out of the registry.
+///
+/// # Panics
+///
+/// This macro fails to compile if the build script has not run correctly, and under the
+/// same conditions as [`include_packed!`] for any of the assets.
+#[proc_macro]
+pub fn include_packed_registry(input: TokenStream) -> TokenStream {
+    parse_macro_input!(input as syn::parse::Nothing);
+    expand_registry().unwrap_or_else(TokenStream::from)
+}
+
+/// Expansion logic for [`include_packed_registry!`].
+fn expand_registry() -> Result<TokenStream, TokenStream2> {
+    let span = Span::call_site();
+    let crate_name = crate_name();
 
-    let content = match fs::read(&path) {
-        Ok(bytes) => bytes,
-        Err(err) => {
+    // Without the build script, nothing was packed ahead of time.
+    let contents = if !build_script_ran() {
+        String::new()
+    } else {
+        let out_dir =
+            env::var("OUT_DIR").expect("OUT_DIR is not set; this macro must be run by Cargo.");
+        let registry_path = Path::new(&out_dir).join("include_packed.registry");
+        fs::read_to_string(&registry_path).map_err(|err| {
             let msg = format!(
-                "include_packed: could not read file '{}' for wasm target: {err}",
-                path.display()
+                "include_packed: failed to read registry at '{}': {err}",
+                registry_path.display()
             );
-            return syn::Error::new(lit_str.span(), msg).to_compile_error();
+            syn::Error::new(span, msg).to_compile_error()
+        })?
+    };
+    // Lookups by `PackedDir::contains` rely on sorted paths.
+    let mut paths = contents.lines().collect::<Vec<_>>();
+    paths.sort_unstable();
+    paths.dedup();
+
+    let mut assets = Vec::with_capacity(paths.len());
+    for path in &paths {
+        let logical = LitStr::new(path, span);
+        let packed = pack(&logical, &crate_name)?;
+        assets.push(packed.to_asset(&crate_name, &logical));
+    }
+
+    let error = check_build_script(&crate_name, span);
+    // `include_packed::runtime` declares the static and reads it.
+    Ok(quote! {
+        #error
+
+        #[doc(hidden)]
+        #[unsafe(no_mangle)]
+        #[allow(non_upper_case_globals)]
+        static __include_packed_registry: #crate_name::runtime::PackedDir =
+            #crate_name::runtime::PackedDir::new(&[#(#paths),*], |path| match path {
+                #(#paths => ::core::option::Option::Some(#assets),)*
+                _ => ::core::option::Option::None,
+            });
+    }
+    .into())
+}
+
+/// Shared expansion logic for the macros in this crate.
+///
+/// `wrap` receives the path to the `include_packed` crate, the backend's output, and an
+/// expression of type `include_packed::runtime::PackedAsset` for the asset, and returns
+/// the final expansion.
+fn expand(
+    path: &AssetPath,
+    wrap: impl FnOnce(&Ident, &Packed, TokenStream2) -> TokenStream2,
+) -> TokenStream {
+    let crate_name = crate_name();
+    let expansion = pack(&path.lit, &crate_name).map_or_else(
+        |err| err,
+        |packed| {
+            let asset = packed.to_asset(&crate_name, &path.lit);
+            wrap(&crate_name, &packed, asset)
+        },
+    );
+    path.track(with_build_script_check(
+        &crate_name,
+        path.lit.span(),
+        expansion,
+    ))
+}
+
+/// The path argument of the macros: a string literal, or a `concat!` or `env!`
+/// expression building one, which the macros evaluate themselves since procedural
+/// macros receive their arguments unexpanded, or `alias = "name"` naming an asset by an
+/// alias registered with `Config::alias`.
+struct AssetPath {
+    /// The evaluated path, spanning the whole argument.
+    lit: LitStr,
+    /// The names of the environment variables read by `env!`.
+    env_vars: Vec<LitStr>,
+}
+
+impl Parse for AssetPath {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Ident) && input.peek2(Token![=]) {
+            let key = input.parse::<Ident>()?;
+            if key != "alias" {
+                return Err(syn::Error::new(
+                    key.span(),
+                    "include_packed: expected `alias = \"...\"`",
+                ));
+            }
+            input.parse::<Token![=]>()?;
+            return Ok(Self {
+                lit: resolve_alias(&input.parse()?)?,
+                env_vars: Vec::new(),
+            });
+        }
+        let expr = input.parse::<Expr>()?;
+        let mut env_vars = Vec::new();
+        let value = eval_str(&expr, &mut env_vars)?;
+        Ok(Self {
+            lit: LitStr::new(&value, expr.span()),
+            env_vars,
+        })
+    }
+}
+
+impl AssetPath {
+    /// Finishes the macro's `expansion`, making rustc rebuild the crate when any of the
+    /// environment variables read by `env!` change.
+    fn track(&self, expansion: impl Into<TokenStream2>) -> TokenStream {
+        let expansion = expansion.into();
+        if self.env_vars.is_empty() {
+            return expansion.into();
+        }
+        let env_vars = &self.env_vars;
+        quote! {
+            {
+                #(const _: &str = ::core::env!(#env_vars);)*
+                #expansion
+            }
+        }
+        .into()
+    }
+}
+
+/// Resolves an alias registered with `Config::alias` to the asset's logical path.
+fn resolve_alias(name: &LitStr) -> syn::Result<LitStr> {
+    let name_str = name.value();
+    if !build_script_ran() {
+        if cfg!(feature = "fallback-embed") {
+            let msg = "include_packed: asset aliases are registered by the build script, which has not run";
+            return Err(syn::Error::new(name.span(), msg));
+        }
+        // The macros expand to placeholders, whatever the path.
+        return Ok(name.clone());
+    }
+
+    let mut names = Vec::new();
+    for root in AssetRoot::all() {
+        let contents =
+            fs::read_to_string(root.out_dir.join("include_packed.aliases")).unwrap_or_default();
+        let aliases = contents
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .collect::<Vec<_>>();
+        // Aliases registered later replace earlier ones of the same name.
+        if let Some((_, path)) = aliases.iter().rev().find(|(alias, _)| *alias == name_str) {
+            return Ok(LitStr::new(path, name.span()));
+        }
+        names.extend(aliases.iter().map(|(alias, _)| (*alias).to_owned()));
+    }
+    let mut msg = format!(
+        "include_packed: no asset alias '{name_str}'; register it with `Config::alias` in the build script"
+    );
+    if let Some(nearest) = nearest(&name_str, &names) {
+        msg.push_str(&format!("\nhelp: did you mean '{nearest}'?"));
+    }
+    Err(syn::Error::new(name.span(), msg))
+}
+
+/// Evaluates `expr` to a string like `concat!` would, reading the environment
+/// variables of `env!` calls and adding their names to `env_vars`.
+fn eval_str(expr: &Expr, env_vars: &mut Vec<LitStr>) -> syn::Result<String> {
+    let unsupported = || {
+        syn::Error::new_spanned(
+            expr,
+            "include_packed: expected a string literal, or a `concat!` or `env!` expression",
+        )
+    };
+    match expr {
+        Expr::Lit(ExprLit { lit, .. }) => match lit {
+            Lit::Str(lit) => Ok(lit.value()),
+            Lit::Char(lit) => Ok(lit.value().to_string()),
+            Lit::Int(lit) => Ok(lit.base10_digits().to_owned()),
+            Lit::Float(lit) => Ok(lit.base10_digits().to_owned()),
+            Lit::Bool(lit) => Ok(lit.value.to_string()),
+            _ => Err(unsupported()),
+        },
+        // Arguments passed through `macro_rules!` fragments arrive in invisible groups.
+        Expr::Group(group) => eval_str(&group.expr, env_vars),
+        Expr::Macro(ExprMacro { mac, .. }) => {
+            let args = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated)?;
+            if is_builtin_macro(&mac.path, "concat") {
+                args.iter().map(|arg| eval_str(arg, env_vars)).collect()
+            } else if is_builtin_macro(&mac.path, "env") {
+                let Some(Expr::Lit(ExprLit {
+                    lit: Lit::Str(name),
+                    ..
+                })) = args.first()
+                else {
+                    return Err(syn::Error::new_spanned(
+                        mac,
+                        "include_packed: expected `env!(\"NAME\")`",
+                    ));
+                };
+                let value = env::var(name.value()).map_err(|_| {
+                    let msg = format!(
+                        "include_packed: environment variable `{}` not defined at compile time",
+                        name.value()
+                    );
+                    syn::Error::new(name.span(), msg)
+                })?;
+                env_vars.push(name.clone());
+                Ok(value)
+            } else {
+                Err(unsupported())
+            }
+        }
+        _ => Err(unsupported()),
+    }
+}
+
+/// Returns whether `path` names the built-in macro `name`, directly or through `core`
+/// or `std`.
+fn is_builtin_macro(path: &syn::Path, name: &str) -> bool {
+    let segments = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>();
+    match segments.as_slice() {
+        [macro_name] => macro_name == name,
+        [krate, macro_name] => (krate == "core" || krate == "std") && macro_name == name,
+        _ => false,
+    }
+}
+
+/// Returns a statement reporting that the build script has not run, unless it has or
+/// the macros embed assets themselves.
+///
+/// Assets then expand to empty placeholders of the right type. The error is left out
+/// while rust-analyzer expands the macros, so that it doesn't flag every call site and
+/// its analysis of the surrounding code carries on.
+fn check_build_script(crate_name: &Ident, span: Span) -> Option<TokenStream2> {
+    if build_script_ran() || cfg!(feature = "fallback-embed") {
+        return None;
+    }
+    let msg = "include_packed: build script has not run. Add one calling `include_packed::Config::build`, or enable the `fallback-embed` feature.";
+    Some(quote_spanned! {span=> #crate_name::__build_script_missing!(#msg); })
+}
+
+/// Prefixes the expression `expansion` with the error of [`check_build_script`], if
+/// any.
+fn with_build_script_check(
+    crate_name: &Ident,
+    span: Span,
+    expansion: TokenStream2,
+) -> TokenStream2 {
+    match check_build_script(crate_name, span) {
+        Some(error) => quote! { { #error #expansion } },
+        None => expansion,
+    }
+}
+
+/// Returns whether the build script has run, which forwards the target architecture.
+fn build_script_ran() -> bool {
+    env::var_os("INCLUDE_PACKED_TARGET_ARCH").is_some()
+}
+
+/// Returns whether the macros embed assets themselves, uncompressed, because the build
+/// script has not run and the `fallback-embed` feature is enabled.
+fn embeds_fallback() -> bool {
+    cfg!(feature = "fallback-embed") && !build_script_ran()
+}
+
+/// The tokens and metadata produced by a backend for a single asset.
+struct Packed {
+    /// An expression of type `&'static [u8]` holding the compressed data.
+    data: TokenStream2,
+    /// Metadata recorded for the asset by the build script, if any.
+    meta: HashMap<String, String>,
+    /// An expression of type `&'static [u8]` holding the asset's zstd dictionary, if any.
+    dictionary: Option<TokenStream2>,
+    /// The asset that the data is a patch against, and its path, if any.
+    base: Option<(Box<Packed>, LitStr)>,
+}
+
+impl Packed {
+    /// Returns an empty asset standing in for one while the build script has not run.
+    fn placeholder() -> Self {
+        Self {
+            data: quote! { &[] },
+            meta: stored_meta(&[]),
+            dictionary: None,
+            base: None,
+        }
+    }
+
+    /// Builds an expression of type `include_packed::runtime::PackedAsset` for the asset.
+    fn to_asset(&self, crate_name: &Ident, lit_str: &LitStr) -> TokenStream2 {
+        let data = &self.data;
+        let mut asset = quote! { #crate_name::runtime::PackedAsset::from_compressed(#data) };
+
+        // Obfuscated assets don't embed their paths.
+        if !self.meta.contains_key("obfuscated") {
+            asset = quote! { #asset.with_path(#lit_str) };
+        }
+
+        if let Some(dictionary) = &self.dictionary {
+            asset = quote! { #asset.with_dictionary(#dictionary) };
+        }
+
+        if let Some((base, path)) = &self.base {
+            let base = base.to_asset(crate_name, path);
+            asset = quote! {
+                #asset.with_delta_base({
+                    static BASE: #crate_name::runtime::PackedAsset = #base;
+                    &BASE
+                })
+            };
+        }
+
+        // Assets packed with zstd, or by older versions, don't need to name the algorithm.
+        if let Some(name) = self.meta.get("algorithm").filter(|name| *name != "zstd") {
+            let algorithm = match algorithm(crate_name, name, lit_str.span()) {
+                Ok(algorithm) => algorithm,
+                Err(err) => return err,
+            };
+            asset = quote! { #asset.with_algorithm(#algorithm) };
+        }
+
+        if let Some(checksum) = self.meta.get("checksum") {
+            match hex_bytes(checksum) {
+                Some(bytes) if bytes.len() == 32 => {
+                    asset = quote! { #asset.with_checksum(&[#(#bytes),*]) };
+                }
+                _ => {
+                    let msg = format!("include_packed: corrupt checksum '{checksum}'");
+                    return syn::Error::new(lit_str.span(), msg).to_compile_error();
+                }
+            }
+        }
+
+        if let Some(signature) = self.meta.get("signature") {
+            let public_key = self.meta.get("public_key").and_then(|key| hex_bytes(key));
+            match (hex_bytes(signature), public_key) {
+                (Some(signature), Some(public_key))
+                    if signature.len() == 64 && public_key.len() == 32 =>
+                {
+                    asset = quote! {
+                        #asset.with_signature({
+                            static SIGNATURE: #crate_name::runtime::Signature =
+                                #crate_name::runtime::Signature::new(
+                                    [#(#public_key),*],
+                                    [#(#signature),*],
+                                );
+                            &SIGNATURE
+                        })
+                    };
+                }
+                _ => {
+                    let msg = format!("include_packed: corrupt signature '{signature}'");
+                    return syn::Error::new(lit_str.span(), msg).to_compile_error();
+                }
+            }
+        }
+
+        if let Some(name) = self.meta.get("cipher") {
+            let cipher = match name.as_str() {
+                "chacha20poly1305" => quote! { ChaCha20Poly1305 },
+                _ => {
+                    let msg = format!("include_packed: unknown cipher '{name}'");
+                    return syn::Error::new(lit_str.span(), msg).to_compile_error();
+                }
+            };
+            asset = quote! { #asset.with_cipher(#crate_name::runtime::Cipher::#cipher) };
+        }
+
+        if let (Some(chunk_size), Some(len), Some(ends)) = (
+            self.meta.get("chunk_size"),
+            self.meta.get("len"),
+            self.meta.get("chunks"),
+        ) {
+            let parsed = (
+                chunk_size.parse::<usize>(),
+                len.parse::<usize>(),
+                ends.split(',')
+                    .map(str::parse::<usize>)
+                    .collect::<Result<Vec<_>, _>>(),
+            );
+            let (Ok(chunk_size), Ok(len), Ok(ends)) = parsed else {
+                let msg = format!(
+                    "include_packed: corrupt chunk index for asset '{}'",
+                    lit_str.value()
+                );
+                return syn::Error::new(lit_str.span(), msg).to_compile_error();
+            };
+            asset = quote! { #asset.with_chunks(#chunk_size, #len, &[#(#ends),*]) };
+        }
+
+        if let Some(align) = self
+            .meta
+            .get("align")
+            .and_then(|align| align.parse::<usize>().ok())
+        {
+            asset = quote! { #asset.with_align(#align) };
+        }
+
+        if let Some(source) = self.meta.get("passthrough") {
+            asset = quote! { #asset.with_passthrough(#source) };
+        }
+
+        if let Some(version) = self.meta.get("crate_version") {
+            let name = self.meta.get("crate_name").map_or("", String::as_str);
+            let timestamp: u64 = self
+                .meta
+                .get("build_timestamp")
+                .and_then(|t| t.parse().ok())
+                .unwrap_or_default();
+            let commit = self
+                .meta
+                .get("git_commit")
+                .map_or_else(|| quote! { None }, |commit| quote! { Some(#commit) });
+            asset = quote! {
+                #asset.with_provenance({
+                    static PROVENANCE: #crate_name::runtime::Provenance =
+                        #crate_name::runtime::Provenance::new(#name, #version, #commit, #timestamp);
+                    &PROVENANCE
+                })
+            };
+        }
+
+        asset
+    }
+}
+
+/// Builds an expression of type `include_packed::runtime::AssetInfo` for the asset at
+/// the logical `path`, from the metadata recorded for it.
+///
+/// Returns `None` if the sizes are missing or malformed.
+fn asset_info(
+    crate_name: &Ident,
+    path: &str,
+    meta: &HashMap<String, String>,
+    span: Span,
+) -> Option<Result<TokenStream2, TokenStream2>> {
+    let len: usize = meta.get("len")?.parse().ok()?;
+    let compressed_len: usize = meta.get("compressed_len")?.parse().ok()?;
+    let algorithm = match algorithm(
+        crate_name,
+        meta.get("algorithm").map_or("zstd", String::as_str),
+        span,
+    ) {
+        Ok(algorithm) => algorithm,
+        Err(err) => return Some(Err(err)),
+    };
+    let modified = match meta
+        .get("modified")
+        .and_then(|secs| secs.parse::<u64>().ok())
+    {
+        Some(secs) => quote! { ::core::option::Option::Some(#secs) },
+        None => quote! { ::core::option::Option::None },
+    };
+    let mime_type = match mime_guess::from_path(path).first_raw() {
+        Some(mime_type) => quote! { ::core::option::Option::Some(#mime_type) },
+        None => quote! { ::core::option::Option::None },
+    };
+    let path = if meta.contains_key("obfuscated") {
+        ""
+    } else {
+        path
+    };
+    Some(Ok(quote! {
+        #crate_name::runtime::AssetInfo::new(
+            #path,
+            #len,
+            #compressed_len,
+            #algorithm,
+            #modified,
+            #mime_type,
+        )
+    }))
+}
+
+/// Builds an expression of type `include_packed::runtime::Algorithm` for the algorithm
+/// the build script recorded under `name`.
+fn algorithm(crate_name: &Ident, name: &str, span: Span) -> Result<TokenStream2, TokenStream2> {
+    let variant = match name {
+        "zstd" => "Zstd",
+        "lz4" => "Lz4",
+        "brotli" => "Brotli",
+        "store" => "Store",
+        _ => {
+            let msg = format!("include_packed: unknown compression algorithm '{name}'");
+            return Err(syn::Error::new(span, msg).to_compile_error());
         }
     };
+    let variant = Ident::new(variant, span);
+    Ok(quote! { #crate_name::runtime::Algorithm::#variant })
+}
+
+/// Decodes a string of hexadecimal digits, returning `None` if it is malformed.
+fn hex_bytes(hex: &str) -> Option<Vec<u8>> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        })
+        .collect()
+}
+
+/// Parses the `key=value` lines of an asset's `.meta` file written by the build script.
+fn parse_meta(contents: &str) -> HashMap<String, String> {
+    contents
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        .collect()
+}
 
-    let compressed_content = zstd::encode_all(&*content, zstd::DEFAULT_COMPRESSION_LEVEL)
-        .expect("zstd compression failed in proc-macro");
-    let compressed_len = compressed_content.len();
+/// Resolves the path under which the `include_packed` crate is available to the caller.
+fn crate_name() -> Ident {
+    use proc_macro_crate::{FoundCrate, crate_name};
 
-    let crate_name = match crate_name("include_packed") {
+    match crate_name("include_packed") {
         Ok(FoundCrate::Name(name)) => Ident::new(&name, Span::call_site()),
         Ok(FoundCrate::Itself) => Ident::new("crate", Span::call_site()),
         Err(_) => Ident::new("include_packed", Span::call_site()), // Fallback
+    }
+}
+
+/// Reads the build script's artifacts for a single asset.
+fn pack(lit_str: &LitStr, crate_name: &Ident) -> Result<Packed, TokenStream2> {
+    let path_str = lit_str.value();
+    let root = AssetRoot::find(&path_str);
+    if embeds_fallback() {
+        return pack_fallback(lit_str, &root);
+    }
+    if !build_script_ran() {
+        return Ok(Packed::placeholder());
+    }
+    if source_file(&root.manifest_dir.join(&path_str)).is_none() {
+        return Err(missing_asset(lit_str, None));
+    }
+    let unique_name = unique_name(&root, &path_str);
+    let meta_path = root.meta_path(&unique_name);
+    let Ok(meta_str) = fs::read_to_string(&meta_path) else {
+        return Err(missing_asset(lit_str, Some(&meta_path)));
     };
+    let meta = parse_meta(&meta_str);
+    if meta.contains_key("excluded") {
+        let msg = format!(
+            "include_packed: asset '{path_str}' was excluded from packing by the build script's `Config::include` or `Config::exclude` patterns"
+        );
+        return Err(syn::Error::new(lit_str.span(), msg).to_compile_error());
+    }
 
-    quote! {
-        {
-            const COMPRESSED_DATA: [u8; #compressed_len] = [#(#compressed_content),*];
-            #crate_name::decompress(&COMPRESSED_DATA)
+    let compressed_len: usize = meta
+        .get("compressed_len")
+        .and_then(|len| len.parse().ok())
+        .unwrap_or_else(|| {
+            panic!(
+                "include_packed: corrupt .meta file at '{}'",
+                meta_path.display()
+            )
+        });
+
+    let align: usize = meta
+        .get("align")
+        .map_or(Ok(1), |align| align.parse())
+        .unwrap_or_else(|_| {
+            panic!(
+                "include_packed: corrupt .meta file at '{}'",
+                meta_path.display()
+            )
+        });
+
+    // The symbol may differ from the metadata file's name, e.g. when namespaced.
+    let symbol = meta.get("symbol").cloned().unwrap_or(unique_name);
+    let data = if meta.contains_key("url") {
+        let msg = format!(
+            "include_packed: asset '{path_str}' is served over HTTP; use `include_packed_fetch!` to access it"
+        );
+        syn::Error::new(lit_str.span(), msg).to_compile_error()
+    } else if let Some(bundle) = meta.get("bundle") {
+        let (Some(offset), Some(bundle_len)) = (
+            meta.get("bundle_offset")
+                .and_then(|offset| offset.parse::<usize>().ok()),
+            meta.get("bundle_len")
+                .and_then(|len| len.parse::<usize>().ok()),
+        ) else {
+            panic!(
+                "include_packed: corrupt .meta file at '{}'",
+                meta_path.display()
+            );
+        };
+        let bundle = linked_data(crate_name, &root, bundle, bundle_len, align);
+        let end = offset + compressed_len;
+        quote! { &(#bundle)[#offset..#end] }
+    } else {
+        linked_data(crate_name, &root, &symbol, compressed_len, align)
+    };
+
+    let dictionary = match (meta.get("dictionary"), meta.get("dictionary_len")) {
+        (Some(symbol), Some(len)) => {
+            let len: usize = len.parse().unwrap_or_else(|_| {
+                panic!(
+                    "include_packed: corrupt .meta file at '{}'",
+                    meta_path.display()
+                )
+            });
+            Some(linked_data(crate_name, &root, symbol, len, 1))
+        }
+        _ => None,
+    };
+
+    // Bases are never patches themselves, so this recurses at most once.
+    let base = match meta.get("delta_base") {
+        Some(base) => {
+            let path = LitStr::new(base, lit_str.span());
+            Some((Box::new(pack(&path, crate_name)?), path))
+        }
+        None => None,
+    };
+
+    Ok(Packed {
+        data,
+        meta,
+        dictionary,
+        base,
+    })
+}
+
+/// Reports that no asset was packed at `lit_str`, whose metadata was expected at
+/// `meta_path` if the file exists, along with the likely cause and the packed asset the
+/// caller most likely meant.
+fn missing_asset(lit_str: &LitStr, meta_path: Option<&Path>) -> TokenStream2 {
+    let path_str = lit_str.value();
+    let sources = packed_sources();
+    let mut msg = match meta_path {
+        Some(meta_path) => format!(
+            "include_packed: asset '{path_str}' was not packed by the build script\nexpected metadata at: {}",
+            meta_path.display()
+        ),
+        None => format!(
+            "include_packed: no file at '{path_str}', relative to the crate root (`CARGO_MANIFEST_DIR`)"
+        ),
+    };
+
+    // Packed assets are keyed by their modification time, so metadata for the same path
+    // means the file changed after the build script ran.
+    if meta_path.is_some() && sources.contains(&path_str) {
+        msg.push_str(
+            "\nhelp: the file changed after the build script last ran; rebuild to repack it",
+        );
+        return syn::Error::new(lit_str.span(), msg).to_compile_error();
+    }
+    let root = AssetRoot::find(&path_str);
+    if let Some(dir) = root.manifest_dir.join(&path_str).parent()
+        && meta_path.is_some()
+        && directory_changed(&root.fingerprints(), dir)
+    {
+        return stale_directory(dir, lit_str);
+    }
+
+    let trimmed = path_str.trim_start_matches(['.', '/']);
+    let rerooted = sources.iter().find(|source| {
+        source.ends_with(&format!("/{trimmed}")) || trimmed.ends_with(&format!("/{source}"))
+    });
+    if let Some(source) = rerooted {
+        msg.push_str(&format!(
+            "\nhelp: asset paths are relative to the crate root, not the source file; did you mean '{source}'?"
+        ));
+    } else if let Some(source) = nearest(&path_str, &sources) {
+        msg.push_str(&format!("\nhelp: did you mean '{source}'?"));
+    } else if meta_path.is_some() {
+        msg.push_str(
+            "\nhelp: pack the file's directory with `include_packed::Config` in the build script; files added after it last ran are packed once it runs again",
+        );
+    }
+    syn::Error::new(lit_str.span(), msg).to_compile_error()
+}
+
+/// Returns the logical paths of the assets packed for the crate being compiled,
+/// including shared ones, in sorted order.
+fn packed_sources() -> Vec<String> {
+    let mut sources = Vec::new();
+    for root in AssetRoot::all() {
+        let Ok(entries) = fs::read_dir(&root.out_dir) else {
+            continue;
+        };
+        for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+            if path.extension().is_none_or(|extension| extension != "meta") {
+                continue;
+            }
+            let Ok(contents) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let mut meta = parse_meta(&contents);
+            if !meta.contains_key("excluded")
+                && let Some(source) = meta.remove("source")
+            {
+                sources.push(source);
+            }
         }
     }
+    sources.sort_unstable();
+    sources.dedup();
+    sources
 }
 
-/// Native implementation: Uses build script artifacts (.len file and linked .o file).
-fn get_tokens_native(lit_str: &LitStr) -> TokenStream2 {
-    use proc_macro_crate::{FoundCrate, crate_name};
-    use proc_macro2::Span;
+/// Returns the candidate closest to `target` by edit distance, if it is close enough to
+/// be a typo.
+fn nearest<'a>(target: &str, candidates: &'a [String]) -> Option<&'a str> {
+    let max_distance = (target.chars().count() / 3).max(1);
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(target, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+/// Returns the Levenshtein distance between `a` and `b`, in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substituted = diagonal + usize::from(a != *b);
+            diagonal = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Embeds the asset at `lit_str` uncompressed with `include_bytes!`, for builds in
+/// which the build script has not run.
+fn pack_fallback(lit_str: &LitStr, root: &AssetRoot) -> Result<Packed, TokenStream2> {
+    let path = root.manifest_dir.join(lit_str.value());
+    let content = fs::read(&path).map_err(|err| {
+        let msg = format!(
+            "include_packed: could not read asset '{}': {err}",
+            path.display()
+        );
+        syn::Error::new(lit_str.span(), msg).to_compile_error()
+    })?;
+    let path = path.to_string_lossy();
+    Ok(Packed {
+        data: quote! { ::core::include_bytes!(#path) as &'static [u8] },
+        meta: stored_meta(&content),
+        dictionary: None,
+        base: None,
+    })
+}
+
+/// Returns the metadata of an asset stored uncompressed with the given `content`.
+fn stored_meta(content: &[u8]) -> HashMap<String, String> {
+    HashMap::from([
+        ("len".to_owned(), content.len().to_string()),
+        ("compressed_len".to_owned(), content.len().to_string()),
+        ("algorithm".to_owned(), "store".to_owned()),
+        (
+            "utf8".to_owned(),
+            std::str::from_utf8(content).is_ok().to_string(),
+        ),
+    ])
+}
+
+/// A crate whose build script packs assets for the crate being compiled, or a directory
+/// set with `Config::root` whose assets it packs.
+struct AssetRoot {
+    /// The crate's root directory, or the directory set with `Config::root`, which asset
+    /// paths are relative to.
+    manifest_dir: PathBuf,
+    /// The build script's `OUT_DIR`, holding the assets' metadata and artifacts.
+    out_dir: PathBuf,
+    /// Whether a dependency shared the assets, instead of this crate packing them.
+    shared: bool,
+}
+
+impl AssetRoot {
+    /// Returns the crate being compiled, followed by the roots set with `Config::root`
+    /// and the dependencies whose assets the build script's `use_shared_assets` calls
+    /// made available.
+    fn all() -> Vec<Self> {
+        let manifest_dir = env::var("CARGO_MANIFEST_DIR")
+            .expect("CARGO_MANIFEST_DIR is not set; this macro must be run by Cargo.");
+        // Crates without a build script have no `OUT_DIR`, and nothing is read from it.
+        let out_dir = env::var_os("OUT_DIR").map_or_else(
+            || {
+                assert!(
+                    !build_script_ran(),
+                    "OUT_DIR is not set; this macro must be run by Cargo."
+                );
+                PathBuf::new()
+            },
+            PathBuf::from,
+        );
+        let mut roots = vec![Self {
+            manifest_dir: PathBuf::from(manifest_dir),
+            out_dir: out_dir.clone(),
+            shared: false,
+        }];
+        // Roots set with `Config::root` hold assets packed by this crate's build script.
+        let extra_roots = env::var("INCLUDE_PACKED_ROOTS").unwrap_or_default();
+        for root in extra_roots.split('\x1f').filter(|root| !root.is_empty()) {
+            roots.push(Self {
+                manifest_dir: PathBuf::from(root),
+                out_dir: out_dir.clone(),
+                shared: false,
+            });
+        }
+        let shared = env::var("INCLUDE_PACKED_SHARED").unwrap_or_default();
+        for entry in shared.split('\x1f') {
+            if let Some((manifest_dir, out_dir)) = entry.split_once('\x1e') {
+                roots.push(Self {
+                    manifest_dir: PathBuf::from(manifest_dir),
+                    out_dir: PathBuf::from(out_dir),
+                    shared: true,
+                });
+            }
+        }
+        roots
+    }
+
+    /// Returns the first crate in which `path_str` exists, or the crate being compiled
+    /// if there is none.
+    fn find(path_str: &str) -> Self {
+        let mut roots = Self::all();
+        let index = roots
+            .iter()
+            .position(|root| source_file(&root.manifest_dir.join(path_str)).is_some())
+            .unwrap_or(0);
+        roots.swap_remove(index)
+    }
+
+    /// Returns the fingerprints the build script recorded for the directories it walked,
+    /// by canonical path.
+    fn fingerprints(&self) -> HashMap<PathBuf, u64> {
+        let contents = fs::read_to_string(self.out_dir.join("include_packed.fingerprints"))
+            .unwrap_or_default();
+        contents
+            .lines()
+            .filter_map(|line| {
+                let (fingerprint, dir) = line.split_once('\t')?;
+                Some((
+                    PathBuf::from(dir),
+                    u64::from_str_radix(fingerprint, 16).ok()?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns the path of the metadata the build script wrote for the asset named
+    /// `unique_name`.
+    fn meta_path(&self, unique_name: &str) -> PathBuf {
+        self.out_dir.join(format!("{unique_name}.meta"))
+    }
+}
+
+/// Returns whether the build script skipped the asset at `path_str`, because of its
+/// include or exclude patterns, or because it was reached through a symbolic link
+/// (`linked`) that wasn't followed.
+fn is_skipped(root: &AssetRoot, path_str: &str, linked: bool) -> bool {
+    if !build_script_ran() {
+        return false;
+    }
+    match fs::read_to_string(root.meta_path(&unique_name(root, path_str))) {
+        Ok(meta) => parse_meta(&meta).contains_key("excluded"),
+        Err(_) => linked,
+    }
+}
+
+/// Returns the name the build script derived for the asset at `path_str`, relative to
+/// the root of the crate `root`, from its key and modification time.
+///
+/// # Panics
+///
+/// Panics if the asset doesn't exist.
+fn unique_name(root: &AssetRoot, path_str: &str) -> String {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
-    use syn::Ident;
 
-    let out_dir =
-        env::var("OUT_DIR").expect("OUT_DIR is not set; this macro must be run by Cargo.");
-
-    let path_str = lit_str.value();
-    let manifest_dir = env::var("CARGO_MANIFEST_DIR")
-        .expect("CARGO_MANIFEST_DIR is not set; this macro must be run by Cargo.");
-    let mut path = PathBuf::from(&manifest_dir);
-    path.push(&path_str);
+    let path = root.manifest_dir.join(path_str);
 
-    let canonical_path = path
-        .canonicalize()
-        .unwrap_or_else(|e| panic!("Could not find file '{}': {e}", path.display()));
-    let path_for_hashing = PathBuf::from(&path_str);
-    let metadata = fs::metadata(&canonical_path).unwrap_or_else(|e| {
-        panic!(
-            "Could not read metadata for '{}': {e}",
-            canonical_path.display()
-        )
-    });
+    let (canonical_path, metadata) =
+        source_file(&path).unwrap_or_else(|| panic!("Could not find file '{}'", path.display()));
+    let path_for_hashing = asset_key(&canonical_path, &root.manifest_dir);
     let modified_time = metadata.modified().unwrap_or_else(|e| {
         panic!(
             "Could not read modification time for '{}': {e}",
@@ -130,37 +1979,106 @@ fn get_tokens_native(lit_str: &LitStr) -> TokenStream2 {
     let mut hasher = DefaultHasher::new();
     path_for_hashing.hash(&mut hasher);
     modified_time.hash(&mut hasher);
-    let unique_name = format!("include_packed_{:016x}", hasher.finish());
+    format!("include_packed_{:016x}", hasher.finish())
+}
 
-    let len_path = PathBuf::from(&out_dir).join(format!("{unique_name}.len"));
-    let Ok(len_str) = fs::read_to_string(&len_path) else {
-        let msg = format!(
-            "include_packed: failed to read .len file for asset at '{path_str}'\nexpected at: {}",
-            len_path.display()
-        );
-        return syn::Error::new(lit_str.span(), msg).to_compile_error();
-    };
+/// Returns the canonical path and metadata of the file at `path`.
+///
+/// For a member of an archive packed with `Config::from_archive`, i.e. a path below a
+/// file, this returns the archive's canonical path joined with the member's path, and
+/// the archive's metadata, like the build script derives them.
+fn source_file(path: &Path) -> Option<(PathBuf, fs::Metadata)> {
+    if let Ok(canonical_path) = path.canonicalize() {
+        let metadata = fs::metadata(&canonical_path).ok()?;
+        return Some((canonical_path, metadata));
+    }
+    let archive = path
+        .ancestors()
+        .skip(1)
+        .find(|ancestor| ancestor.is_file())?;
+    let member = path.strip_prefix(archive).ok()?;
+    let canonical_archive = archive.canonicalize().ok()?;
+    let metadata = fs::metadata(&canonical_archive).ok()?;
+    Some((canonical_archive.join(member), metadata))
+}
 
-    let compressed_len: usize = len_str.parse().unwrap_or_else(|_| {
-        panic!(
-            "include_packed: corrupt .len file at '{}'",
-            len_path.display()
-        )
-    });
+/// Builds an expression of type `&'static [u8]` for data the build script of the crate
+/// `root` linked into the binary under the name `symbol`, aligned to `align` bytes.
+fn linked_data(
+    crate_name: &Ident,
+    root: &AssetRoot,
+    symbol: &str,
+    len: usize,
+    align: usize,
+) -> TokenStream2 {
+    // On Wasm and targets without object file support, the build script wrote the raw
+    // data to a file instead of an object file.
+    if env::var_os("INCLUDE_PACKED_INCLUDE_BYTES").is_some()
+        || env::var("INCLUDE_PACKED_TARGET_ARCH").as_deref() == Ok("wasm32")
+    {
+        let bytes = if root.shared {
+            let path = root.out_dir.join(format!("{symbol}.bin"));
+            let path = path.to_string_lossy();
+            quote! { ::core::include_bytes!(#path) }
+        } else {
+            let file_name = format!("/{symbol}.bin");
+            quote! { ::core::include_bytes!(::core::concat!(::core::env!("OUT_DIR"), #file_name)) }
+        };
+        if align <= 1 {
+            return quote! { #bytes as &'static [u8] };
+        }
+        // `include_bytes!` doesn't align its data, so it is wrapped in an aligned type.
+        let align = Literal::usize_unsuffixed(align);
+        return quote! {
+            {
+                #[repr(C, align(#align))]
+                struct Aligned([u8; #len]);
+                static DATA: Aligned = Aligned(*#bytes);
+                &DATA.0 as &'static [u8]
+            }
+        };
+    }
 
-    let crate_name = match crate_name("include_packed") {
-        Ok(FoundCrate::Name(name)) => Ident::new(&name, Span::call_site()),
-        Ok(FoundCrate::Itself) => Ident::new("crate", Span::call_site()),
-        Err(_) => Ident::new("include_packed", Span::call_site()), // Fallback
-    };
+    // The build script embedded the data as a Windows resource instead of a symbol.
+    // Shared assets are always linked as symbols.
+    if env::var_os("INCLUDE_PACKED_WINDOWS_RESOURCES").is_some() && !root.shared {
+        return quote! { #crate_name::runtime::windows_resource(#symbol) };
+    }
 
     quote! {
         {
             unsafe extern "C" {
-                #[link_name = #unique_name]
-                static STATIC: [u8; #compressed_len];
+                #[link_name = #symbol]
+                static STATIC: [u8; #len];
             }
-            #crate_name::decompress(unsafe { &STATIC })
+            unsafe { &STATIC }
+        }
+    }
+}
+
+/// Derives the key identifying an asset in symbol names from its canonical path.
+///
+/// This must match the derivation in the build script: assets inside the crate are
+/// keyed by their path relative to `CARGO_MANIFEST_DIR`, and assets elsewhere by their
+/// absolute path after applying the `--remap-path-prefix` rules forwarded by the build
+/// script.
+fn asset_key(canonical_path: &Path, manifest_dir: &Path) -> PathBuf {
+    let manifest_dir = manifest_dir
+        .canonicalize()
+        .unwrap_or_else(|_| manifest_dir.to_path_buf());
+    if let Ok(relative) = canonical_path.strip_prefix(&manifest_dir) {
+        return relative.to_path_buf();
+    }
+
+    let rules = env::var("INCLUDE_PACKED_REMAP_PATH_PREFIX").unwrap_or_default();
+    // As in rustc, later rules take precedence over earlier ones.
+    for rule in rules.split('\x1f').rev() {
+        let Some((from, to)) = rule.rsplit_once('=') else {
+            continue;
+        };
+        if let Ok(rest) = canonical_path.strip_prefix(from) {
+            return Path::new(to).join(rest);
         }
     }
+    canonical_path.to_path_buf()
 }